@@ -7,8 +7,9 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use server_fn::{
     client::{browser::BrowserClient, Client},
     codec::{
-        Encoding, FromReq, FromRes, GetUrl, IntoReq, IntoRes, MultipartData,
-        MultipartFormData, Rkyv, SerdeLite, StreamingText, TextStream,
+        Encoding, FromReq, FromRes, GetUrl, IntoReq, IntoRes, Json,
+        MultipartData, MultipartFormData, Rkyv, SerdeLite, StreamingText,
+        TextStream, Websocket, WebsocketStream,
     },
     request::{browser::BrowserRequest, ClientReq, Req},
     response::{browser::BrowserResponse, ClientRes, Res},
@@ -59,6 +60,7 @@ pub fn HomePage() -> impl IntoView {
         <FileUpload/>
         <FileUploadWithProgress/>
         <FileWatcher/>
+        <WebsocketExample/>
         <CustomEncoding/>
         <CustomClientExample/>
     }
@@ -773,6 +775,66 @@ pub async fn why_not(
     }))
 }
 
+/// A websocket server function keeps a single connection open, over which the client sends a
+/// stream of messages and the server sends back a stream of responses. Each side can send
+/// messages whenever it likes, so this works well for things like chat or collaborative editing.
+///
+/// The connection stays open until the stream returned by the server function ends.
+#[component]
+pub fn WebsocketExample() -> impl IntoView {
+    use futures::channel::mpsc;
+
+    // with a websocket input, the output uses the same encoding by default
+    #[server(input = Websocket<Json>)]
+    pub async fn shout_back(
+        input: WebsocketStream<String>,
+    ) -> Result<WebsocketStream<String>, ServerFnError> {
+        let output = input
+            .into_inner()
+            .map(|msg| msg.map(|msg| msg.to_ascii_uppercase()));
+        Ok(WebsocketStream::new(output))
+    }
+
+    let (tx, rx) = mpsc::unbounded();
+    let (messages, set_messages) = create_signal(Vec::new());
+
+    // open the connection once, in the browser
+    if cfg!(feature = "hydrate") {
+        spawn_local(async move {
+            match shout_back(rx.into()).await {
+                Ok(output) => {
+                    let mut output = output.into_inner();
+                    while let Some(msg) = output.next().await {
+                        let msg = msg.unwrap_or_else(|e| e.to_string());
+                        set_messages.update(|messages| messages.push(msg));
+                    }
+                }
+                Err(e) => logging::warn!("{e}"),
+            }
+        });
+    }
+
+    view! {
+        <h3>Websockets</h3>
+        <p>"Every change to the input is sent over a websocket, and shouted back by the server."</p>
+        <input
+            type="text"
+            on:input=move |ev| {
+                _ = tx.unbounded_send(event_target_value(&ev));
+            }
+        />
+        <ul>
+            {move || {
+                messages
+                    .get()
+                    .into_iter()
+                    .map(|msg| view! { <li>{msg}</li> })
+                    .collect_view()
+            }}
+        </ul>
+    }
+}
+
 #[component]
 pub fn CustomEncoding() -> impl IntoView {
     let input_ref = NodeRef::<Input>::new();
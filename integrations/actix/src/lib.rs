@@ -25,7 +25,9 @@ use leptos_meta::*;
 use leptos_router::*;
 use parking_lot::RwLock;
 use regex::Regex;
-use server_fn::{redirect::REDIRECT_HEADER, request::actix::ActixRequest};
+use server_fn::{
+    csrf::ClientSecret, redirect::REDIRECT_HEADER, request::actix::ActixRequest,
+};
 use std::{
     fmt::{Debug, Display},
     future::Future,
@@ -208,6 +210,10 @@ pub fn handle_server_fns() -> Route {
 /// method, while subsequent calls from the client are handled by the server function handler.
 /// The same context needs to be provided to both handlers.
 ///
+/// [Batches](server_fn::batch) of server function calls are only supported by
+/// the Axum integration. They are answered with `501 Not Implemented`, and the
+/// client then sends each call on its own.
+///
 /// ## Provided Context Types
 /// This function always provides context values including the following types:
 /// - [ResponseOptions]
@@ -222,6 +228,9 @@ pub fn handle_server_fns_with_context(
             let additional_context = additional_context.clone();
 
             let path = req.path();
+            if path == server_fn::batch::BATCH_PATH {
+                return server_fn::actix::batch_not_supported();
+            }
             if let Some(mut service) =
                 server_fn::actix::get_server_fn_service(path)
             {
@@ -695,13 +704,46 @@ fn provide_contexts(req: &HttpRequest, res_options: ResponseOptions) {
     let integration = ServerIntegration { path };
     provide_context(RouterIntegrationContext::new(integration));
     provide_context(MetaContext::new());
+    provide_client_secret(req, &res_options);
     provide_context(res_options);
+    provide_context(request_cookies(req));
     provide_context(req.clone());
     provide_server_redirect(redirect);
     #[cfg(feature = "nonce")]
     leptos::nonce::provide_nonce();
 }
 
+/// Collects the cookies sent with the request, so that signals saved in cookies
+/// can be rendered with their saved values.
+fn request_cookies(req: &HttpRequest) -> RequestCookies {
+    RequestCookies::new(cookie_header(req))
+}
+
+/// Joins the `Cookie` headers of the request into one.
+fn cookie_header(req: &HttpRequest) -> String {
+    req.headers()
+        .get_all(header::COOKIE)
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Provides the secret that the CSRF tokens of forms are tied to, if the
+/// `csrf` feature of `server_fn` is enabled, and sets the cookie that holds it
+/// if the client does not have one yet.
+fn provide_client_secret(req: &HttpRequest, res_options: &ResponseOptions) {
+    let Some(secret) = ClientSecret::from_cookies(&cookie_header(req)) else {
+        return;
+    };
+    if let Some(cookie) = secret
+        .set_cookie()
+        .and_then(|cookie| HeaderValue::from_str(&cookie).ok())
+    {
+        res_options.append_header(header::SET_COOKIE, cookie);
+    }
+    provide_context(secret);
+}
+
 fn leptos_corrected_path(req: &HttpRequest) -> String {
     let path = req.path();
     let query = req.query_string();
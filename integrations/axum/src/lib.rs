@@ -55,6 +55,7 @@ use leptos_router::*;
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use server_fn::{
+    csrf::ClientSecret,
     error::{NoCustomError, ServerFnErrorSerde},
     redirect::REDIRECT_HEADER,
 };
@@ -270,6 +271,20 @@ async fn handle_server_fns_inner(
     additional_context: impl Fn() + 'static + Clone + Send,
     req: Request<Body>,
 ) -> impl IntoResponse {
+    // each call in a batch is handled as if it had been sent on its own
+    if req.uri().path() == server_fn::batch::BATCH_PATH {
+        return server_fn::axum::handle_batch(req, move |req| {
+            Box::pin(handle_server_fn_call(additional_context.clone(), req))
+        })
+        .await;
+    }
+    handle_server_fn_call(additional_context, req).await
+}
+
+async fn handle_server_fn_call(
+    additional_context: impl Fn() + 'static + Clone + Send,
+    req: Request<Body>,
+) -> Response<Body> {
     use server_fn::middleware::Service;
 
     let (tx, rx) = futures::channel::oneshot::channel();
@@ -896,6 +911,8 @@ fn provide_contexts(
     let integration = ServerIntegration { path };
     provide_context(RouterIntegrationContext::new(integration));
     provide_context(MetaContext::new());
+    provide_context(request_cookies(&parts));
+    provide_client_secret(&parts, &default_res_options);
     provide_context(parts);
     provide_context(default_res_options);
     provide_server_redirect(redirect);
@@ -903,6 +920,39 @@ fn provide_contexts(
     leptos::nonce::provide_nonce();
 }
 
+/// Collects the cookies sent with the request, so that signals saved in cookies
+/// can be rendered with their saved values.
+fn request_cookies(parts: &Parts) -> RequestCookies {
+    RequestCookies::new(cookie_header(parts))
+}
+
+/// Joins the `Cookie` headers of the request into one.
+fn cookie_header(parts: &Parts) -> String {
+    parts
+        .headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Provides the secret that the CSRF tokens of forms are tied to, if the
+/// `csrf` feature of `server_fn` is enabled, and sets the cookie that holds it
+/// if the client does not have one yet.
+fn provide_client_secret(parts: &Parts, res_options: &ResponseOptions) {
+    let Some(secret) = ClientSecret::from_cookies(&cookie_header(parts)) else {
+        return;
+    };
+    if let Some(cookie) = secret
+        .set_cookie()
+        .and_then(|cookie| HeaderValue::from_str(&cookie).ok())
+    {
+        res_options.append_header(header::SET_COOKIE, cookie);
+    }
+    provide_context(secret);
+}
+
 /// Returns an Axum [Handler](axum::handler::Handler) that listens for a `GET` request and tries
 /// to route it using [leptos_router], asynchronously rendering an HTML page after all
 /// `async` [Resource](leptos::Resource)s have loaded.
@@ -89,6 +89,11 @@
 //! - `tracing` Adds additional support for [`tracing`](https://docs.rs/tracing/latest/tracing/) to components.
 //! - `default-tls` Use default native TLS support. (Only applies when using server functions with a non-WASM client like a desktop app.)
 //! - `rustls` Use `rustls`. (Only applies when using server functions with a non-WASM client like a desktop app.)
+//! - `openapi` Registers every server function for an OpenAPI document, which can be generated with
+//!   [`openapi_spec`](server_fn::openapi::openapi_spec). (Implies `ssr`.)
+//! - `csrf` Protects server functions that are called by HTML forms against cross-site request forgery,
+//!   and adds a token to each [`ActionForm`](https://docs.rs/leptos_router/latest/leptos_router/fn.ActionForm.html).
+//!   See [`server_fn::csrf`]. (Enable it for both the server and the client.)
 //! - `template_macro` Enables the [`template!`](leptos_macro::template) macro, which offers faster DOM node creation for some use cases in `csr`.
 //!
 //! **Important Note:** You must enable one of `csr`, `hydrate`, or `ssr` to tell Leptos
@@ -180,7 +185,9 @@ pub mod error {
 pub use leptos_macro::template;
 #[cfg(not(all(target_arch = "wasm32", feature = "template_macro")))]
 pub use leptos_macro::view as template;
-pub use leptos_macro::{component, island, slice, slot, view, Params};
+pub use leptos_macro::{
+    component, island, slice, slot, view, Params, Schema, Store,
+};
 cfg_if::cfg_if!(
     if #[cfg(feature="spin")] {
         pub use leptos_spin_macro::server;
@@ -191,8 +198,8 @@ cfg_if::cfg_if!(
 pub use leptos_reactive::*;
 pub use leptos_server::{
     self, create_action, create_multi_action, create_server_action,
-    create_server_multi_action, Action, MultiAction, ServerFnError,
-    ServerFnErrorErr,
+    create_server_multi_action, Action, MultiAction, QueueOptions,
+    ServerFnError, ServerFnErrorErr, Submission,
 };
 pub use server_fn::{self, ServerFn as _};
 mod error_boundary;
@@ -187,6 +187,8 @@ pub fn render_to_stream_with_prefix_undisposed_with_context_and_block_replacemen
 
     // create the runtime
     let runtime = create_runtime();
+    // later values of streaming resources are sent at the end of the stream
+    SharedContext::enable_stream_resource_updates();
 
     // Add additional context items
     additional_context();
@@ -276,7 +278,20 @@ pub fn render_to_stream_with_prefix_undisposed_with_context_and_block_replacemen
             }
         },
     )
-    .chain(ooo_body_stream_recurse(nonce_str, fragments, serializers));
+    .chain(ooo_body_stream_recurse(
+        nonce_str.clone(),
+        fragments,
+        serializers,
+    ))
+    // later values of streaming resources, once every resource has sent its
+    // first value
+    .chain(
+        futures::stream::once(async move {
+            let updates = SharedContext::stream_resource_updates();
+            render_serializers(nonce_str, updates)
+        })
+        .flatten(),
+    );
 
     (stream, runtime)
 }
@@ -702,7 +717,7 @@ pub(crate) fn to_kebab_case(name: &str) -> String {
 )]
 pub(crate) fn render_serializers(
     nonce_str: String,
-    serializers: FuturesUnordered<PinnedFuture<(ResourceId, String)>>,
+    serializers: impl Stream<Item = (ResourceId, String)>,
 ) -> impl Stream<Item = String> {
     serializers.map(move |(id, json)| {
         let id = serde_json::to_string(&id).unwrap();
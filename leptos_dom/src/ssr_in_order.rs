@@ -95,6 +95,8 @@ pub fn render_to_stream_in_order_with_prefix_undisposed_with_context(
 
     // create the runtime
     let runtime = create_runtime();
+    // later values of streaming resources are sent at the end of the stream
+    SharedContext::enable_stream_resource_updates();
 
     // add additional context
     additional_context();
@@ -149,7 +151,14 @@ pub fn render_to_stream_in_order_with_prefix_undisposed_with_context(
     .chain(
         futures::stream::once(async move {
             let serializers = SharedContext::serialization_resolvers();
-            render_serializers(nonce_str, serializers)
+            render_serializers(nonce_str.clone(), serializers).chain(
+                // later values of streaming resources
+                futures::stream::once(async move {
+                    let updates = SharedContext::stream_resource_updates();
+                    render_serializers(nonce_str, updates)
+                })
+                .flatten(),
+            )
         })
         .flatten(),
     );
@@ -39,6 +39,7 @@ use view::{client_template::render_template, render_view};
 mod component;
 mod slice;
 mod slot;
+mod store;
 
 /// The `view` macro uses RSX (like JSX, but Rust!) It follows most of the
 /// same rules as HTML, with the following differences:
@@ -886,6 +887,22 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 ///   to convert from the argument type to the server function type, and vice versa, allowing you to convert
 ///   between them easily. Setting `impl_from` to `false` disables this, which can be necessary for argument types
 ///   for which this would create a conflicting implementation. (defaults to `true`)
+/// - `typed_errors`: whether errors are sent to the client encoded with the `output` encoding, which
+///   keeps the structure of a custom error type, rather than as text. The custom error type must be
+///   encodable with the `output` encoding, and implement
+///   [`ErrorStatus`](../server_fn/error/trait.ErrorStatus.html) to choose the status code of each
+///   error. (defaults to `false`)
+/// - `retries` and `timeout_ms`: how many times the client retries a failed call, and how many
+///   milliseconds it waits for a response to each attempt (default to the policy set with
+///   [`set_client_policy`](../server_fn/client/fn.set_client_policy.html), which never retries
+///   or times out unless it has been changed)
+/// - `client_policy`: an expression that returns the full
+///   [`ClientPolicy`](../server_fn/client/struct.ClientPolicy.html) for this server fn, including
+///   its backoff and the response statuses it retries on. `retries` and `timeout_ms` override it.
+/// - `cache`: an expression that returns a
+///   [`CachePolicy`](../server_fn/cache/struct.CachePolicy.html), which lets the response be
+///   cached and revalidated with HTTP caching headers. This can only be used with an `input`
+///   encoding that uses `GET`, like `GetUrl`, and a server fn with a policy is never batched.
 ///
 /// ```rust,ignore
 /// #[server(
@@ -929,6 +946,25 @@ pub fn slot(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
 /// pub async fn with_default_value(#[server(default)] values: Vec<u32>) /* etc. */
 /// ```
 ///
+/// ## Client Interceptors
+///
+/// Individual server functions can be given client-side
+/// [`Interceptor`](../server_fn/client/trait.Interceptor.html)s with `#[interceptor(...)]`, which
+/// wrap each call made from the client, after any interceptors added to the client itself.
+/// ```rust,ignore
+/// #[server]
+/// #[interceptor(LogTimings)]
+/// pub async fn my_server_fn() -> Result<(), ServerFnError> /* etc. */
+/// ```
+///
+/// ## Batching
+///
+/// Once [batching](../server_fn/batch/index.html) has been turned on with
+/// `server_fn::batch::set_batching(true)`, calls that are made at the same time are sent to the
+/// server in a single request. This applies to every server function that does not stream its
+/// arguments or output, take multipart form data, or use a websocket. Batches are currently
+/// handled by the Axum integration only.
+///
 /// ## Important Notes
 /// - **Server functions must be `async`.** Even if the work being done inside the function body
 ///   can run synchronously on the server, from the client’s perspective it involves an asynchronous
@@ -989,6 +1025,75 @@ pub fn params_derive(
     }
 }
 
+/// Derives reactive access to each field of a struct inside a
+/// [`Store`](../leptos/struct.Store.html).
+///
+/// This generates a trait named `{StructName}StoreFields`, with a method for
+/// each field of the struct, which is implemented for the store itself and for
+/// any field of a store that contains the struct. Each field can be read and
+/// updated like a signal, and only notifies the subscribers of that field (and
+/// of the values that contain it) when it changes.
+///
+/// ```rust
+/// # use leptos::*;
+/// # let runtime = create_runtime();
+/// #[derive(Store, Clone, Default)]
+/// struct Todos {
+///     user: String,
+///     todos: Vec<Todo>,
+/// }
+///
+/// #[derive(Store, Clone, Default)]
+/// struct Todo {
+///     label: String,
+///     completed: bool,
+/// }
+///
+/// let store = Store::new(Todos {
+///     user: "Bob".to_string(),
+///     todos: vec![Todo {
+///         label: "Write docs".to_string(),
+///         completed: false,
+///     }],
+/// });
+///
+/// // only reruns when `user` changes
+/// let user = create_memo(move |_| store.user().get());
+///
+/// store.todos().at(0).completed().set(true);
+/// assert!(store.todos().at(0).completed().get());
+/// assert_eq!(user.get(), "Bob");
+/// # runtime.dispose();
+/// ```
+#[proc_macro_derive(Store)]
+pub fn store_derive(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    match syn::parse(input) {
+        Ok(ast) => store::store_impl(&ast).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Derives [`Schema`](../server_fn/openapi/trait.Schema.html), which
+/// describes the type in the OpenAPI document generated for server functions.
+/// This does nothing unless the `openapi` feature is enabled.
+///
+/// The schema follows the way the type is serialized by `serde`, including
+/// its `rename`, `rename_all`, `default` and `skip` attributes.
+#[proc_macro_derive(Schema, attributes(serde))]
+pub fn schema_derive(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    match server_fn_macro::schema_derive_impl(
+        input.into(),
+        Some(syn::parse_quote!(::leptos::server_fn)),
+    ) {
+        Err(e) => e.to_compile_error().into(),
+        Ok(s) => s.into(),
+    }
+}
+
 pub(crate) fn attribute_value(attr: &KeyedAttribute) -> &syn::Expr {
     match attr.value() {
         Some(value) => value,
@@ -0,0 +1,109 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Data, DeriveInput, Fields, GenericParam};
+
+pub fn store_impl(ast: &DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            fields => {
+                return syn::Error::new(
+                    fields.span(),
+                    "`Store` can only be derived for structs with named \
+                     fields",
+                )
+                .to_compile_error()
+            }
+        },
+        _ => {
+            return syn::Error::new(
+                Span::call_site(),
+                "`Store` can only be derived for structs with named fields",
+            )
+            .to_compile_error()
+        }
+    };
+
+    let trait_name = format_ident!("{name}StoreFields");
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let where_predicates = where_clause.map(|w| &w.predicates);
+
+    // the trait takes the struct's generics, after the type of the field it
+    // is implemented for; lifetimes have to come before any other parameter
+    let (lifetimes, params): (Vec<_>, Vec<_>) = ast
+        .generics
+        .params
+        .iter()
+        .partition(|param| matches!(param, GenericParam::Lifetime(_)));
+    let name_of = |param: &&GenericParam| match param {
+        GenericParam::Type(ty) => {
+            let ident = &ty.ident;
+            quote! { #ident }
+        }
+        GenericParam::Lifetime(lt) => {
+            let lifetime = &lt.lifetime;
+            quote! { #lifetime }
+        }
+        GenericParam::Const(c) => {
+            let ident = &c.ident;
+            quote! { #ident }
+        }
+    };
+    let lifetime_names = lifetimes.iter().map(name_of).collect::<Vec<_>>();
+    let param_names = params.iter().map(name_of).collect::<Vec<_>>();
+
+    let (signatures, methods): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let ident = field.ident.as_ref().expect("named struct fields");
+            let ty = &field.ty;
+            let doc = format!(
+                "Reactive access to the `{ident}` field of [`{name}`]."
+            );
+            let signature = quote! {
+                #[doc = #doc]
+                fn #ident(self) -> ::leptos::Subfield<AnyStoreField, #name #ty_generics, #ty>
+            };
+            let method = quote! {
+                #[inline(always)]
+                fn #ident(self) -> ::leptos::Subfield<AnyStoreField, #name #ty_generics, #ty> {
+                    ::leptos::Subfield::new(
+                        self,
+                        #index,
+                        |prev| &prev.#ident,
+                        |prev| &mut prev.#ident,
+                    )
+                }
+            };
+            (signature, method)
+        })
+        .unzip();
+
+    let trait_doc = format!(
+        "Reactive access to each field of [`{name}`] inside a \
+         [`Store`](::leptos::Store)."
+    );
+
+    quote! {
+        #[doc = #trait_doc]
+        #vis trait #trait_name<#(#lifetimes,)* AnyStoreField, #(#params),*>
+        #where_clause
+        {
+            #(#signatures;)*
+        }
+
+        impl<#(#lifetimes,)* AnyStoreField, #(#params),*>
+            #trait_name<#(#lifetime_names,)* AnyStoreField, #(#param_names),*>
+            for AnyStoreField
+        where
+            AnyStoreField: ::leptos::StoreField<Value = #name #ty_generics>,
+            #where_predicates
+        {
+            #(#methods)*
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use leptos::{
+    create_isomorphic_effect, create_memo, create_runtime, create_rw_signal,
+    SignalGet, SignalGetUntracked, SignalSet, SignalUpdate, SignalWith, Store,
+    StoreVecExt,
+};
+
+#[derive(Store, Clone, Default)]
+pub struct Todos {
+    user: String,
+    todos: Vec<Todo>,
+}
+
+#[derive(Store, Clone, Default)]
+pub struct Todo {
+    label: String,
+    completed: bool,
+}
+
+#[derive(Store, Clone, Default)]
+pub struct Wrapper<T: Clone> {
+    inner: T,
+}
+
+#[derive(Store, Clone)]
+pub struct Labelled<'a, T: Clone> {
+    label: &'a str,
+    value: T,
+}
+
+fn data() -> Todos {
+    Todos {
+        user: "Bob".to_string(),
+        todos: vec![
+            Todo {
+                label: "Create a store".to_string(),
+                completed: true,
+            },
+            Todo {
+                label: "Read one field".to_string(),
+                completed: false,
+            },
+        ],
+    }
+}
+
+#[test]
+fn fields_notify_only_their_own_subscribers() {
+    let runtime = create_runtime();
+
+    let store = Store::new(data());
+    let user_runs = create_rw_signal(0);
+    let todo_runs = create_rw_signal(0);
+
+    create_isomorphic_effect(move |_| {
+        store.user().track();
+        user_runs.update(|n| *n += 1);
+    });
+    create_isomorphic_effect(move |_| {
+        store.todos().at(1).label().track();
+        todo_runs.update(|n| *n += 1);
+    });
+
+    store.todos().at(0).completed().set(false);
+    assert_eq!(user_runs.get_untracked(), 1);
+    assert_eq!(todo_runs.get_untracked(), 1);
+
+    store
+        .todos()
+        .at(1)
+        .label()
+        .set("Read two fields".to_string());
+    assert_eq!(user_runs.get_untracked(), 1);
+    assert_eq!(todo_runs.get_untracked(), 2);
+
+    store.user().set("Alice".to_string());
+    assert_eq!(user_runs.get_untracked(), 2);
+    assert_eq!(todo_runs.get_untracked(), 2);
+
+    runtime.dispose();
+}
+
+#[test]
+fn updating_a_field_notifies_ancestors_and_descendants() {
+    let runtime = create_runtime();
+
+    let store = Store::new(data());
+    let completed = create_memo(move |_| {
+        store
+            .todos()
+            .with(|todos| todos.iter().filter(|todo| todo.completed).count())
+    });
+    let label = create_memo(move |_| store.todos().at(1).label().get());
+    assert_eq!(completed.get(), 1);
+
+    // the list as a whole contains the changed field
+    store.todos().at(1).completed().set(true);
+    assert_eq!(completed.get(), 2);
+
+    // replacing the whole todo replaces its label
+    store.todos().at(1).set(Todo {
+        label: "Replaced".to_string(),
+        completed: false,
+    });
+    assert_eq!(label.get(), "Replaced");
+    assert_eq!(completed.get(), 1);
+
+    runtime.dispose();
+}
+
+#[test]
+fn generic_structs() {
+    let runtime = create_runtime();
+
+    let store = Store::new(Wrapper { inner: 1 });
+    let doubled = create_memo(move |_| store.inner().get() * 2);
+    store.inner().update(|n| *n += 1);
+    assert_eq!(doubled.get(), 4);
+
+    runtime.dispose();
+}
+
+#[test]
+fn structs_with_lifetimes() {
+    let runtime = create_runtime();
+
+    let store = Store::new(Labelled {
+        label: "count",
+        value: 1,
+    });
+    let value = create_memo(move |_| store.value().get());
+    store.label().set("total");
+    assert_eq!(store.label().get(), "total");
+    assert_eq!(value.get(), 1);
+
+    store.value().set(2);
+    assert_eq!(value.get(), 2);
+
+    runtime.dispose();
+}
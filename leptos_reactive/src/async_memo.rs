@@ -0,0 +1,325 @@
+use crate::{
+    create_isomorphic_effect, create_local_resource, create_resource,
+    create_signal, node::NodeId, runtime::with_runtime,
+    serialization::Serializable, ReadSignal, Resource, Signal, SignalDispose,
+    SignalGet, SignalUpdate, SignalWith,
+};
+use pin_project::pin_project;
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+// IMPLEMENTATION NOTE:
+// An async memo is a resource whose source is a version counter. A separate
+// effect node acts as the observer for the async computation: it is set as
+// the observer while the fetcher is called, and again every time the
+// resulting `Future` is polled, so that signals read after an `.await` are
+// tracked as well. When any of those signals change, the effect bumps the
+// version, which causes the resource to run the computation again.
+
+/// Creates an asynchronous derived value, which runs an `async` computation
+/// and tracks every signal it reads, including those read after an `.await`.
+///
+/// Whenever any of those signals change, the computation runs again, and any
+/// pending run of it is dropped. Unlike a [`Resource`](crate::Resource), there
+/// is no separate `source`: the dependencies are discovered by running the
+/// computation itself.
+///
+/// Reading an async memo returns the value of the last run that completed
+/// ([`Option::None`] until the first run completes), and registers it with any
+/// `<Suspense/>` or `<Transition/>` it is read in, exactly like a resource.
+/// [`AsyncMemo::pending`] returns whether a run is currently in flight.
+///
+/// Like [`create_resource`], the computation runs on the server, and its value
+/// is serialized and sent to the client. Because its dependencies are only
+/// known once it has run, the computation runs once more in the background
+/// after the client has hydrated, to find them. If the value can't be
+/// serialized, use [`create_local_async_memo`].
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// async fn fetch_user_name(id: u32) -> String {
+///     format!("user {id}")
+/// }
+///
+/// # if !cfg!(feature = "ssr") {
+/// let (user_id, set_user_id) = create_signal(1);
+/// let (greeting, set_greeting) = create_signal("Hello");
+///
+/// let message = create_async_memo(move || async move {
+///     let name = fetch_user_name(user_id.get()).await;
+///     // also tracked, even though it is read after the `.await`
+///     format!("{}, {name}!", greeting.get())
+/// });
+/// assert_eq!(message.get(), Some("Hello, user 1!".to_string()));
+///
+/// set_greeting.set("Goodbye");
+/// assert_eq!(message.get(), Some("Goodbye, user 1!".to_string()));
+/// # }
+/// # runtime.dispose();
+/// ```
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>()
+        )
+    )
+)]
+#[track_caller]
+pub fn create_async_memo<T, Fu>(fun: impl Fn() -> Fu + 'static) -> AsyncMemo<T>
+where
+    T: Serializable + 'static,
+    Fu: Future<Output = T> + 'static,
+{
+    let (version, tracker, run) = async_memo_parts(fun);
+
+    let ran = Rc::new(Cell::new(false));
+    let resource = create_resource(move || version.get(), {
+        let run = Rc::clone(&run);
+        let ran = Rc::clone(&ran);
+        move |_| {
+            ran.set(true);
+            run()
+        }
+    });
+
+    // a value sent by the server is used without running the computation,
+    // so its dependencies are found by running it again in the background
+    #[cfg(feature = "hydrate")]
+    if !ran.get() {
+        use crate::SignalSet;
+
+        crate::spawn_local(async move {
+            let value = run().await;
+            resource.set(value);
+        });
+    }
+
+    AsyncMemo { resource, tracker }
+}
+
+/// Creates an asynchronous derived value that always runs locally, like
+/// [`create_local_resource`], so its value does not need to be
+/// [`Serializable`]. It is not loaded on the server.
+///
+/// See [`create_async_memo`] for more.
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>()
+        )
+    )
+)]
+#[track_caller]
+pub fn create_local_async_memo<T, Fu>(
+    fun: impl Fn() -> Fu + 'static,
+) -> AsyncMemo<T>
+where
+    T: 'static,
+    Fu: Future<Output = T> + 'static,
+{
+    let (version, tracker, run) = async_memo_parts(fun);
+    let resource = create_local_resource(move || version.get(), move |_| run());
+
+    AsyncMemo { resource, tracker }
+}
+
+/// Creates the version counter that the resource uses as its source, the
+/// node that tracks the computation's dependencies, and a function that runs
+/// the computation with that node as its observer.
+#[allow(clippy::type_complexity)]
+fn async_memo_parts<Fu>(
+    fun: impl Fn() -> Fu + 'static,
+) -> (ReadSignal<usize>, NodeId, Rc<dyn Fn() -> Tracked<Fu>>)
+where
+    Fu: Future + 'static,
+{
+    let (version, set_version) = create_signal(0_usize);
+
+    // the first run happens when the resource first loads, so the effect
+    // only needs to react to later changes in the computation's dependencies
+    let tracker = create_isomorphic_effect(move |prev: Option<()>| {
+        if prev.is_some() {
+            set_version.update(|n| *n += 1);
+        }
+    })
+    .id;
+
+    let run = Rc::new(move || {
+        let fut = with_runtime(|runtime| {
+            // drop the dependencies and children of the previous run
+            runtime.cleanup_node(tracker);
+            runtime.cleanup_sources(tracker);
+            runtime.with_observer(tracker, &fun)
+        })
+        .expect(
+            "tried to run an async memo in a runtime that has been disposed",
+        );
+        Tracked {
+            observer: tracker,
+            fut,
+        }
+    });
+
+    (version, tracker, run)
+}
+
+/// An asynchronous derived value, which runs an `async` computation and reruns
+/// it whenever any signal it reads changes, including signals read after an
+/// `.await`.
+///
+/// See [`create_async_memo`] for more.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct AsyncMemo<T>
+where
+    T: 'static,
+{
+    resource: Resource<usize, T>,
+    tracker: NodeId,
+}
+
+impl<T> Clone for AsyncMemo<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AsyncMemo<T> {}
+
+impl<T> AsyncMemo<T> {
+    /// Creates an asynchronous derived value, which runs an `async` computation
+    /// and tracks every signal it reads, including those read after an `.await`.
+    ///
+    /// This is identical to [`create_async_memo`].
+    #[inline(always)]
+    #[track_caller]
+    pub fn new<Fu>(fun: impl Fn() -> Fu + 'static) -> Self
+    where
+        T: Serializable,
+        Fu: Future<Output = T> + 'static,
+    {
+        create_async_memo(fun)
+    }
+
+    /// Creates an asynchronous derived value that always runs locally.
+    ///
+    /// This is identical to [`create_local_async_memo`].
+    #[inline(always)]
+    #[track_caller]
+    pub fn local<Fu>(fun: impl Fn() -> Fu + 'static) -> Self
+    where
+        Fu: Future<Output = T> + 'static,
+    {
+        create_local_async_memo(fun)
+    }
+
+    /// Returns a signal that is `true` while the computation is running, i.e.,
+    /// until its first run completes and whenever it is rerun after that.
+    pub fn pending(&self) -> Signal<bool> {
+        self.resource.loading()
+    }
+
+    /// Reruns the computation, even if none of its dependencies have changed.
+    pub fn refetch(&self) {
+        self.resource.refetch();
+    }
+}
+
+impl<T> SignalWith for AsyncMemo<T> {
+    type Value = Option<T>;
+
+    #[track_caller]
+    fn with<O>(&self, f: impl FnOnce(&Option<T>) -> O) -> O {
+        self.resource.with(f)
+    }
+
+    #[track_caller]
+    fn try_with<O>(&self, f: impl FnOnce(&Option<T>) -> O) -> Option<O> {
+        self.resource.try_with(f)
+    }
+}
+
+impl<T: Clone> SignalGet for AsyncMemo<T> {
+    type Value = Option<T>;
+
+    #[inline(always)]
+    #[track_caller]
+    fn get(&self) -> Option<T> {
+        self.resource.get()
+    }
+
+    #[inline(always)]
+    #[track_caller]
+    fn try_get(&self) -> Option<Option<T>> {
+        self.resource.try_get()
+    }
+}
+
+impl<T> SignalDispose for AsyncMemo<T> {
+    #[track_caller]
+    fn dispose(self) {
+        self.resource.dispose();
+        _ = with_runtime(|runtime| runtime.dispose_node(self.tracker));
+    }
+}
+
+/// Polls the inner `Future` with the async memo's tracking node as the
+/// current observer, so that any signal read while polling is tracked.
+#[pin_project]
+struct Tracked<Fut> {
+    observer: NodeId,
+    #[pin]
+    fut: Fut,
+}
+
+impl<Fut: Future> Future for Tracked<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let observer = *this.observer;
+        let mut fut = this.fut;
+        with_runtime(|runtime| {
+            runtime.with_observer(observer, || fut.as_mut().poll(cx))
+        })
+        // if the runtime is gone, there is nothing left to notify
+        .unwrap_or(Poll::Pending)
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Clone> FnOnce<()> for AsyncMemo<T> {
+    type Output = Option<T>;
+
+    #[inline(always)]
+    extern "rust-call" fn call_once(self, _args: ()) -> Self::Output {
+        self.get()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Clone> FnMut<()> for AsyncMemo<T> {
+    #[inline(always)]
+    extern "rust-call" fn call_mut(&mut self, _args: ()) -> Self::Output {
+        self.get()
+    }
+}
+
+#[cfg(feature = "nightly")]
+impl<T: Clone> Fn<()> for AsyncMemo<T> {
+    #[inline(always)]
+    extern "rust-call" fn call(&self, _args: ()) -> Self::Output {
+        self.get()
+    }
+}
@@ -70,19 +70,31 @@ where
 {
     cfg_if! {
         if #[cfg(not(feature = "ssr"))] {
-            use crate::{Owner, queue_microtask, with_owner};
+            use crate::{EffectScheduler, Owner, queue_microtask, with_owner};
 
             let runtime = Runtime::current();
             let owner = Owner::current();
-            let id = runtime.create_effect(f);
+            let id = runtime.create_effect(f, EffectPriority::User);
 
-            queue_microtask(move || {
-                with_owner(owner.unwrap(), move || {
-                    _ = with_runtime( |runtime| {
-                        runtime.update_if_necessary(id);
+            // with the manual scheduler, the first run waits to be flushed
+            let manual = with_runtime(|runtime| {
+                let manual = runtime.scheduler.get() == EffectScheduler::Manual;
+                if manual {
+                    runtime.effect_queue.borrow_mut().push(id, EffectPriority::User);
+                }
+                manual
+            })
+            .unwrap_or_default();
+
+            if !manual {
+                queue_microtask(move || {
+                    with_owner(owner.unwrap(), move || {
+                        _ = with_runtime( |runtime| {
+                            runtime.update_if_necessary(id);
+                        });
                     });
                 });
-            });
+            }
 
             Effect { id, ty: PhantomData }
         } else {
@@ -250,7 +262,7 @@ where
     T: 'static,
 {
     let runtime = Runtime::current();
-    let id = runtime.create_effect(f);
+    let id = runtime.create_effect(f, EffectPriority::User);
     //crate::macros::debug_warn!("creating effect {e:?}");
     _ = with_runtime(|runtime| {
         runtime.update_if_necessary(id);
@@ -285,7 +297,7 @@ where
     cfg_if! {
         if #[cfg(not(feature = "ssr"))] {
             let runtime = Runtime::current();
-            let id = runtime.create_effect(f);
+            let id = runtime.create_effect(f, EffectPriority::Render);
             _ = with_runtime( |runtime| {
                 runtime.update_if_necessary(id);
             });
@@ -298,6 +310,94 @@ where
     }
 }
 
+/// Creates a low-priority effect, which runs the next time the browser is
+/// idle (using [`requestIdleCallback`](https://developer.mozilla.org/en-US/docs/Web/API/Window/requestIdleCallback)),
+/// both for its first run and whenever its dependencies change.
+///
+/// This is useful for work that should not delay rendering or other effects,
+/// like logging, analytics, or prefetching. Outside the browser, idle effects
+/// run right after any other effects that are waiting to run.
+///
+/// Like [`create_effect`], idle effects **do not run on the server**.
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// set_effect_scheduler(EffectScheduler::Manual);
+///
+/// let (count, set_count) = create_signal(0);
+/// let log = create_rw_signal(Vec::new());
+/// create_idle_effect(move |_| {
+///     let count = count.get();
+///     log.update(|log| log.push(format!("idle {count}")));
+/// });
+/// create_effect(move |_| {
+///     let count = count.get();
+///     log.update(|log| log.push(format!("user {count}")));
+/// });
+///
+/// flush_effects();
+/// # if !cfg!(feature = "ssr") {
+/// // user effects run before idle effects, even though this one was created later
+/// assert_eq!(log.get_untracked(), ["user 0", "idle 0"]);
+/// # }
+/// # runtime.dispose();
+/// ```
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>()
+        )
+    )
+)]
+#[track_caller]
+#[inline(always)]
+pub fn create_idle_effect<T>(f: impl Fn(Option<T>) -> T + 'static) -> Effect<T>
+where
+    T: 'static,
+{
+    cfg_if! {
+        if #[cfg(not(feature = "ssr"))] {
+            use crate::EffectScheduler;
+
+            let runtime = Runtime::current();
+            let id = runtime.create_effect(f, EffectPriority::Idle);
+            _ = with_runtime(|runtime| {
+                runtime.effect_queue.borrow_mut().push(id, EffectPriority::Idle);
+                if runtime.scheduler.get() == EffectScheduler::Automatic {
+                    runtime.schedule_idle_flush();
+                }
+            });
+            Effect { id, ty: PhantomData }
+        } else {
+            // clear warnings
+            _ = f;
+            Effect { id: Default::default(), ty: PhantomData }
+        }
+    }
+}
+
+/// The order in which effects run after they have been notified of a change.
+///
+/// Whenever several effects are waiting to run, all render effects run first,
+/// then all user effects, and idle effects only once nothing else is waiting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EffectPriority {
+    /// Effects that keep the DOM up to date, created with
+    /// [`create_render_effect`].
+    Render,
+    /// Effects created with [`create_effect`], [`create_isomorphic_effect`] or
+    /// [`watch`](crate::watch).
+    #[default]
+    User,
+    /// Low-priority effects created with [`create_idle_effect`], which run
+    /// when the browser is idle.
+    Idle,
+}
+
 /// A handle to an effect, can be used to explicitly dispose of the effect.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Effect<T> {
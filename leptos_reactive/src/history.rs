@@ -0,0 +1,358 @@
+use crate::{
+    batch, create_isomorphic_effect, create_rw_signal,
+    runtime::current_batch_id, store_value, RwSignal, Signal,
+    SignalGetUntracked, SignalSet, SignalWith, StoredValue,
+};
+use std::collections::VecDeque;
+
+// IMPLEMENTATION NOTE:
+// Each tracked signal has an effect that remembers the last value the history
+// knows about. When the signal changes, the effect records the old and new
+// values as a change. Undoing or redoing a change updates that remembered value
+// before writing to the signal, so the effect sees that nothing new happened
+// and doesn't record the write again. Effects that run because of the same
+// `batch` share a batch id, which is used to merge their changes into a single
+// entry.
+
+/// Creates a [`SignalHistory`], which records changes to the signals it tracks
+/// so that they can be undone and redone.
+///
+/// Call [`SignalHistory::track`] to start recording a signal. Each write to a
+/// tracked signal becomes one entry in the history, except that all writes
+/// made inside the same [`batch`] become a single entry, so they are undone
+/// and redone together.
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// let name = create_rw_signal("Alice".to_string());
+/// let age = create_rw_signal(30);
+///
+/// let history = create_signal_history();
+/// history.track(name);
+/// history.track(age);
+/// let can_undo = history.can_undo();
+/// assert!(!can_undo.get());
+///
+/// name.set("Bob".to_string());
+/// // both writes are undone in one step
+/// batch(|| {
+///     name.set("Carol".to_string());
+///     age.set(40);
+/// });
+/// assert!(can_undo.get());
+///
+/// history.undo();
+/// assert_eq!(name.get(), "Bob");
+/// assert_eq!(age.get(), 30);
+///
+/// history.redo();
+/// assert_eq!(name.get(), "Carol");
+/// assert_eq!(age.get(), 40);
+/// # runtime.dispose();
+/// ```
+#[track_caller]
+pub fn create_signal_history() -> SignalHistory {
+    SignalHistory::new()
+}
+
+/// Records changes to one or more signals, so that they can be undone and
+/// redone.
+///
+/// See [`create_signal_history`] for more.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SignalHistory {
+    state: StoredValue<HistoryState>,
+    can_undo: RwSignal<bool>,
+    can_redo: RwSignal<bool>,
+}
+
+impl Clone for SignalHistory {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl Copy for SignalHistory {}
+
+impl Default for SignalHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A point in a [`SignalHistory`] that it can go back to with
+/// [`SignalHistory::go_to`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HistoryCheckpoint(u64);
+
+impl SignalHistory {
+    /// Creates a history that keeps every change.
+    ///
+    /// This is identical to [`create_signal_history`].
+    #[track_caller]
+    pub fn new() -> Self {
+        Self::with_limit_inner(None)
+    }
+
+    /// Creates a history that only keeps the `limit` most recent entries,
+    /// dropping older ones once it is full.
+    #[track_caller]
+    pub fn with_limit(limit: usize) -> Self {
+        Self::with_limit_inner(Some(limit))
+    }
+
+    fn with_limit_inner(limit: Option<usize>) -> Self {
+        Self {
+            state: store_value(HistoryState {
+                limit,
+                ..Default::default()
+            }),
+            can_undo: create_rw_signal(false),
+            can_redo: create_rw_signal(false),
+        }
+    }
+
+    /// Starts recording changes to `signal`.
+    ///
+    /// A write that leaves the signal equal to its previous value is not
+    /// recorded.
+    #[track_caller]
+    pub fn track<T>(&self, signal: RwSignal<T>)
+    where
+        T: Clone + PartialEq,
+    {
+        let this = *self;
+        let last = store_value(signal.get_untracked());
+        create_isomorphic_effect(move |_| {
+            let unchanged =
+                signal.with(|new| last.with_value(|last| last == new));
+            if unchanged {
+                return;
+            }
+            let new = signal.get_untracked();
+            let Some(old) = last
+                .try_update_value(|last| std::mem::replace(last, new.clone()))
+            else {
+                return;
+            };
+            this.record(Box::new(SignalChange {
+                signal,
+                last,
+                old,
+                new,
+            }));
+        });
+    }
+
+    /// Reverts the most recent entry. Returns `false` if there was nothing
+    /// to undo.
+    pub fn undo(&self) -> bool {
+        let Some(entry) = self
+            .state
+            .try_update_value(|state| state.undo.pop_back())
+            .flatten()
+        else {
+            return false;
+        };
+        batch(|| {
+            for change in entry.changes.iter().rev() {
+                change.apply(Direction::Undo);
+            }
+        });
+        self.update_state(|state| state.redo.push(entry));
+        true
+    }
+
+    /// Reapplies the most recently undone entry. Returns `false` if there
+    /// was nothing to redo.
+    pub fn redo(&self) -> bool {
+        let Some(entry) = self
+            .state
+            .try_update_value(|state| state.redo.pop())
+            .flatten()
+        else {
+            return false;
+        };
+        batch(|| {
+            for change in &entry.changes {
+                change.apply(Direction::Redo);
+            }
+        });
+        self.update_state(|state| state.push_undo(entry));
+        true
+    }
+
+    /// Returns a signal that is `true` when there is an entry to undo.
+    pub fn can_undo(&self) -> Signal<bool> {
+        self.can_undo.read_only().into()
+    }
+
+    /// Returns a signal that is `true` when there is an entry to redo.
+    pub fn can_redo(&self) -> Signal<bool> {
+        self.can_redo.read_only().into()
+    }
+
+    /// Returns a checkpoint for the current state of the tracked signals,
+    /// which can be restored later with [`SignalHistory::go_to`].
+    pub fn checkpoint(&self) -> HistoryCheckpoint {
+        HistoryCheckpoint(
+            self.state
+                .try_with_value(HistoryState::current)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Undoes or redoes entries until the tracked signals are back in the
+    /// state they were in when `checkpoint` was taken.
+    ///
+    /// Returns `false` without changing anything if the checkpoint can no
+    /// longer be reached, because its entry was dropped to stay within the
+    /// history's limit, or because a new change was recorded after undoing
+    /// past it.
+    pub fn go_to(&self, checkpoint: HistoryCheckpoint) -> bool {
+        let target = checkpoint.0;
+        let Some(backward) = self
+            .state
+            .try_with_value(|state| {
+                if state.base == target
+                    || state.undo.iter().any(|entry| entry.id == target)
+                {
+                    Some(true)
+                } else if state.redo.iter().any(|entry| entry.id == target) {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .flatten()
+        else {
+            return false;
+        };
+        batch(|| {
+            while self.checkpoint().0 != target {
+                let moved = if backward { self.undo() } else { self.redo() };
+                if !moved {
+                    break;
+                }
+            }
+        });
+        true
+    }
+
+    /// Forgets every entry, without changing the tracked signals.
+    pub fn clear(&self) {
+        self.update_state(|state| {
+            state.base = state.current();
+            state.undo.clear();
+            state.redo.clear();
+        });
+    }
+
+    fn record(&self, change: Box<dyn Change>) {
+        let batch = current_batch_id();
+        self.update_state(|state| {
+            state.redo.clear();
+            match state.undo.back_mut() {
+                Some(entry) if batch.is_some() && entry.batch == batch => {
+                    entry.changes.push(change);
+                }
+                _ => {
+                    state.next_id += 1;
+                    let entry = Entry {
+                        id: state.next_id,
+                        batch,
+                        changes: vec![change],
+                    };
+                    state.push_undo(entry);
+                }
+            }
+        });
+    }
+
+    /// Updates the state, then the `can_undo` and `can_redo` signals. The
+    /// signals are only set once the state is no longer borrowed, because
+    /// their subscribers may use the history again.
+    fn update_state(&self, f: impl FnOnce(&mut HistoryState)) {
+        let Some((can_undo, can_redo)) = self.state.try_update_value(|state| {
+            f(state);
+            (!state.undo.is_empty(), !state.redo.is_empty())
+        }) else {
+            return;
+        };
+        if self.can_undo.get_untracked() != can_undo {
+            self.can_undo.set(can_undo);
+        }
+        if self.can_redo.get_untracked() != can_redo {
+            self.can_redo.set(can_redo);
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistoryState {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    limit: Option<usize>,
+    /// The id of the state before the oldest entry in `undo`.
+    base: u64,
+    next_id: u64,
+}
+
+impl HistoryState {
+    fn current(&self) -> u64 {
+        self.undo.back().map(|entry| entry.id).unwrap_or(self.base)
+    }
+
+    fn push_undo(&mut self, entry: Entry) {
+        self.undo.push_back(entry);
+        if let Some(limit) = self.limit {
+            while self.undo.len() > limit {
+                if let Some(dropped) = self.undo.pop_front() {
+                    self.base = dropped.id;
+                }
+            }
+        }
+    }
+}
+
+struct Entry {
+    id: u64,
+    /// The [`batch`] the changes were made in, if any.
+    batch: Option<u64>,
+    changes: Vec<Box<dyn Change>>,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+trait Change {
+    fn apply(&self, direction: Direction);
+}
+
+struct SignalChange<T: 'static> {
+    signal: RwSignal<T>,
+    last: StoredValue<T>,
+    old: T,
+    new: T,
+}
+
+impl<T> Change for SignalChange<T>
+where
+    T: Clone,
+{
+    fn apply(&self, direction: Direction) {
+        let value = match direction {
+            Direction::Undo => &self.old,
+            Direction::Redo => &self.new,
+        };
+        // update the last known value first, so that the write isn't
+        // recorded as a new change
+        if self.last.try_set_value(value.clone()).is_none() {
+            self.signal.try_set(value.clone());
+        }
+    }
+}
@@ -1,10 +1,11 @@
 #[cfg(all(feature = "hydrate", feature = "experimental-islands"))]
 use crate::Owner;
 use crate::{
-    runtime::PinnedFuture, suspense::StreamChunk, with_runtime, ResourceId,
-    SignalGet, SuspenseContext,
+    runtime::{PinnedFuture, PinnedStream},
+    suspense::StreamChunk,
+    with_runtime, ResourceId, SignalGet, SuspenseContext,
 };
-use futures::stream::FuturesUnordered;
+use futures::stream::{FuturesUnordered, SelectAll};
 #[cfg(feature = "experimental-islands")]
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -22,6 +23,9 @@ pub struct SharedContext {
     pub pending_fragments: HashMap<String, FragmentData>,
     /// Suspense fragments that contain only local resources.
     pub fragments_with_local_resources: HashSet<String>,
+    /// Whether later values of streaming resources are kept to be sent to
+    /// the client.
+    pub stream_resource_updates: bool,
     #[cfg(feature = "experimental-islands")]
     pub no_hydrate: bool,
     #[cfg(all(feature = "hydrate", feature = "experimental-islands"))]
@@ -59,6 +63,38 @@ impl SharedContext {
             .unwrap_or_default()
     }
 
+    /// Returns a stream of every value yielded by a streaming
+    /// [`Resource`](crate::Resource) after its first one, serialized, along
+    /// with the ID of the resource. The stream ends once all of the streaming
+    /// resources have finished.
+    #[cfg_attr(
+        any(debug_assertions, feature = "ssr"),
+        instrument(level = "trace", skip_all,)
+    )]
+    pub fn stream_resource_updates(
+    ) -> SelectAll<PinnedStream<(ResourceId, String)>> {
+        with_runtime(|runtime| runtime.stream_updates()).unwrap_or_default()
+    }
+
+    /// Keeps every value yielded by a streaming [`Resource`](crate::Resource)
+    /// created after this call, so that it can be read from
+    /// [`stream_resource_updates`](SharedContext::stream_resource_updates).
+    ///
+    /// This should only be called by renderers that read those updates, as
+    /// they are held in memory until then.
+    pub fn enable_stream_resource_updates() {
+        _ = with_runtime(|runtime| {
+            runtime.shared_context.borrow_mut().stream_resource_updates = true;
+        });
+    }
+
+    pub(crate) fn stream_resource_updates_enabled() -> bool {
+        with_runtime(|runtime| {
+            runtime.shared_context.borrow().stream_resource_updates
+        })
+        .unwrap_or(false)
+    }
+
     /// Registers the given [`SuspenseContext`](crate::SuspenseContext) with the current scope,
     /// calling the `resolver` when its resources are all resolved.
     #[cfg_attr(
@@ -300,6 +336,7 @@ impl Default for SharedContext {
                 resolved_resources,
                 fragments_with_local_resources,
                 pending_fragments: Default::default(),
+                stream_resource_updates: false,
                 #[cfg(feature = "experimental-islands")]
                 no_hydrate: true,
                 #[cfg(all(
@@ -318,6 +355,7 @@ impl Default for SharedContext {
                 resolved_resources: Default::default(),
                 pending_fragments: Default::default(),
                 fragments_with_local_resources: Default::default(),
+                stream_resource_updates: false,
                 #[cfg(feature = "experimental-islands")]
                 no_hydrate: true,
                 #[cfg(all(
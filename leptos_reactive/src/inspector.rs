@@ -0,0 +1,428 @@
+//! Utilities for inspecting the reactive graph, intended for building
+//! developer tools and for asserting the shape of the graph in tests.
+//!
+//! It can also report reactive nodes that seem to have been leaked, using
+//! [`find_leaks`] while the app is running or [`assert_no_leaks`] in tests.
+//!
+//! This module is only available with the `inspector` feature, which adds
+//! some bookkeeping to every reactive node and should not be enabled in
+//! production builds.
+//!
+//! ```
+//! # use leptos_reactive::{*, inspector::*};
+//! # let runtime = create_runtime();
+//! let (count, set_count) = create_signal(0);
+//! let double = create_memo(move |_| count.get() * 2);
+//! double.get();
+//!
+//! let graph = snapshot_graph();
+//! let memo = graph
+//!     .nodes
+//!     .iter()
+//!     .find(|node| node.kind == NodeKind::Memo)
+//!     .unwrap();
+//! let signal = graph.get(memo.sources[0]).unwrap();
+//! assert_eq!(signal.kind, NodeKind::Signal);
+//! assert_eq!(signal.type_name, Some("i32"));
+//! assert_eq!(signal.subscribers, vec![memo.id]);
+//! # runtime.dispose();
+//! ```
+
+pub use crate::node::NodeId;
+use crate::{
+    node::{ReactiveNodeState, ReactiveNodeType},
+    runtime::{with_runtime, Runtime},
+};
+use futures::{
+    channel::mpsc::{self, UnboundedSender},
+    Stream,
+};
+use rustc_hash::FxHashSet;
+use slotmap::SparseSecondaryMap;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    panic::Location,
+};
+
+/// The kind of a node in the reactive graph.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// A signal, which holds a value.
+    Signal,
+    /// A trigger, which holds no value. This is also used for reactive owners
+    /// that are not signals, memos, or effects, like the root of the runtime.
+    Trigger,
+    /// A memo, which holds a value derived from other nodes.
+    Memo,
+    /// An effect, which runs a side effect when its sources change.
+    Effect,
+}
+
+impl From<&ReactiveNodeType> for NodeKind {
+    fn from(value: &ReactiveNodeType) -> Self {
+        match value {
+            ReactiveNodeType::Signal => NodeKind::Signal,
+            ReactiveNodeType::Trigger => NodeKind::Trigger,
+            ReactiveNodeType::Memo { .. } => NodeKind::Memo,
+            ReactiveNodeType::Effect { .. } => NodeKind::Effect,
+        }
+    }
+}
+
+/// Whether a node is up to date.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeState {
+    /// The node is up to date.
+    Clean,
+    /// One of the node’s sources may have changed, so it needs to check
+    /// before it next runs.
+    Check,
+    /// The node needs to run again.
+    Dirty,
+}
+
+impl From<ReactiveNodeState> for NodeState {
+    fn from(value: ReactiveNodeState) -> Self {
+        match value {
+            ReactiveNodeState::Clean => NodeState::Clean,
+            ReactiveNodeState::Check => NodeState::Check,
+            ReactiveNodeState::Dirty | ReactiveNodeState::DirtyMarked => {
+                NodeState::Dirty
+            }
+        }
+    }
+}
+
+/// A single node in a [`GraphSnapshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeSnapshot {
+    /// The unique ID of this node.
+    pub id: NodeId,
+    /// What kind of node this is.
+    pub kind: NodeKind,
+    /// The type of the value held by the node, if it has one.
+    pub type_name: Option<&'static str>,
+    /// The location at which the node was created, if known.
+    pub defined_at: Option<&'static Location<'static>>,
+    /// Whether the node is up to date.
+    pub state: NodeState,
+    /// The reactive owner of this node, which will dispose of it.
+    pub owner: Option<NodeId>,
+    /// The nodes owned by this node.
+    pub children: Vec<NodeId>,
+    /// The nodes this node read the last time it ran.
+    pub sources: Vec<NodeId>,
+    /// The nodes that will be notified when this node changes.
+    pub subscribers: Vec<NodeId>,
+}
+
+/// A point-in-time copy of the reactive graph of the current runtime.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphSnapshot {
+    /// Every node that is alive in the runtime.
+    pub nodes: Vec<NodeSnapshot>,
+}
+
+impl GraphSnapshot {
+    /// Returns the node with the given ID, if it is in the snapshot.
+    pub fn get(&self, id: NodeId) -> Option<&NodeSnapshot> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Returns every node that has no owner.
+    pub fn roots(&self) -> impl Iterator<Item = &NodeSnapshot> {
+        self.nodes.iter().filter(|node| node.owner.is_none())
+    }
+}
+
+/// A change to the reactive graph, as yielded by [`graph_events`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GraphEvent {
+    /// A node was created.
+    Created(NodeId),
+    /// A node was disposed.
+    Disposed(NodeId),
+    /// A signal or trigger notified its subscribers of a change.
+    Notified(NodeId),
+    /// A memo or effect ran.
+    Ran(NodeId),
+    /// A node began to track another one.
+    Subscribed {
+        /// The node that was read.
+        source: NodeId,
+        /// The memo or effect that read it.
+        subscriber: NodeId,
+    },
+}
+
+/// Takes a snapshot of the reactive graph of the current runtime.
+///
+/// Returns an empty snapshot if the runtime has been disposed.
+pub fn snapshot_graph() -> GraphSnapshot {
+    with_runtime(|runtime| runtime.inspector.snapshot(runtime))
+        .unwrap_or_default()
+}
+
+/// Returns a stream of every change made to the reactive graph of the
+/// current runtime from now on.
+///
+/// Events are queued until the stream is polled. The stream ends if the
+/// runtime is disposed.
+pub fn graph_events() -> impl Stream<Item = GraphEvent> + Unpin {
+    let (tx, rx) = mpsc::unbounded();
+    _ = with_runtime(|runtime| {
+        runtime.inspector.listeners.borrow_mut().push(tx)
+    });
+    rx
+}
+
+/// Why a node was reported by [`find_leaks`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LeakReason {
+    /// The node was created outside of any reactive owner, so nothing will
+    /// ever dispose of it.
+    Unowned,
+    /// The owner that was current when the node was created has been
+    /// disposed, but the node is still alive.
+    OutlivedOwner,
+    /// The node is still alive after this many owners have been disposed.
+    SurvivedDisposals(usize),
+}
+
+/// A reactive node that may have been leaked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeakedNode {
+    /// The unique ID of this node.
+    pub id: NodeId,
+    /// What kind of node this is.
+    pub kind: NodeKind,
+    /// The type of the value held by the node, if it has one.
+    pub type_name: Option<&'static str>,
+    /// The location at which the node was created, if known.
+    pub defined_at: Option<&'static Location<'static>>,
+    /// Why the node was reported.
+    pub reason: LeakReason,
+}
+
+impl fmt::Display for LeakedNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.kind)?;
+        if let Some(type_name) = self.type_name {
+            write!(f, "<{type_name}>")?;
+        }
+        if let Some(defined_at) = self.defined_at {
+            write!(f, " created at {defined_at}")?;
+        }
+        match self.reason {
+            LeakReason::Unowned => write!(f, " has no owner"),
+            LeakReason::OutlivedOwner => {
+                write!(f, " outlived the owner it was created in")
+            }
+            LeakReason::SurvivedDisposals(n) => {
+                write!(f, " survived {n} owner disposals")
+            }
+        }
+    }
+}
+
+/// Returns the nodes in the current runtime that look like they have been
+/// leaked: nodes that were created outside an owner, nodes that outlived the
+/// owner they were created in and, if `max_owner_disposals` is given, nodes
+/// that are still alive after more than that many owners have been disposed
+/// since they were created.
+///
+/// Calling this periodically can help to track down memory that grows over
+/// the lifetime of a long-running app. Nodes that are expected to live as long
+/// as the app itself will be reported by the last check, so choose a limit
+/// that is large compared to how often parts of your app are unmounted.
+pub fn find_leaks(max_owner_disposals: Option<usize>) -> Vec<LeakedNode> {
+    with_runtime(|runtime| {
+        runtime.inspector.leaks(runtime, |_, reason| match reason {
+            LeakReason::SurvivedDisposals(n) => {
+                max_owner_disposals.is_some_and(|max| n > max)
+            }
+            _ => true,
+        })
+    })
+    .unwrap_or_default()
+}
+
+/// Runs `f`, then panics if any reactive nodes created while running it are
+/// still alive, listing where each of them was created.
+///
+/// This is useful in tests, to check that unmounting a tree of components
+/// disposes of everything it created.
+///
+/// ```
+/// # use leptos_reactive::{*, inspector::*};
+/// # let runtime = create_runtime();
+/// assert_no_leaks(|| {
+///     let (_, disposer) = as_child_of_current_owner(|_| {
+///         let (count, _) = create_signal(0);
+///         create_memo(move |_| count.get() + 1);
+///     })(());
+///     drop(disposer);
+/// });
+/// # runtime.dispose();
+/// ```
+#[track_caller]
+pub fn assert_no_leaks<T>(f: impl FnOnce() -> T) -> T {
+    let before = alive_nodes();
+    let value = f();
+    let leaks = with_runtime(|runtime| {
+        runtime
+            .inspector
+            .leaks(runtime, |id, _| !before.contains(&id))
+    })
+    .unwrap_or_default();
+
+    if !leaks.is_empty() {
+        let leaks = leaks
+            .iter()
+            .map(|leak| format!("\n  - {leak}"))
+            .collect::<String>();
+        panic!("reactive nodes were leaked:{leaks}");
+    }
+    value
+}
+
+fn alive_nodes() -> FxHashSet<NodeId> {
+    with_runtime(|runtime| runtime.nodes.borrow().keys().collect())
+        .unwrap_or_default()
+}
+
+pub(crate) struct NodeInfo {
+    pub type_name: Option<&'static str>,
+    pub defined_at: Option<&'static Location<'static>>,
+    /// The owner that was current when the node was created.
+    pub created_by: Option<NodeId>,
+    /// The number of owners that had been disposed when the node was created.
+    pub created_after: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct Inspector {
+    pub info: RefCell<SparseSecondaryMap<NodeId, NodeInfo>>,
+    /// The number of owners disposed so far in this runtime.
+    pub owner_disposals: Cell<usize>,
+    listeners: RefCell<Vec<UnboundedSender<GraphEvent>>>,
+}
+
+impl Inspector {
+    pub(crate) fn created(&self, id: NodeId, owner: Option<NodeId>) {
+        self.info.borrow_mut().insert(
+            id,
+            NodeInfo {
+                type_name: None,
+                defined_at: None,
+                created_by: owner,
+                created_after: self.owner_disposals.get(),
+            },
+        );
+        self.emit(GraphEvent::Created(id));
+    }
+
+    pub(crate) fn describe(
+        &self,
+        id: NodeId,
+        type_name: Option<&'static str>,
+        defined_at: &'static Location<'static>,
+    ) {
+        if let Some(info) = self.info.borrow_mut().get_mut(id) {
+            info.type_name = type_name;
+            info.defined_at = Some(defined_at);
+        }
+    }
+
+    pub(crate) fn disposed(&self, id: NodeId, was_owner: bool) {
+        self.info.borrow_mut().remove(id);
+        if was_owner {
+            self.owner_disposals.set(self.owner_disposals.get() + 1);
+        }
+        self.emit(GraphEvent::Disposed(id));
+    }
+
+    pub(crate) fn emit(&self, event: GraphEvent) {
+        self.listeners
+            .borrow_mut()
+            .retain(|tx| tx.unbounded_send(event).is_ok());
+    }
+
+    /// Returns every node for which `keep` returns `true`, given the reason
+    /// it would be leaked.
+    fn leaks(
+        &self,
+        runtime: &Runtime,
+        keep: impl Fn(NodeId, LeakReason) -> bool,
+    ) -> Vec<LeakedNode> {
+        let info = self.info.borrow();
+        let nodes = runtime.nodes.borrow();
+
+        // the root of the runtime is the only node created without going
+        // through the inspector, and is never leaked
+        info.iter()
+            .filter_map(|(id, info)| {
+                let node = nodes.get(id)?;
+                let reason = match info.created_by {
+                    None => LeakReason::Unowned,
+                    Some(owner) if !nodes.contains_key(owner) => {
+                        LeakReason::OutlivedOwner
+                    }
+                    Some(_) => LeakReason::SurvivedDisposals(
+                        self.owner_disposals.get() - info.created_after,
+                    ),
+                };
+                keep(id, reason).then(|| LeakedNode {
+                    id,
+                    kind: (&node.node_type).into(),
+                    type_name: info.type_name,
+                    defined_at: info.defined_at,
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    fn snapshot(&self, runtime: &Runtime) -> GraphSnapshot {
+        let info = self.info.borrow();
+        let nodes = runtime.nodes.borrow();
+        let owners = runtime.node_owners.borrow();
+        let properties = runtime.node_properties.borrow();
+        let sources = runtime.node_sources.borrow();
+        let subscribers = runtime.node_subscribers.borrow();
+
+        let nodes = nodes
+            .iter()
+            .map(|(id, node)| {
+                let info = info.get(id);
+                NodeSnapshot {
+                    id,
+                    kind: (&node.node_type).into(),
+                    type_name: info.and_then(|info| info.type_name),
+                    defined_at: info.and_then(|info| info.defined_at),
+                    state: node.state.into(),
+                    owner: owners.get(id).copied(),
+                    children: properties
+                        .get(id)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|property| property.to_node_id())
+                        .collect(),
+                    sources: sources
+                        .get(id)
+                        .map(|sources| {
+                            sources.borrow().iter().copied().collect()
+                        })
+                        .unwrap_or_default(),
+                    subscribers: subscribers
+                        .get(id)
+                        .map(|subs| subs.borrow().iter().copied().collect())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        GraphSnapshot { nodes }
+    }
+}
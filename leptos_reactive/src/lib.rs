@@ -30,6 +30,8 @@
 //! 4. *Resources:* [`create_resource`], which converts an `async` [`Future`](std::future::Future) into a
 //!    synchronous [`Resource`] signal.
 //! 5. *Triggers:* [`create_trigger`], creates a purely reactive [`Trigger`] primitive without any associated state.
+//! 6. *Queries:* [`create_query`], which creates a [`Resource`] whose results are cached and
+//!    shared by key in a [`QueryClient`].
 //!
 //! ### Effects
 //! 1. Use [`create_effect`] when you need to synchronize the reactive system
@@ -37,6 +39,13 @@
 //! 2. The Leptos DOM renderer wraps any [`Fn`] in your template with [`create_effect`], so
 //!    components you write do *not* need explicit effects to synchronize with the DOM.
 //!
+//! ### Threads
+//! Reactive runtimes are stored per thread, and the values they own are not required to be
+//! `Send`. A signal, memo, effect or resource can only be used on the thread that created it;
+//! on the server, run reactive `async` code with [`spawn_local`] rather than on a multi-threaded
+//! executor. There is no thread-safe runtime, because the views built on top of it in
+//! `leptos_dom` are single-threaded as well.
+//!
 //! ### Example
 //! ```
 //! use leptos_reactive::*;
@@ -82,12 +91,16 @@ extern crate tracing;
 
 #[macro_use]
 mod signal;
+mod async_memo;
 pub mod callback;
 mod context;
 #[macro_use]
 mod diagnostics;
 mod effect;
+mod history;
 mod hydration;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 // contains "private" implementation details right now.
 // could make this unhidden in the future if needed.
 // macro_export makes it public from the crate root anyways
@@ -95,6 +108,8 @@ mod hydration;
 pub mod macros;
 mod memo;
 mod node;
+mod persisted;
+mod query;
 mod resource;
 mod runtime;
 mod selector;
@@ -106,29 +121,35 @@ mod signal_wrappers_write;
 mod slice;
 mod spawn;
 mod spawn_microtask;
+mod store;
 mod stored_value;
 pub mod suspense;
 mod trigger;
 mod watch;
 
+pub use async_memo::*;
 pub use callback::*;
 pub use context::*;
 pub use diagnostics::SpecialNonReactiveZone;
 pub use effect::*;
+pub use history::*;
 pub use hydration::{FragmentData, SharedContext};
 pub use memo::*;
 pub use node::Disposer;
 pub use oco::*;
 pub use oco_ref as oco;
+pub use persisted::*;
+pub use query::*;
 pub use resource::*;
 use runtime::*;
 pub use runtime::{
     as_child_of_current_owner, batch, create_runtime, current_runtime,
-    on_cleanup, run_as_child, set_current_runtime,
-    spawn_local_with_current_owner, spawn_local_with_owner, try_batch,
+    flush_effects, on_cleanup, run_as_child, set_current_runtime,
+    set_effect_scheduler, spawn_local_with_current_owner,
+    spawn_local_with_owner, tick, try_batch,
     try_spawn_local_with_current_owner, try_spawn_local_with_owner,
     try_with_owner, untrack, untrack_with_diagnostics, with_current_owner,
-    with_owner, Owner, RuntimeId, ScopedFuture,
+    with_owner, EffectScheduler, Owner, RuntimeId, ScopedFuture,
 };
 pub use selector::*;
 pub use serialization::*;
@@ -138,6 +159,7 @@ pub use signal_wrappers_write::*;
 pub use slice::*;
 pub use spawn::*;
 pub use spawn_microtask::*;
+pub use store::*;
 pub use stored_value::*;
 pub use suspense::{GlobalSuspenseContext, SuspenseContext};
 pub use trigger::*;
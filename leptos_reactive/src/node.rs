@@ -1,4 +1,4 @@
-use crate::{with_runtime, AnyComputation};
+use crate::{with_runtime, AnyComputation, EffectPriority};
 use std::{any::Any, cell::RefCell, rc::Rc};
 
 slotmap::new_key_type! {
@@ -36,8 +36,13 @@ impl ReactiveNode {
 pub(crate) enum ReactiveNodeType {
     Trigger,
     Signal,
-    Memo { f: Rc<dyn AnyComputation> },
-    Effect { f: Rc<dyn AnyComputation> },
+    Memo {
+        f: Rc<dyn AnyComputation>,
+    },
+    Effect {
+        f: Rc<dyn AnyComputation>,
+        priority: EffectPriority,
+    },
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -0,0 +1,525 @@
+use crate::{
+    create_isomorphic_effect, create_rw_signal, macros::debug_warn,
+    store_value, RwSignal, Serializable, SignalSet, SignalWith,
+};
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc, time::Duration};
+
+/// Creates an [`RwSignal`] whose value is saved to `backend` under `key`, and
+/// loaded from it again the next time the signal is created, e.g., after the
+/// page is reloaded.
+///
+/// If nothing has been saved under `key` yet, or the saved value cannot be
+/// deserialized, the signal starts with `default`.
+///
+/// ## Backends
+/// - [`LocalStorage`] and [`SessionStorage`] save the value in the browser's
+///   [Web Storage](https://developer.mozilla.org/en-US/docs/Web/API/Web_Storage_API).
+///   When the value is changed in another tab, the signal is updated to match.
+/// - [`CookieStorage`] saves the value in a cookie. Because the cookie is
+///   sent along with each request, the server can render the saved value too.
+/// - [`MemoryStorage`] keeps the value in memory, which is useful on the
+///   server and in tests.
+///
+/// Any other storage can be used by implementing [`StorageBackend`].
+///
+/// ## Hydration
+/// The server cannot read the browser's Web Storage, so it always renders
+/// `default` for signals saved there. To avoid a mismatch while hydrating, a
+/// signal that was rendered on the server only loads its saved value once
+/// hydration has finished. Cookies can be read on both sides, so a signal
+/// saved in [`CookieStorage`] starts with its saved value everywhere, as long
+/// as the server integration provides the [`RequestCookies`].
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// let storage = MemoryStorage::default();
+///
+/// let theme = create_persisted_signal("theme", "light".to_string(), storage.clone());
+/// assert_eq!(theme.get(), "light");
+/// theme.set("dark".to_string());
+///
+/// // e.g., after reloading the page
+/// let theme = create_persisted_signal("theme", "light".to_string(), storage);
+/// assert_eq!(theme.get(), "dark");
+/// # runtime.dispose();
+/// ```
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>()
+        )
+    )
+)]
+#[track_caller]
+pub fn create_persisted_signal<T, B>(
+    key: impl Into<String>,
+    default: T,
+    backend: B,
+) -> RwSignal<T>
+where
+    T: Serializable + Clone + 'static,
+    B: StorageBackend,
+{
+    let key: Rc<str> = key.into().into();
+    let backend = Rc::new(backend);
+    let signal = create_rw_signal(default.clone());
+    // the last serialized value that was loaded or saved, which doesn't need
+    // to be written to the backend again
+    let last = store_value(None::<String>);
+
+    let load = {
+        let key = key.clone();
+        move |raw: Option<String>| {
+            let value = match raw.as_deref().map(T::de) {
+                Some(Ok(value)) => value,
+                Some(Err(e)) => {
+                    debug_warn!(
+                        "[create_persisted_signal] could not deserialize the \
+                         value saved for {key:?}: {e}"
+                    );
+                    return;
+                }
+                None => default.clone(),
+            };
+            if last.with_value(|last| *last != raw) {
+                last.set_value(raw);
+                signal.set(value);
+            }
+        }
+    };
+
+    // while hydrating, start with the same value as the server did
+    if cfg!(feature = "hydrate") && !backend.is_available_on_server() {
+        let key = key.clone();
+        let backend = backend.clone();
+        let load = load.clone();
+        crate::create_effect(move |_| {
+            if let Some(raw) = backend.get(&key) {
+                load(Some(raw));
+            }
+        });
+    } else if let Some(raw) = backend.get(&key) {
+        load(Some(raw));
+    }
+
+    backend.subscribe(&key, Box::new(load));
+
+    create_isomorphic_effect(move |prev: Option<()>| {
+        let serialized = signal.with(|value| value.ser());
+        // the initial value has either just been loaded, or is the default
+        if prev.is_none() {
+            return;
+        }
+        match serialized {
+            Ok(serialized) => {
+                if last.with_value(|last| {
+                    last.as_deref() != Some(serialized.as_str())
+                }) {
+                    backend.set(&key, &serialized);
+                    last.set_value(Some(serialized));
+                }
+            }
+            Err(e) => {
+                debug_warn!(
+                    "[create_persisted_signal] could not serialize the value \
+                     for {key:?}: {e}"
+                );
+            }
+        }
+    });
+
+    signal
+}
+
+/// Somewhere that [`create_persisted_signal`] can save serialized values.
+pub trait StorageBackend: 'static {
+    /// Returns the value saved under `key`, if any.
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Saves `value` under `key`.
+    fn set(&self, key: &str, value: &str);
+
+    /// Whether the server can read the same values as the browser, so that
+    /// the saved value can be used while rendering on the server and
+    /// hydrating. Defaults to `false`.
+    fn is_available_on_server(&self) -> bool {
+        false
+    }
+
+    /// Calls `on_change` with the new value whenever the value saved under
+    /// `key` is changed from somewhere else, like another tab, until the
+    /// current reactive owner is disposed. The value is [`None`] if it was
+    /// removed.
+    ///
+    /// Backends that cannot be changed from elsewhere do not need to
+    /// implement this.
+    fn subscribe(&self, key: &str, on_change: Box<dyn Fn(Option<String>)>) {
+        _ = (key, on_change);
+    }
+}
+
+/// Saves values in the browser's
+/// [`localStorage`](https://developer.mozilla.org/en-US/docs/Web/API/Window/localStorage),
+/// which keeps them until they are removed, and shares them between tabs.
+///
+/// Outside the browser, nothing is saved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalStorage;
+
+/// Saves values in the browser's
+/// [`sessionStorage`](https://developer.mozilla.org/en-US/docs/Web/API/Window/sessionStorage),
+/// which keeps them until the tab is closed.
+///
+/// Outside the browser, nothing is saved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SessionStorage;
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        target_arch = "wasm32",
+        any(feature = "hydrate", feature = "csr")
+    ))] {
+        impl StorageBackend for LocalStorage {
+            fn get(&self, key: &str) -> Option<String> {
+                web_storage::get(web_storage::local(), key)
+            }
+
+            fn set(&self, key: &str, value: &str) {
+                web_storage::set(web_storage::local(), key, value);
+            }
+
+            fn subscribe(
+                &self,
+                key: &str,
+                on_change: Box<dyn Fn(Option<String>)>,
+            ) {
+                web_storage::subscribe(web_storage::local(), key, on_change);
+            }
+        }
+
+        impl StorageBackend for SessionStorage {
+            fn get(&self, key: &str) -> Option<String> {
+                web_storage::get(web_storage::session(), key)
+            }
+
+            fn set(&self, key: &str, value: &str) {
+                web_storage::set(web_storage::session(), key, value);
+            }
+
+            fn subscribe(
+                &self,
+                key: &str,
+                on_change: Box<dyn Fn(Option<String>)>,
+            ) {
+                web_storage::subscribe(web_storage::session(), key, on_change);
+            }
+        }
+
+        mod web_storage {
+            use crate::on_cleanup;
+            use wasm_bindgen::{prelude::Closure, JsCast};
+            use web_sys::{Storage, StorageEvent};
+
+            pub fn local() -> Option<Storage> {
+                web_sys::window()?.local_storage().ok().flatten()
+            }
+
+            pub fn session() -> Option<Storage> {
+                web_sys::window()?.session_storage().ok().flatten()
+            }
+
+            pub fn get(storage: Option<Storage>, key: &str) -> Option<String> {
+                storage?.get_item(key).ok().flatten()
+            }
+
+            pub fn set(storage: Option<Storage>, key: &str, value: &str) {
+                // storage can be full, or disabled by the user
+                if let Some(storage) = storage {
+                    _ = storage.set_item(key, value);
+                }
+            }
+
+            pub fn subscribe(
+                storage: Option<Storage>,
+                key: &str,
+                on_change: Box<dyn Fn(Option<String>)>,
+            ) {
+                let (Some(window), Some(storage)) = (web_sys::window(), storage)
+                else {
+                    return;
+                };
+                let key = key.to_string();
+                // only fired for changes made by other documents
+                let listener =
+                    Closure::<dyn Fn(StorageEvent)>::new(move |ev: StorageEvent| {
+                        let same_storage = ev.storage_area().is_some_and(|area| {
+                            js_sys::Object::is(&area, &storage)
+                        });
+                        if !same_storage {
+                            return;
+                        }
+                        match ev.key() {
+                            Some(changed) if changed == key => {
+                                on_change(ev.new_value())
+                            }
+                            // the whole storage was cleared
+                            None => on_change(None),
+                            _ => {}
+                        }
+                    });
+                _ = window.add_event_listener_with_callback(
+                    "storage",
+                    listener.as_ref().unchecked_ref(),
+                );
+                on_cleanup(move || {
+                    _ = window.remove_event_listener_with_callback(
+                        "storage",
+                        listener.as_ref().unchecked_ref(),
+                    );
+                });
+            }
+        }
+    } else {
+        impl StorageBackend for LocalStorage {
+            fn get(&self, _key: &str) -> Option<String> {
+                None
+            }
+
+            fn set(&self, _key: &str, _value: &str) {}
+        }
+
+        impl StorageBackend for SessionStorage {
+            fn get(&self, _key: &str) -> Option<String> {
+                None
+            }
+
+            fn set(&self, _key: &str, _value: &str) {}
+        }
+    }
+}
+
+/// Saves values in cookies, so that they are sent to the server with each
+/// request.
+///
+/// In the browser, values are read from and written to `document.cookie`. On
+/// the server, values are read from the [`RequestCookies`] in context, and
+/// are not written; use [`CookieStorage::set_cookie_header`] to save them as
+/// part of the response instead.
+///
+/// Values are percent-encoded, and cookies are limited to around 4KB each, so
+/// this is best suited to small values like preferences.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CookieStorage {
+    path: String,
+    max_age: Option<Duration>,
+    same_site: SameSite,
+}
+
+/// The [`SameSite`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Set-Cookie#samesitesamesite-value)
+/// attribute of a cookie set by [`CookieStorage`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SameSite {
+    /// The cookie is only sent with requests from the same site.
+    Strict,
+    /// The cookie is also sent when navigating to the site from elsewhere.
+    #[default]
+    Lax,
+    /// The cookie is sent with all requests. Browsers only accept these
+    /// cookies over HTTPS.
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        })
+    }
+}
+
+impl Default for CookieStorage {
+    fn default() -> Self {
+        Self {
+            path: "/".to_string(),
+            // about a year
+            max_age: Some(Duration::from_secs(60 * 60 * 24 * 365)),
+            same_site: SameSite::default(),
+        }
+    }
+}
+
+impl CookieStorage {
+    /// Sets the path the cookies are sent for. Defaults to `/`.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Sets how long the cookies are kept for, or [`None`] to keep them until
+    /// the browser is closed. Defaults to about a year.
+    pub fn max_age(mut self, max_age: Option<Duration>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Sets the `SameSite` attribute of the cookies. Defaults to
+    /// [`SameSite::Lax`].
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// Returns the value of a `Set-Cookie` header that saves `value` under
+    /// `key`, e.g., to save a value while responding to a request on the
+    /// server.
+    pub fn set_cookie_header(&self, key: &str, value: &str) -> String {
+        let mut cookie = format!(
+            "{}={}; Path={}; SameSite={}",
+            percent_encode(key),
+            percent_encode(value),
+            self.path,
+            self.same_site
+        );
+        if let Some(max_age) = self.max_age {
+            cookie.push_str(&format!("; Max-Age={}", max_age.as_secs()));
+        }
+        if self.same_site == SameSite::None {
+            cookie.push_str("; Secure");
+        }
+        cookie
+    }
+}
+
+impl StorageBackend for CookieStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        cfg_if::cfg_if! {
+            if #[cfg(all(
+                target_arch = "wasm32",
+                any(feature = "hydrate", feature = "csr")
+            ))] {
+                use wasm_bindgen::JsCast;
+
+                let cookies = web_sys::window()?
+                    .document()?
+                    .dyn_into::<web_sys::HtmlDocument>()
+                    .ok()?
+                    .cookie()
+                    .ok()?;
+                RequestCookies::new(cookies).get(key)
+            } else {
+                crate::use_context::<RequestCookies>()?.get(key)
+            }
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn set(&self, key: &str, value: &str) {
+        #[cfg(all(
+            target_arch = "wasm32",
+            any(feature = "hydrate", feature = "csr")
+        ))]
+        {
+            use wasm_bindgen::JsCast;
+
+            if let Some(document) = web_sys::window()
+                .and_then(|window| window.document())
+                .and_then(|document| {
+                    document.dyn_into::<web_sys::HtmlDocument>().ok()
+                })
+            {
+                _ = document.set_cookie(&self.set_cookie_header(key, value));
+            }
+        }
+    }
+
+    fn is_available_on_server(&self) -> bool {
+        true
+    }
+}
+
+/// The cookies sent with the current request, which [`CookieStorage`] reads
+/// from when rendering on the server.
+///
+/// Server integrations provide this as context for each request.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RequestCookies(String);
+
+impl RequestCookies {
+    /// Creates the cookies from the value of a `Cookie` header.
+    pub fn new(header: impl Into<String>) -> Self {
+        Self(header.into())
+    }
+
+    /// Returns the percent-decoded value of the cookie called `name`, if any.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.0.split(';').find_map(|cookie| {
+            let (key, value) = cookie.split_once('=')?;
+            (percent_decode(key.trim())? == name)
+                .then(|| percent_decode(value.trim()))
+                .flatten()
+        })
+    }
+}
+
+/// Keeps values in memory, shared between all clones of the same
+/// `MemoryStorage`.
+///
+/// This is useful on the server, where there is no browser storage, and in
+/// tests.
+#[derive(Default, Clone)]
+pub struct MemoryStorage {
+    values: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl fmt::Debug for MemoryStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryStorage")
+            .field("values", &*self.values.borrow())
+            .finish()
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
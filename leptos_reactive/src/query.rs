@@ -0,0 +1,674 @@
+//! A keyed cache that sits on top of [`Resource`], so that components asking
+//! for the same data share a single fetch and a single cached result.
+
+use crate::{
+    create_isomorphic_effect, create_memo, create_resource, on_cleanup,
+    provide_context, serialization::Serializable, spawn_local, use_context,
+    Resource, SignalGet, SignalGetUntracked, SignalSet, SignalWith,
+};
+use futures::{future::WeakShared, FutureExt};
+use rustc_hash::FxHashMap;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    time::Duration,
+};
+
+type Fetch<V> = Pin<Box<dyn Future<Output = V>>>;
+type ObserverSetter = Rc<dyn Fn(&dyn Any)>;
+
+/// Options that control how long a cached query result is considered fresh,
+/// and how long it is kept once nothing is observing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryOptions {
+    /// How long a cached value is considered fresh. While a value is fresh,
+    /// new queries for the same key use it without fetching. Once it is stale,
+    /// it is still returned immediately, but revalidated in the background.
+    ///
+    /// Defaults to `0`, i.e., every new observer triggers a revalidation.
+    pub stale_time: Duration,
+    /// How long an entry that is no longer observed by any query is kept
+    /// before it is garbage-collected.
+    ///
+    /// Defaults to five minutes.
+    pub gc_time: Duration,
+}
+
+impl Default for QueryOptions {
+    fn default() -> Self {
+        Self {
+            stale_time: Duration::ZERO,
+            gc_time: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// A cache of query results, keyed by the serialized form of each query’s
+/// key. It is shared by all queries that can access it via context; see
+/// [`provide_query_client`].
+#[derive(Clone, Default)]
+pub struct QueryClient(Rc<RefCell<QueryCache>>);
+
+#[derive(Default)]
+struct QueryCache {
+    entries: FxHashMap<String, QueryEntry>,
+    observers: FxHashMap<usize, QueryObserver>,
+    next_observer_id: usize,
+}
+
+struct QueryEntry {
+    /// The key the entry was created for, before it was serialized.
+    key: Option<Rc<dyn Any>>,
+    value: Option<Rc<dyn Any>>,
+    updated_at: f64,
+    invalidated: bool,
+    in_flight: Option<Box<dyn InFlight>>,
+    unused_since: Option<f64>,
+    gc_time: Duration,
+}
+
+struct QueryObserver {
+    key: Option<String>,
+    gc_time: Duration,
+    set: ObserverSetter,
+    refetch: Rc<dyn Fn()>,
+}
+
+/// A fetch that is currently running for an entry. Only a weak handle is
+/// kept, so that a fetch is dropped (and any request it made aborted) once
+/// every resource waiting for it has moved on.
+trait InFlight {
+    fn as_any(&self) -> &dyn Any;
+
+    fn is_alive(&self) -> bool;
+}
+
+impl<V: Clone + 'static> InFlight for WeakShared<Fetch<V>> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_alive(&self) -> bool {
+        self.upgrade().is_some()
+    }
+}
+
+impl QueryEntry {
+    fn new(gc_time: Duration) -> Self {
+        Self {
+            key: None,
+            value: None,
+            updated_at: 0.0,
+            invalidated: false,
+            in_flight: None,
+            unused_since: None,
+            gc_time,
+        }
+    }
+
+    fn value<V: Clone + 'static>(&self) -> Option<V> {
+        self.value.as_ref()?.downcast_ref::<V>().cloned()
+    }
+
+    fn is_fresh(&self, now: f64, stale_time: Duration) -> bool {
+        self.value.is_some()
+            && !self.invalidated
+            && now - self.updated_at < stale_time.as_secs_f64() * 1000.0
+    }
+}
+
+impl fmt::Debug for QueryClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cache = self.0.borrow();
+        f.debug_struct("QueryClient")
+            .field("entries", &cache.entries.len())
+            .field("observers", &cache.observers.len())
+            .finish()
+    }
+}
+
+impl QueryClient {
+    /// Creates a new, empty query cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached value for this key, if any, regardless of whether
+    /// it is stale.
+    pub fn get_query_data<K, V>(&self, key: &K) -> Option<V>
+    where
+        K: Serializable,
+        V: Clone + 'static,
+    {
+        let key = cache_key(key)?;
+        self.0.borrow().entries.get(&key)?.value()
+    }
+
+    /// Replaces the cached value for this key, and updates every query that
+    /// is currently observing it.
+    ///
+    /// A new entry is kept for the `gc_time` of the queries observing it, or
+    /// the default `gc_time` if there are none.
+    ///
+    /// A fetch for the key that is already running keeps running, and is
+    /// shared with any query that starts observing the key in the meantime.
+    pub fn set_query_data<K, V>(&self, key: &K, value: V)
+    where
+        K: Serializable + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let Some(serialized) = cache_key(key) else {
+            return;
+        };
+        self.store(
+            &serialized,
+            Rc::new(key.clone()),
+            value.clone(),
+            QueryOptions::default().gc_time,
+            false,
+        );
+        self.notify(&serialized, &value);
+    }
+
+    /// Marks the entry for this key as stale and refetches it in every query
+    /// that is currently observing it.
+    pub fn invalidate<K>(&self, key: &K)
+    where
+        K: Serializable,
+    {
+        if let Some(key) = cache_key(key) {
+            self.invalidate_matching(|k, _| k == key);
+        }
+    }
+
+    /// Marks every entry whose key is a `K` for which `matches` returns
+    /// `true` as stale, and refetches them in every query that is currently
+    /// observing them.
+    ///
+    /// This is useful to invalidate a group of related keys at once:
+    /// ```
+    /// # use leptos_reactive::*;
+    /// # let runtime = create_runtime();
+    /// let client = provide_query_client();
+    /// // invalidates `("todos", 1)`, `("todos", 2)`, ..., but not `("users", 1)`
+    /// client.invalidate_where(|(kind, _): &(String, u32)| kind == "todos");
+    /// # runtime.dispose();
+    /// ```
+    pub fn invalidate_where<K: 'static>(&self, matches: impl Fn(&K) -> bool) {
+        self.invalidate_matching(|_, key| {
+            key.and_then(|key| key.downcast_ref::<K>())
+                .is_some_and(&matches)
+        });
+    }
+
+    /// Marks every entry in the cache as stale, and refetches every query that
+    /// is currently observed.
+    pub fn invalidate_all(&self) {
+        self.invalidate_matching(|_, _| true);
+    }
+
+    /// Removes the entry for this key from the cache.
+    pub fn remove<K>(&self, key: &K)
+    where
+        K: Serializable,
+    {
+        if let Some(key) = cache_key(key) {
+            self.0.borrow_mut().entries.remove(&key);
+        }
+    }
+
+    /// Removes all entries that have not been observed by any query for
+    /// longer than their `gc_time`.
+    ///
+    /// This runs automatically whenever a query fetches or is disposed, so
+    /// it’s usually not necessary to call it yourself.
+    pub fn gc(&self) {
+        let now = now();
+        self.0.borrow_mut().entries.retain(|_, entry| {
+            entry.in_flight.as_ref().is_some_and(|f| f.is_alive())
+                || match entry.unused_since {
+                    Some(since) => {
+                        now - since < entry.gc_time.as_secs_f64() * 1000.0
+                    }
+                    None => true,
+                }
+        });
+    }
+
+    /// The number of entries currently held in the cache.
+    pub fn len(&self) -> usize {
+        self.0.borrow().entries.len()
+    }
+
+    /// Whether the cache is currently empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().entries.is_empty()
+    }
+
+    /// Invalidates the entries for which `matches` returns `true`, given
+    /// their serialized key and the key they were created for.
+    fn invalidate_matching(
+        &self,
+        matches: impl Fn(&str, Option<&dyn Any>) -> bool,
+    ) {
+        let refetches = {
+            let mut cache = self.0.borrow_mut();
+            for (key, entry) in cache.entries.iter_mut() {
+                if matches(key, entry.key.as_deref()) {
+                    entry.invalidated = true;
+                }
+            }
+            let cache = &*cache;
+            cache
+                .observers
+                .values()
+                .filter(|o| {
+                    o.key.as_deref().is_some_and(|key| {
+                        let entry = cache.entries.get(key);
+                        matches(key, entry.and_then(|e| e.key.as_deref()))
+                    })
+                })
+                .map(|o| Rc::clone(&o.refetch))
+                .collect::<Vec<_>>()
+        };
+        for refetch in refetches {
+            refetch();
+        }
+    }
+
+    fn fetch<V, Fu>(
+        &self,
+        key: String,
+        typed_key: Rc<dyn Any>,
+        options: QueryOptions,
+        fetcher: impl FnOnce() -> Fu,
+    ) -> Pin<Box<dyn Future<Output = V>>>
+    where
+        V: Clone + 'static,
+        Fu: Future<Output = V> + 'static,
+    {
+        self.gc();
+        let now = now();
+
+        let (cached, in_flight) = {
+            let mut cache = self.0.borrow_mut();
+            let entry = cache
+                .entries
+                .entry(key.clone())
+                .or_insert_with(|| QueryEntry::new(options.gc_time));
+            entry.gc_time = entry.gc_time.max(options.gc_time);
+            entry.key.get_or_insert_with(|| Rc::clone(&typed_key));
+            if entry.is_fresh(now, options.stale_time) {
+                if let Some(value) = entry.value::<V>() {
+                    return Box::pin(async move { value });
+                }
+            }
+            let in_flight = entry
+                .in_flight
+                .as_ref()
+                .and_then(|f| f.as_any().downcast_ref::<WeakShared<Fetch<V>>>())
+                .and_then(WeakShared::upgrade);
+            (entry.value::<V>(), in_flight)
+        };
+
+        // deduplicate: only start a new fetch if there isn't one in flight
+        let fut = in_flight.unwrap_or_else(|| {
+            let fut = fetcher();
+            let fut = {
+                let client = self.clone();
+                let key = key.clone();
+                Box::pin(async move {
+                    let value = fut.await;
+                    client.store(
+                        &key,
+                        typed_key,
+                        value.clone(),
+                        options.gc_time,
+                        true,
+                    );
+                    value
+                }) as Fetch<V>
+            }
+            .shared();
+            if let (Some(entry), Some(weak)) =
+                (self.0.borrow_mut().entries.get_mut(&key), fut.downgrade())
+            {
+                entry.in_flight = Some(Box::new(weak));
+            }
+            fut
+        });
+
+        match cached {
+            // stale-while-revalidate: return the stale value now, and update
+            // observers once the revalidation has finished
+            Some(stale) => {
+                spawn_local({
+                    let client = self.clone();
+                    let key = key.clone();
+                    async move {
+                        let value = fut.await;
+                        client.notify(&key, &value);
+                    }
+                });
+                // if the revalidation already resolved synchronously, use it
+                let value = self
+                    .0
+                    .borrow()
+                    .entries
+                    .get(&key)
+                    .filter(|entry| entry.in_flight.is_none())
+                    .and_then(QueryEntry::value::<V>)
+                    .unwrap_or(stale);
+                Box::pin(async move { value })
+            }
+            None => Box::pin(fut),
+        }
+    }
+
+    /// Stores a value for the key. Only a finished fetch clears the fetch
+    /// that is in flight, so that a value set in the meantime doesn't stop
+    /// it from being shared.
+    fn store<V: 'static>(
+        &self,
+        key: &str,
+        typed_key: Rc<dyn Any>,
+        value: V,
+        gc_time: Duration,
+        fetched: bool,
+    ) {
+        let mut cache = self.0.borrow_mut();
+        // the entry is kept for as long as any query observing it asks for
+        let observed_gc_time = cache
+            .observers
+            .values()
+            .filter(|o| o.key.as_deref() == Some(key))
+            .map(|o| o.gc_time)
+            .max();
+        let observed = observed_gc_time.is_some();
+        let entry = cache.entries.entry(key.to_string()).or_insert_with(|| {
+            QueryEntry::new(observed_gc_time.unwrap_or(gc_time))
+        });
+        if let Some(gc_time) = observed_gc_time {
+            entry.gc_time = entry.gc_time.max(gc_time);
+        }
+        entry.key.get_or_insert(typed_key);
+        entry.value = Some(Rc::new(value));
+        entry.updated_at = now();
+        entry.invalidated = false;
+        if fetched {
+            entry.in_flight = None;
+        }
+        if !observed && entry.unused_since.is_none() {
+            entry.unused_since = Some(now());
+        }
+    }
+
+    fn hydrate<V: Clone + 'static>(
+        &self,
+        key: &str,
+        typed_key: Rc<dyn Any>,
+        value: &V,
+        gc_time: Duration,
+    ) {
+        let has_value = self
+            .0
+            .borrow()
+            .entries
+            .get(key)
+            .map(|entry| entry.value.is_some())
+            .unwrap_or(false);
+        if !has_value {
+            self.store(key, typed_key, value.clone(), gc_time, false);
+        }
+    }
+
+    fn notify<V: 'static>(&self, key: &str, value: &V) {
+        let setters = self
+            .0
+            .borrow()
+            .observers
+            .values()
+            .filter(|o| o.key.as_deref() == Some(key))
+            .map(|o| Rc::clone(&o.set))
+            .collect::<Vec<_>>();
+        for set in setters {
+            set(value);
+        }
+    }
+
+    fn observe(&self, observer: QueryObserver) -> usize {
+        let mut cache = self.0.borrow_mut();
+        let id = cache.next_observer_id;
+        cache.next_observer_id += 1;
+        cache.observers.insert(id, observer);
+        id
+    }
+
+    fn set_observer_key(&self, id: usize, key: Option<String>) {
+        let prev = {
+            let mut cache = self.0.borrow_mut();
+            if let Some(entry) =
+                key.as_ref().and_then(|key| cache.entries.get_mut(key))
+            {
+                entry.unused_since = None;
+            }
+            cache
+                .observers
+                .get_mut(&id)
+                .and_then(|o| std::mem::replace(&mut o.key, key))
+        };
+        if let Some(prev) = prev {
+            self.release(&prev);
+        }
+    }
+
+    fn unobserve(&self, id: usize) {
+        let prev = self
+            .0
+            .borrow_mut()
+            .observers
+            .remove(&id)
+            .and_then(|o| o.key);
+        if let Some(prev) = prev {
+            self.release(&prev);
+        }
+        self.gc();
+    }
+
+    fn release(&self, key: &str) {
+        let mut cache = self.0.borrow_mut();
+        let observed = cache
+            .observers
+            .values()
+            .any(|o| o.key.as_deref() == Some(key));
+        if !observed {
+            if let Some(entry) = cache.entries.get_mut(key) {
+                entry.unused_since.get_or_insert_with(now);
+            }
+        }
+    }
+}
+
+/// Creates a new [`QueryClient`] and provides it via context, so that every
+/// query created beneath the current owner shares the same cache.
+///
+/// This should usually be called once, near the root of your application.
+pub fn provide_query_client() -> QueryClient {
+    let client = QueryClient::new();
+    provide_context(client.clone());
+    client
+}
+
+/// Returns the [`QueryClient`] provided via context. If none has been
+/// provided, creates a new one and provides it beneath the current owner.
+pub fn use_query_client() -> QueryClient {
+    use_context::<QueryClient>().unwrap_or_else(provide_query_client)
+}
+
+/// Creates a [`Resource`] whose results are stored in the [`QueryClient`]
+/// provided via context, using the default [`QueryOptions`].
+///
+/// The `key` is a reactive source, like the `source` of [`create_resource`]:
+/// whenever it changes, the query switches to the entry for the new key.
+/// Queries that use the same key share their results:
+/// 1. a fresh cached value is used without calling the `fetcher`,
+/// 2. a stale cached value is returned immediately and revalidated in the
+///    background (“stale-while-revalidate”), and
+/// 3. concurrent fetches for the same key are deduplicated.
+///
+/// Because this is built on [`create_resource`], query results are serialized
+/// from the server to the client like any other resource, and are written into
+/// the client’s cache when it hydrates.
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// # if false {
+/// async fn fetch_todo(id: u32) -> String {
+///     format!("todo #{id}")
+/// }
+///
+/// let client = provide_query_client();
+/// let (id, set_id) = create_signal(1);
+/// let todo = create_query(move || id.get(), fetch_todo);
+///
+/// // later, after a mutation
+/// client.invalidate(&1);
+/// # }
+/// # runtime.dispose();
+/// ```
+#[track_caller]
+pub fn create_query<K, V, Fu>(
+    key: impl Fn() -> K + 'static,
+    fetcher: impl Fn(K) -> Fu + 'static,
+) -> Resource<K, V>
+where
+    K: Serializable + PartialEq + Clone + 'static,
+    V: Serializable + Clone + 'static,
+    Fu: Future<Output = V> + 'static,
+{
+    create_query_with_options(key, fetcher, QueryOptions::default())
+}
+
+/// Creates a [`Resource`] whose results are stored in the [`QueryClient`]
+/// provided via context, using the given [`QueryOptions`].
+///
+/// See [`create_query`] for details.
+#[track_caller]
+pub fn create_query_with_options<K, V, Fu>(
+    key: impl Fn() -> K + 'static,
+    fetcher: impl Fn(K) -> Fu + 'static,
+    options: QueryOptions,
+) -> Resource<K, V>
+where
+    K: Serializable + PartialEq + Clone + 'static,
+    V: Serializable + Clone + 'static,
+    Fu: Future<Output = V> + 'static,
+{
+    let client = use_query_client();
+    let key = create_memo(move |_| key());
+
+    // the observer is registered before the resource exists, so that its key
+    // is known by the time the resource first fetches
+    let resource = Rc::new(Cell::new(None::<Resource<K, V>>));
+    let observer = client.observe(QueryObserver {
+        key: None,
+        gc_time: options.gc_time,
+        set: Rc::new({
+            let resource = Rc::clone(&resource);
+            move |value: &dyn Any| {
+                if let (Some(resource), Some(value)) =
+                    (resource.get(), value.downcast_ref::<V>())
+                {
+                    resource.set(value.clone());
+                }
+            }
+        }),
+        refetch: Rc::new({
+            let resource = Rc::clone(&resource);
+            move || {
+                if let Some(resource) = resource.get() {
+                    resource.refetch();
+                }
+            }
+        }),
+    });
+    on_cleanup({
+        let client = client.clone();
+        move || client.unobserve(observer)
+    });
+    create_isomorphic_effect({
+        let client = client.clone();
+        move |_| {
+            let key = key.with(cache_key);
+            client.set_observer_key(observer, key);
+        }
+    });
+
+    let fetcher = Rc::new(fetcher);
+    let query = create_resource(move || key.get(), {
+        let client = client.clone();
+        move |k: K| {
+            let fetcher = Rc::clone(&fetcher);
+            match cache_key(&k) {
+                Some(key) => {
+                    let typed_key = Rc::new(k.clone());
+                    client.fetch(key, typed_key, options, move || fetcher(k))
+                }
+                // a key that can't be serialized can't be cached either
+                None => Box::pin(fetcher(k)),
+            }
+        }
+    });
+    resource.set(Some(query));
+
+    // values that arrive without calling the fetcher (i.e., resources
+    // resolved on the server during hydration) are written into the cache
+    if let Some(value) = query.value_signal() {
+        create_isomorphic_effect(move |_| {
+            value.with(|value| {
+                if let Some(value) = value {
+                    let k = key.get_untracked();
+                    if let Some(serialized) = cache_key(&k) {
+                        client.hydrate(
+                            &serialized,
+                            Rc::new(k),
+                            value,
+                            options.gc_time,
+                        );
+                    }
+                }
+            })
+        });
+    }
+
+    query
+}
+
+fn cache_key<K: Serializable>(key: &K) -> Option<String> {
+    key.ser()
+        .map_err(|e| {
+            crate::macros::debug_warn!(
+                "[create_query] could not serialize the query key, so it \
+                 will not be cached: {e}"
+            );
+        })
+        .ok()
+}
+
+fn now() -> f64 {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_arch = "wasm32", any(feature = "csr", feature = "hydrate")))] {
+            js_sys::Date::now()
+        } else {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs_f64() * 1000.0)
+                .unwrap_or_default()
+        }
+    }
+}
@@ -1,15 +1,22 @@
-#[cfg(feature = "experimental-islands")]
-use crate::SharedContext;
 #[cfg(debug_assertions)]
 use crate::SpecialNonReactiveZone;
 use crate::{
     create_isomorphic_effect, create_memo, create_render_effect, create_signal,
-    queue_microtask, runtime::with_runtime, serialization::Serializable,
-    signal_prelude::format_signal_warning, spawn::spawn_local,
-    suspense::LocalStatus, use_context, GlobalSuspenseContext, Memo,
-    ReadSignal, ScopeProperty, Signal, SignalDispose, SignalGet,
-    SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
-    SignalWithUntracked, SuspenseContext, WriteSignal,
+    queue_microtask,
+    runtime::{with_runtime, PinnedStream},
+    serialization::{Serializable, SerializationError},
+    signal_prelude::format_signal_warning,
+    spawn::spawn_local,
+    suspense::LocalStatus,
+    use_context, GlobalSuspenseContext, Memo, ReadSignal, ScopeProperty,
+    SharedContext, Signal, SignalDispose, SignalGet, SignalGetUntracked,
+    SignalSet, SignalUpdate, SignalWith, SignalWithUntracked, SuspenseContext,
+    WriteSignal,
+};
+use futures::{
+    channel::mpsc,
+    future::AbortHandle,
+    stream::{self, Stream, StreamExt},
 };
 use std::{
     any::Any,
@@ -205,16 +212,35 @@ where
     S: PartialEq + Clone + 'static,
     T: Serializable + 'static,
     Fu: Future<Output = T> + 'static,
+{
+    //crate::macros::debug_warn!("creating fetcher");
+    let fetcher =
+        Rc::new(move |s| Box::pin(stream::once(fetcher(s))) as PinnedStream<T>);
+    create_serializable_resource_from_stream(
+        source,
+        fetcher,
+        initial_value,
+        serializable,
+        false,
+    )
+}
+
+fn create_serializable_resource_from_stream<S, T>(
+    source: impl Fn() -> S + 'static,
+    fetcher: Rc<dyn Fn(S) -> PinnedStream<T>>,
+    initial_value: Option<T>,
+    serializable: ResourceSerialization,
+    streaming: bool,
+) -> Resource<S, T>
+where
+    S: PartialEq + Clone + 'static,
+    T: Serializable + 'static,
 {
     let resolved = initial_value.is_some();
     let (value, set_value) = create_signal(initial_value);
 
     let (loading, set_loading) = create_signal(false);
 
-    //crate::macros::debug_warn!("creating fetcher");
-    let fetcher = Rc::new(move |s| {
-        Box::pin(fetcher(s)) as Pin<Box<dyn Future<Output = T>>>
-    });
     let source = create_memo(move |_| source());
 
     let r = Rc::new(ResourceState {
@@ -227,8 +253,14 @@ where
         resolved: Rc::new(Cell::new(resolved)),
         scheduled: Rc::new(Cell::new(false)),
         version: Rc::new(Cell::new(0)),
+        abort_handle: Default::default(),
         suspense_contexts: Default::default(),
         serializable,
+        streaming,
+        stream_updates: (streaming
+            && cfg!(feature = "ssr")
+            && SharedContext::stream_resource_updates_enabled())
+        .then(StreamUpdates::new),
         #[cfg(feature = "experimental-islands")]
         should_send_to_client: Default::default(),
     });
@@ -358,15 +390,27 @@ where
     S: PartialEq + Clone + 'static,
     T: 'static,
     Fu: Future<Output = T> + 'static,
+{
+    let fetcher =
+        Rc::new(move |s| Box::pin(stream::once(fetcher(s))) as PinnedStream<T>);
+    create_local_resource_from_stream(source, fetcher, initial_value, false)
+}
+
+fn create_local_resource_from_stream<S, T>(
+    source: impl Fn() -> S + 'static,
+    fetcher: Rc<dyn Fn(S) -> PinnedStream<T>>,
+    initial_value: Option<T>,
+    streaming: bool,
+) -> Resource<S, T>
+where
+    S: PartialEq + Clone + 'static,
+    T: 'static,
 {
     let resolved = initial_value.is_some();
     let (value, set_value) = create_signal(initial_value);
 
     let (loading, set_loading) = create_signal(false);
 
-    let fetcher = Rc::new(move |s| {
-        Box::pin(fetcher(s)) as Pin<Box<dyn Future<Output = T>>>
-    });
     let source = create_memo(move |_| source());
 
     let r = Rc::new(ResourceState {
@@ -379,8 +423,11 @@ where
         resolved: Rc::new(Cell::new(resolved)),
         scheduled: Rc::new(Cell::new(false)),
         version: Rc::new(Cell::new(0)),
+        abort_handle: Default::default(),
         suspense_contexts: Default::default(),
         serializable: ResourceSerialization::Local,
+        streaming,
+        stream_updates: None,
         #[cfg(feature = "experimental-islands")]
         should_send_to_client: Default::default(),
     });
@@ -412,6 +459,99 @@ where
     }
 }
 
+/// Creates a streaming [`Resource`](crate::Resource), which reflects the
+/// latest value yielded by a [`Stream`]. This is the resource counterpart of
+/// [`create_signal_from_stream`](crate::create_signal_from_stream).
+///
+/// Takes a `fetcher` function that generates a [`Stream`] when called and a
+/// `source` signal that provides the argument for the `fetcher`. Whenever the
+/// value of the `source` changes, the previous stream is dropped and a new one
+/// is created and run.
+///
+/// The resource counts as loading for any `<Suspense/>` that reads it until the
+/// stream yields its first item. After that, it keeps updating with every
+/// later item, without suspending again.
+///
+/// When server-side rendering is used with out-of-order or in-order streaming,
+/// each item after the first is also serialized into the HTML stream, so the
+/// client picks up from the latest value the server has seen. The response is
+/// not complete until the stream has ended.
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// # if false {
+/// let (ticks, set_ticks) = create_signal(3);
+/// let countdown = create_stream_resource(
+///     move || ticks.get(),
+///     |n| futures::stream::iter((0..n).rev()),
+/// );
+/// # }
+/// # runtime.dispose();
+/// ```
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>(),
+            signal_ty = %std::any::type_name::<S>(),
+        )
+    )
+)]
+#[track_caller]
+pub fn create_stream_resource<S, T, St>(
+    source: impl Fn() -> S + 'static,
+    fetcher: impl Fn(S) -> St + 'static,
+) -> Resource<S, T>
+where
+    S: PartialEq + Clone + 'static,
+    T: Serializable + 'static,
+    St: Stream<Item = T> + 'static,
+{
+    let fetcher = Rc::new(move |s| Box::pin(fetcher(s)) as PinnedStream<T>);
+    create_serializable_resource_from_stream(
+        source,
+        fetcher,
+        None,
+        ResourceSerialization::Serializable,
+        true,
+    )
+}
+
+/// Creates a _local_ streaming [`Resource`](crate::Resource), which reflects
+/// the latest value yielded by a [`Stream`].
+///
+/// Unlike [`create_stream_resource()`], this [`Stream`] is always run on the
+/// local system and therefore its items do not need to be [`Serializable`].
+///
+/// Local resources do not load on the server, only in the client’s browser.
+#[cfg_attr(
+    any(debug_assertions, feature="ssr"),
+    instrument(
+        level = "trace",
+        skip_all,
+        fields(
+            ty = %std::any::type_name::<T>(),
+            signal_ty = %std::any::type_name::<S>(),
+        )
+    )
+)]
+#[track_caller]
+pub fn create_local_stream_resource<S, T, St>(
+    source: impl Fn() -> S + 'static,
+    fetcher: impl Fn(S) -> St + 'static,
+) -> Resource<S, T>
+where
+    S: PartialEq + Clone + 'static,
+    T: 'static,
+    St: Stream<Item = T> + 'static,
+{
+    let fetcher = Rc::new(move |s| Box::pin(fetcher(s)) as PinnedStream<T>);
+    create_local_resource_from_stream(source, fetcher, None, true)
+}
+
 #[cfg(not(feature = "hydrate"))]
 fn load_resource<S, T>(id: ResourceId, r: Rc<ResourceState<S, T>>)
 where
@@ -431,8 +571,6 @@ where
     S: PartialEq + Clone + 'static,
     T: Serializable + 'static,
 {
-    use wasm_bindgen::{JsCast, UnwrapThrowExt};
-
     _ = with_runtime(|runtime| {
         let mut context = runtime.shared_context.borrow_mut();
         if let Some(data) = context.resolved_resources.remove(&id) {
@@ -451,6 +589,11 @@ where
             r.set_value.update(|n| *n = Some(res));
             r.set_loading.update(|n| *n = false);
 
+            // a streaming resource may still receive later values
+            if r.streaming {
+                register_resource_resolver(id, &r);
+            }
+
             // for reactivity
             r.source.track();
         } else if context.pending_resources.remove(&id) {
@@ -458,40 +601,7 @@ where
             // that it will be set as soon as the server sends the serialized
             // value
             r.set_loading.update(|n| *n = true);
-
-            let resolve = {
-                let resolved = r.resolved.clone();
-                let set_value = r.set_value;
-                let set_loading = r.set_loading;
-                move |res: String| {
-                    let res = T::de(&res).unwrap_or_else(|e| {
-                        panic!(
-                            "could not deserialize Resource JSON for {id:?}: \
-                             {e:?}"
-                        )
-                    });
-                    resolved.set(true);
-                    set_value.update(|n| *n = Some(res));
-                    set_loading.update(|n| *n = false);
-                }
-            };
-            let resolve = wasm_bindgen::closure::Closure::wrap(
-                Box::new(resolve) as Box<dyn Fn(String)>,
-            );
-            let resource_resolvers = js_sys::Reflect::get(
-                &web_sys::window().unwrap(),
-                &wasm_bindgen::JsValue::from_str("__LEPTOS_RESOURCE_RESOLVERS"),
-            )
-            .expect_throw(
-                "no __LEPTOS_RESOURCE_RESOLVERS found in the JS global scope",
-            );
-            let id = serde_json::to_string(&id)
-                .expect_throw("could not serialize Resource ID");
-            _ = js_sys::Reflect::set(
-                &resource_resolvers,
-                &wasm_bindgen::JsValue::from_str(&id),
-                resolve.as_ref().unchecked_ref(),
-            );
+            register_resource_resolver(id, &r);
 
             // for reactivity
             r.source.track()
@@ -503,6 +613,47 @@ where
     })
 }
 
+/// Registers a closure the server’s HTML stream will call with each
+/// serialized value of this resource.
+#[cfg(feature = "hydrate")]
+fn register_resource_resolver<S, T>(id: ResourceId, r: &ResourceState<S, T>)
+where
+    T: Serializable + 'static,
+{
+    use wasm_bindgen::{JsCast, UnwrapThrowExt};
+
+    let resolve = {
+        let resolved = r.resolved.clone();
+        let set_value = r.set_value;
+        let set_loading = r.set_loading;
+        move |res: String| {
+            let res = T::de(&res).unwrap_or_else(|e| {
+                panic!("could not deserialize Resource JSON for {id:?}: {e:?}")
+            });
+            resolved.set(true);
+            set_value.update(|n| *n = Some(res));
+            set_loading.update(|n| *n = false);
+        }
+    };
+    let resolve = wasm_bindgen::closure::Closure::wrap(
+        Box::new(resolve) as Box<dyn Fn(String)>
+    );
+    let resource_resolvers = js_sys::Reflect::get(
+        &web_sys::window().unwrap(),
+        &wasm_bindgen::JsValue::from_str("__LEPTOS_RESOURCE_RESOLVERS"),
+    )
+    .expect_throw(
+        "no __LEPTOS_RESOURCE_RESOLVERS found in the JS global scope",
+    );
+    let id = serde_json::to_string(&id)
+        .expect_throw("could not serialize Resource ID");
+    _ = js_sys::Reflect::set(
+        &resource_resolvers,
+        &wasm_bindgen::JsValue::from_str(&id),
+        resolve.as_ref().unchecked_ref(),
+    );
+}
+
 impl<S, T> Resource<S, T>
 where
     S: Clone + 'static,
@@ -622,6 +773,18 @@ where
         });
     }
 
+    /// Returns the signal that holds the resource’s value, without registering
+    /// the read with any `<Suspense/>`.
+    pub(crate) fn value_signal(&self) -> Option<ReadSignal<Option<T>>> {
+        with_runtime(|runtime| {
+            runtime.try_resource(self.id, |resource: &ResourceState<S, T>| {
+                resource.value
+            })
+        })
+        .ok()
+        .flatten()
+    }
+
     /// Returns a [`Future`] that will resolve when the resource has loaded,
     /// yield its [`ResourceId`] and a JSON string.
     #[cfg(any(feature = "ssr", doc))]
@@ -720,6 +883,9 @@ impl<S, T> SignalUpdate for Resource<S, T> {
             runtime.try_resource(self.id, |resource: &ResourceState<S, T>| {
                 if resource.loading.get_untracked() {
                     resource.version.set(resource.version.get() + 1);
+                    if let Some(handle) = resource.abort_handle.take() {
+                        handle.abort();
+                    }
                     for suspense_context in
                         resource.suspense_contexts.borrow().iter()
                     {
@@ -1145,12 +1311,16 @@ where
     set_loading: WriteSignal<bool>,
     source: Memo<S>,
     #[allow(clippy::type_complexity)]
-    fetcher: Rc<dyn Fn(S) -> Pin<Box<dyn Future<Output = T>>>>,
+    fetcher: Rc<dyn Fn(S) -> PinnedStream<T>>,
     resolved: Rc<Cell<bool>>,
     scheduled: Rc<Cell<bool>>,
     version: Rc<Cell<usize>>,
+    abort_handle: Rc<Cell<Option<AbortHandle>>>,
     suspense_contexts: Rc<RefCell<HashSet<SuspenseContext>>>,
     serializable: ResourceSerialization,
+    #[cfg_attr(not(feature = "hydrate"), allow(dead_code))]
+    streaming: bool,
+    stream_updates: Option<StreamUpdates<T>>,
     #[cfg(feature = "experimental-islands")]
     should_send_to_client: Rc<Cell<Option<bool>>>,
 }
@@ -1167,6 +1337,54 @@ pub(crate) enum ResourceSerialization {
     Blocking,
 }
 
+/// Carries every value after the first one yielded by a streaming resource on
+/// the server, already serialized, so it can be sent to the client after the
+/// resource has resolved.
+pub(crate) struct StreamUpdates<T> {
+    tx: Rc<RefCell<Option<mpsc::UnboundedSender<String>>>>,
+    rx: Rc<RefCell<Option<mpsc::UnboundedReceiver<String>>>>,
+    ser: fn(&T) -> Result<String, SerializationError>,
+}
+
+impl<T> Clone for StreamUpdates<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: Rc::clone(&self.tx),
+            rx: Rc::clone(&self.rx),
+            ser: self.ser,
+        }
+    }
+}
+
+impl<T: Serializable> StreamUpdates<T> {
+    fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        Self {
+            tx: Rc::new(RefCell::new(Some(tx))),
+            rx: Rc::new(RefCell::new(Some(rx))),
+            ser: T::ser,
+        }
+    }
+}
+
+impl<T> StreamUpdates<T> {
+    fn send(&self, value: &T) {
+        if let Some(tx) = self.tx.borrow().as_ref() {
+            match (self.ser)(value) {
+                Ok(value) => _ = tx.unbounded_send(value),
+                Err(e) => crate::macros::debug_warn!(
+                    "[create_stream_resource] could not serialize a value, \
+                     so it will not be sent to the client: {e}"
+                ),
+            }
+        }
+    }
+
+    fn close(&self) {
+        self.tx.borrow_mut().take();
+    }
+}
+
 impl<S, T> ResourceState<S, T>
 where
     S: Clone + 'static,
@@ -1377,7 +1595,12 @@ where
         self.scheduled.set(false);
 
         _ = self.source.try_with_untracked(|source| {
-            let fut = (self.fetcher)(source.clone());
+            // drop any superseded fetch, rather than letting it run to
+            // completion and then discarding its result
+            self.abort_pending();
+            let (mut stream, abort_handle) =
+                stream::abortable((self.fetcher)(source.clone()));
+            self.abort_handle.set(Some(abort_handle));
 
             // `scheduled` is true for the rest of this code only
             self.scheduled.set(true);
@@ -1404,8 +1627,9 @@ where
             }
 
             let current_span = tracing::Span::current();
-            // run the Future
+            // run the Future (or Stream)
             let serializable = self.serializable;
+            let stream_updates = self.stream_updates.clone();
             spawn_local({
                 let resolved = self.resolved.clone();
                 let set_value = self.set_value;
@@ -1414,24 +1638,67 @@ where
                 async move {
                     // continue trace context within resource fetcher
                     let _guard = current_span.enter();
-                    let res = fut.await;
+                    let mut first = true;
 
-                    if version == last_version.get() {
-                        resolved.set(true);
-                        set_value.try_update(|n| *n = Some(res));
-                        set_loading.try_update(|n| *n = false);
+                    while let Some(res) = stream.next().await {
+                        if version != last_version.get() {
+                            break;
+                        }
+                        if first {
+                            resolved.set(true);
+                            set_value.try_update(|n| *n = Some(res));
+                            set_loading.try_update(|n| *n = false);
+                            // the resource is ready as soon as the first
+                            // value arrives, even if more values follow
+                            for suspense_context in
+                                suspense_contexts.borrow().iter()
+                            {
+                                suspense_context.decrement_for_resource(
+                                    serializable
+                                        != ResourceSerialization::Local,
+                                    id,
+                                );
+                            }
+                            first = false;
+                        } else {
+                            if let Some(updates) = &stream_updates {
+                                updates.send(&res);
+                            }
+                            set_value.try_update(|n| *n = Some(res));
+                        }
                     }
 
-                    for suspense_context in suspense_contexts.borrow().iter() {
-                        suspense_context.decrement_for_resource(
-                            serializable != ResourceSerialization::Local,
-                            id,
-                        );
+                    if version == last_version.get() {
+                        if let Some(updates) = &stream_updates {
+                            updates.close();
+                        }
+                    }
+                    if first {
+                        if version == last_version.get() {
+                            set_loading.try_update(|n| *n = false);
+                        }
+                        for suspense_context in
+                            suspense_contexts.borrow().iter()
+                        {
+                            suspense_context.decrement_for_resource(
+                                serializable != ResourceSerialization::Local,
+                                id,
+                            );
+                        }
                     }
                 }
             })
         });
     }
+
+    /// Aborts the pending fetch, if any. The aborted `Future` is dropped the
+    /// next time its task is polled.
+    fn abort_pending(&self) {
+        if let Some(handle) = self.abort_handle.take() {
+            handle.abort();
+        }
+    }
+
     #[cfg_attr(
         any(debug_assertions, feature = "ssr"),
         instrument(level = "trace", skip_all,)
@@ -1487,6 +1754,11 @@ pub(crate) trait SerializableResource {
     ) -> Pin<Box<dyn Future<Output = (ResourceId, String)>>>;
 
     fn should_send_to_client(&self) -> bool;
+
+    fn to_update_stream(
+        &self,
+        id: ResourceId,
+    ) -> Option<PinnedStream<(ResourceId, String)>>;
 }
 
 impl<S, T> SerializableResource for ResourceState<S, T>
@@ -1526,6 +1798,14 @@ where
             true
         }
     }
+
+    fn to_update_stream(
+        &self,
+        id: ResourceId,
+    ) -> Option<PinnedStream<(ResourceId, String)>> {
+        let rx = self.stream_updates.as_ref()?.rx.borrow_mut().take()?;
+        Some(Box::pin(rx.map(move |value| (id, value))))
+    }
 }
 
 pub(crate) trait UnserializableResource {
@@ -1,3 +1,5 @@
+#[cfg(feature = "inspector")]
+use crate::inspector::GraphEvent;
 #[cfg(debug_assertions)]
 use crate::SpecialNonReactiveZone;
 use crate::{
@@ -5,13 +7,14 @@ use crate::{
     node::{
         Disposer, NodeId, ReactiveNode, ReactiveNodeState, ReactiveNodeType,
     },
-    AnyComputation, AnyResource, EffectState, Memo, MemoState, ReadSignal,
-    ResourceId, ResourceState, RwSignal, SerializableResource, StoredValueId,
-    Trigger, UnserializableResource, WriteSignal,
+    spawn_microtask::request_idle_callback,
+    AnyComputation, AnyResource, EffectPriority, EffectState, Memo, MemoState,
+    ReadSignal, ResourceId, ResourceState, RwSignal, SerializableResource,
+    StoredValueId, Trigger, UnserializableResource, WriteSignal,
 };
 use cfg_if::cfg_if;
 use core::hash::BuildHasherDefault;
-use futures::stream::FuturesUnordered;
+use futures::stream::{self, FuturesUnordered, SelectAll, Stream};
 use indexmap::IndexSet;
 use pin_project::pin_project;
 use rustc_hash::{FxHashMap, FxHasher};
@@ -19,6 +22,7 @@ use slotmap::{SecondaryMap, SlotMap, SparseSecondaryMap};
 use std::{
     any::{Any, TypeId},
     cell::{Cell, RefCell},
+    collections::VecDeque,
     fmt::Debug,
     future::Future,
     marker::PhantomData,
@@ -29,6 +33,7 @@ use std::{
 use thiserror::Error;
 
 pub(crate) type PinnedFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+pub(crate) type PinnedStream<T> = Pin<Box<dyn Stream<Item = T>>>;
 
 cfg_if! {
     if #[cfg(any(feature = "csr", feature = "hydrate"))] {
@@ -75,8 +80,15 @@ pub(crate) struct Runtime {
     pub contexts:
         RefCell<SparseSecondaryMap<NodeId, FxHashMap<TypeId, Box<dyn Any>>>>,
     pub pending_effects: RefCell<Vec<NodeId>>,
+    pub effect_queue: RefCell<EffectQueue>,
+    pub scheduler: Cell<EffectScheduler>,
+    pub idle_flush_scheduled: Cell<bool>,
     pub resources: RefCell<SlotMap<ResourceId, AnyResource>>,
     pub batching: Cell<bool>,
+    pub batch_count: Cell<u64>,
+    pub flushing_batch: Cell<Option<u64>>,
+    #[cfg(feature = "inspector")]
+    pub inspector: crate::inspector::Inspector,
 }
 
 /// The current reactive runtime.
@@ -202,7 +214,10 @@ impl Runtime {
             let changed = match node.node_type {
                 ReactiveNodeType::Signal | ReactiveNodeType::Trigger => true,
                 ReactiveNodeType::Memo { ref f }
-                | ReactiveNodeType::Effect { ref f } => {
+                | ReactiveNodeType::Effect { ref f, .. } => {
+                    #[cfg(feature = "inspector")]
+                    self.inspector.emit(GraphEvent::Ran(node_id));
+
                     let value = node.value();
                     // set this node as the observer
                     self.with_observer(node_id, move || {
@@ -234,6 +249,13 @@ impl Runtime {
     }
 
     pub(crate) fn dispose_node(&self, node_id: NodeId) {
+        #[cfg(feature = "inspector")]
+        let was_owner = self
+            .node_properties
+            .borrow()
+            .get(node_id)
+            .is_some_and(|properties| !properties.is_empty());
+
         self.cleanup_node(node_id);
 
         // each of the subs needs to remove the node from its dependencies
@@ -250,9 +272,13 @@ impl Runtime {
 
         self.node_sources.borrow_mut().remove(node_id);
         let node = { self.nodes.borrow_mut().remove(node_id) };
+        #[cfg(feature = "inspector")]
+        if node.is_some() {
+            self.inspector.disposed(node_id, was_owner);
+        }
         drop(node);
     }
-    fn cleanup_node(&self, node_id: NodeId) {
+    pub(crate) fn cleanup_node(&self, node_id: NodeId) {
         self.run_on_cleanups(node_id);
         self.dispose_children(node_id);
     }
@@ -324,7 +350,11 @@ impl Runtime {
         }
     }
 
-    fn with_observer<T>(&self, observer: NodeId, f: impl FnOnce() -> T) -> T {
+    pub(crate) fn with_observer<T>(
+        &self,
+        observer: NodeId,
+        f: impl FnOnce() -> T,
+    ) -> T {
         // take previous observer and owner
         let prev_observer = self.observer.take();
         let prev_owner = self.owner.take();
@@ -345,6 +375,9 @@ impl Runtime {
     }
 
     pub(crate) fn mark_dirty(&self, node: NodeId) {
+        #[cfg(feature = "inspector")]
+        self.inspector.emit(GraphEvent::Notified(node));
+
         let mut nodes = self.nodes.borrow_mut();
 
         if let Some(current_node) = nodes.get_mut(node) {
@@ -485,13 +518,64 @@ impl Runtime {
 
     pub(crate) fn run_effects(&self) {
         if !self.batching.get() {
-            let effects = self.pending_effects.take();
-            for effect_id in effects {
-                self.update_if_necessary(effect_id);
+            self.queue_pending_effects();
+            if self.scheduler.get() == EffectScheduler::Automatic {
+                self.drain_effects(false);
+                self.schedule_idle_flush();
             }
         }
     }
 
+    /// Moves the effects that have been notified into the queue for their
+    /// priority.
+    pub(crate) fn queue_pending_effects(&self) {
+        let effects = self.pending_effects.take();
+        if effects.is_empty() {
+            return;
+        }
+        let nodes = self.nodes.borrow();
+        let mut queue = self.effect_queue.borrow_mut();
+        for effect_id in effects {
+            if let Some(ReactiveNode {
+                node_type: ReactiveNodeType::Effect { priority, .. },
+                ..
+            }) = nodes.get(effect_id)
+            {
+                queue.push(effect_id, *priority);
+            }
+        }
+    }
+
+    /// Runs queued effects, highest priority first, until the queue is empty.
+    /// Effects queued while this runs are run as well.
+    pub(crate) fn drain_effects(&self, include_idle: bool) {
+        loop {
+            let next = self.effect_queue.borrow_mut().pop(include_idle);
+            match next {
+                Some(effect_id) => self.update_if_necessary(effect_id),
+                None => break,
+            }
+        }
+    }
+
+    /// Runs any queued idle effects the next time the browser is idle.
+    pub(crate) fn schedule_idle_flush(&self) {
+        if self.idle_flush_scheduled.get()
+            || !self.effect_queue.borrow().has_idle()
+        {
+            return;
+        }
+        self.idle_flush_scheduled.set(true);
+        request_idle_callback(|| {
+            _ = with_runtime(|runtime| {
+                runtime.idle_flush_scheduled.set(false);
+                if runtime.scheduler.get() == EffectScheduler::Automatic {
+                    runtime.drain_effects(true);
+                }
+            });
+        });
+    }
+
     #[track_caller]
     pub(crate) fn register_property(
         &self,
@@ -500,6 +584,11 @@ impl Runtime {
             'static,
         >,
     ) {
+        #[cfg(feature = "inspector")]
+        if let Some(node) = property.to_node_id() {
+            self.inspector.created(node, self.owner.get());
+        }
+
         let mut properties = self.node_properties.borrow_mut();
         if let Some(owner) = self.owner.get() {
             if let Some(entry) = properties.entry(owner) {
@@ -983,6 +1072,8 @@ impl RuntimeId {
         .expect(
             "tried to create a trigger in a runtime that has been disposed",
         );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, None);
 
         Trigger {
             id,
@@ -1019,6 +1110,8 @@ impl RuntimeId {
         let id = self.create_concrete_signal(
             Rc::new(RefCell::new(value)) as Rc<RefCell<dyn Any>>
         );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, Some(std::any::type_name::<T>()));
 
         (
             ReadSignal {
@@ -1045,6 +1138,8 @@ impl RuntimeId {
         let id = self.create_concrete_signal(
             Rc::new(RefCell::new(value)) as Rc<RefCell<dyn Any>>
         );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, Some(std::any::type_name::<T>()));
         RwSignal {
             id,
             ty: PhantomData,
@@ -1057,6 +1152,7 @@ impl RuntimeId {
         self,
         value: Rc<RefCell<dyn Any>>,
         effect: Rc<dyn AnyComputation>,
+        priority: EffectPriority,
     ) -> NodeId {
         with_runtime(|runtime| {
             let id = runtime.nodes.borrow_mut().insert(ReactiveNode {
@@ -1064,6 +1160,7 @@ impl RuntimeId {
                 state: ReactiveNodeState::Dirty,
                 node_type: ReactiveNodeType::Effect {
                     f: Rc::clone(&effect),
+                    priority,
                 },
             });
             runtime.push_scope_property(ScopeProperty::Effect(id));
@@ -1096,11 +1193,12 @@ impl RuntimeId {
     pub(crate) fn create_effect<T>(
         self,
         f: impl Fn(Option<T>) -> T + 'static,
+        priority: EffectPriority,
     ) -> NodeId
     where
         T: Any + 'static,
     {
-        self.create_concrete_effect(
+        let id = self.create_concrete_effect(
             Rc::new(RefCell::new(None::<T>)),
             Rc::new(EffectState {
                 f,
@@ -1108,7 +1206,11 @@ impl RuntimeId {
                 #[cfg(any(debug_assertions, feature = "ssr"))]
                 defined_at: std::panic::Location::caller(),
             }),
-        )
+            priority,
+        );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, Some(std::any::type_name::<T>()));
+        id
     }
 
     pub(crate) fn watch<W, T>(
@@ -1172,7 +1274,10 @@ impl RuntimeId {
                 #[cfg(any(debug_assertions, feature = "ssr"))]
                 defined_at: std::panic::Location::caller(),
             }),
+            EffectPriority::User,
         );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, Some(std::any::type_name::<T>()));
 
         (id, move || {
             with_runtime(|runtime| runtime.dispose_node(id)).expect(
@@ -1190,21 +1295,35 @@ impl RuntimeId {
     where
         T: 'static,
     {
+        let id = self.create_concrete_memo(
+            Rc::new(RefCell::new(None::<T>)),
+            Rc::new(MemoState {
+                f,
+                t: PhantomData,
+                #[cfg(any(debug_assertions, feature = "ssr"))]
+                defined_at: std::panic::Location::caller(),
+            }),
+        );
+        #[cfg(feature = "inspector")]
+        self.describe_node(id, Some(std::any::type_name::<T>()));
+
         Memo {
-            id: self.create_concrete_memo(
-                Rc::new(RefCell::new(None::<T>)),
-                Rc::new(MemoState {
-                    f,
-                    t: PhantomData,
-                    #[cfg(any(debug_assertions, feature = "ssr"))]
-                    defined_at: std::panic::Location::caller(),
-                }),
-            ),
+            id,
             ty: PhantomData,
             #[cfg(any(debug_assertions, feature = "ssr"))]
             defined_at: std::panic::Location::caller(),
         }
     }
+
+    /// Records the type and creation site of a node for the inspector.
+    #[cfg(feature = "inspector")]
+    #[track_caller]
+    fn describe_node(self, id: NodeId, type_name: Option<&'static str>) {
+        let defined_at = std::panic::Location::caller();
+        _ = with_runtime(|runtime| {
+            runtime.inspector.describe(id, type_name, defined_at)
+        });
+    }
 }
 
 impl Runtime {
@@ -1339,6 +1458,22 @@ impl Runtime {
         f
     }
 
+    pub(crate) fn stream_updates(
+        &self,
+    ) -> SelectAll<PinnedStream<(ResourceId, String)>> {
+        let resources = { self.resources.borrow().clone() };
+        stream::select_all(resources.iter().filter_map(|(id, resource)| {
+            match resource {
+                AnyResource::Serializable(resource)
+                    if resource.should_send_to_client() =>
+                {
+                    resource.to_update_stream(id)
+                }
+                _ => None,
+            }
+        }))
+    }
+
     /// Do not call on triggers
     pub(crate) fn get_value(
         &self,
@@ -1409,10 +1544,20 @@ pub fn try_batch<T>(f: impl FnOnce() -> T) -> Result<T, ReactiveSystemError> {
 
         let val = f();
 
-        runtime.batching.set(batching.0);
+        let nested = batching.0;
+        runtime.batching.set(nested);
         std::mem::forget(batching);
 
-        runtime.run_effects();
+        // an outer batch runs the effects once it ends
+        if !nested {
+            // give the effects run by this batch a shared id, so they can
+            // tell that their changes happened together
+            let id = runtime.batch_count.get().wrapping_add(1);
+            runtime.batch_count.set(id);
+            let prev = runtime.flushing_batch.replace(Some(id));
+            runtime.run_effects();
+            runtime.flushing_batch.set(prev);
+        }
         val
     })
 }
@@ -1427,6 +1572,121 @@ impl Drop for SetBatchingOnDrop {
     }
 }
 
+/// Returns an id for the outermost [`batch`] whose effects are currently
+/// running, if any. All effects that run because of the same batch see the
+/// same id.
+pub(crate) fn current_batch_id() -> Option<u64> {
+    with_runtime(|runtime| runtime.flushing_batch.get())
+        .ok()
+        .flatten()
+}
+
+/// Effects that have been notified and are waiting to run, by priority.
+#[derive(Default)]
+pub(crate) struct EffectQueue {
+    render: VecDeque<NodeId>,
+    user: VecDeque<NodeId>,
+    idle: VecDeque<NodeId>,
+}
+
+impl EffectQueue {
+    pub fn push(&mut self, effect_id: NodeId, priority: EffectPriority) {
+        match priority {
+            EffectPriority::Render => self.render.push_back(effect_id),
+            EffectPriority::User => self.user.push_back(effect_id),
+            EffectPriority::Idle => self.idle.push_back(effect_id),
+        }
+    }
+
+    fn pop(&mut self, include_idle: bool) -> Option<NodeId> {
+        self.render
+            .pop_front()
+            .or_else(|| self.user.pop_front())
+            .or_else(|| include_idle.then(|| self.idle.pop_front()).flatten())
+    }
+
+    fn has_idle(&self) -> bool {
+        !self.idle.is_empty()
+    }
+}
+
+/// Determines when the effects of the current runtime run after they have
+/// been notified of a change.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EffectScheduler {
+    /// Render and user effects run as soon as they are notified, and idle
+    /// effects run the next time the browser is idle (or, outside the
+    /// browser, right after the other effects).
+    #[default]
+    Automatic,
+    /// Effects only run when [`flush_effects`] or [`tick`] is called. Effects
+    /// created with [`create_effect`](crate::create_effect) or
+    /// [`create_idle_effect`](crate::create_idle_effect) also wait for their
+    /// first run.
+    ///
+    /// This makes it possible to step through the reactive system
+    /// deterministically, e.g., in unit tests.
+    Manual,
+}
+
+/// Sets how the current runtime runs its effects. See [`EffectScheduler`].
+///
+/// Switching back to [`EffectScheduler::Automatic`] does not run any effects
+/// that are already waiting; call [`flush_effects`] to run them.
+pub fn set_effect_scheduler(scheduler: EffectScheduler) {
+    _ = with_runtime(|runtime| runtime.scheduler.set(scheduler));
+}
+
+/// Synchronously runs every effect that is waiting to run, including idle
+/// effects, in order of priority, until none are left.
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// set_effect_scheduler(EffectScheduler::Manual);
+///
+/// let (count, set_count) = create_signal(0);
+/// let runs = create_rw_signal(0);
+/// create_isomorphic_effect(move |_| {
+///     count.track();
+///     runs.update(|n| *n += 1);
+/// });
+///
+/// set_count.set(1);
+/// set_count.set(2);
+/// // nothing has run yet
+/// assert_eq!(runs.get_untracked(), 1);
+///
+/// flush_effects();
+/// // the effect ran once for both changes
+/// assert_eq!(runs.get_untracked(), 2);
+/// # runtime.dispose();
+/// ```
+pub fn flush_effects() {
+    _ = with_runtime(|runtime| {
+        runtime.queue_pending_effects();
+        runtime.drain_effects(true);
+    });
+}
+
+/// Yields to the async runtime once, so that spawned tasks (like the
+/// `Future`s of resources) can make progress, and then runs every effect that
+/// is waiting to run, as with [`flush_effects`].
+pub async fn tick() {
+    let mut yielded = false;
+    futures::future::poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await;
+    flush_effects();
+}
+
 /// Creates a cleanup function, which will be run when the current reactive owner is disposed.
 ///
 /// It runs after child nodes have been disposed, but before signals, effects, and resources
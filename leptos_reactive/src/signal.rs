@@ -1966,7 +1966,17 @@ impl NodeId {
             // add this observer to this node's dependencies (to allow notification)
             let mut subs = runtime.node_subscribers.borrow_mut();
             if let Some(subs) = subs.entry(*self) {
-                subs.or_default().borrow_mut().insert(observer);
+                #[allow(unused_variables)]
+                let added = subs.or_default().borrow_mut().insert(observer);
+                #[cfg(feature = "inspector")]
+                if added {
+                    runtime.inspector.emit(
+                        crate::inspector::GraphEvent::Subscribed {
+                            source: *self,
+                            subscriber: observer,
+                        },
+                    );
+                }
             }
 
             // add this node to the observer's sources (to allow cleanup)
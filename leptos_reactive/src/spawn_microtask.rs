@@ -32,3 +32,49 @@ pub fn queue_microtask(task: impl FnOnce() + 'static) {
         _ = queue_microtask.call1(&JsValue::UNDEFINED, &task);
     }
 }
+
+/// Runs the given task the next time the browser is idle, using
+/// [`requestIdleCallback`](https://developer.mozilla.org/en-US/docs/Web/API/Window/requestIdleCallback)
+/// where it is available, and a timeout otherwise.
+///
+/// Outside the browser, the task is run immediately.
+pub(crate) fn request_idle_callback(task: impl FnOnce() + 'static) {
+    #[cfg(not(all(
+        target_arch = "wasm32",
+        any(feature = "hydrate", feature = "csr")
+    )))]
+    {
+        task();
+    }
+
+    #[cfg(all(
+        target_arch = "wasm32",
+        any(feature = "hydrate", feature = "csr")
+    ))]
+    {
+        use js_sys::{Function, Reflect};
+        use wasm_bindgen::prelude::*;
+
+        let task = Closure::once_into_js(task);
+        let window = web_sys::window().expect("window not available");
+        // Safari does not support requestIdleCallback
+        let request_idle_callback =
+            Reflect::get(&window, &JsValue::from_str("requestIdleCallback"))
+                .ok()
+                .filter(JsValue::is_function);
+        match request_idle_callback {
+            Some(request_idle_callback) => {
+                let request_idle_callback =
+                    request_idle_callback.unchecked_into::<Function>();
+                _ = request_idle_callback.call1(&window, &task);
+            }
+            None => {
+                let set_timeout =
+                    Reflect::get(&window, &JsValue::from_str("setTimeout"))
+                        .expect("setTimeout not available");
+                let set_timeout = set_timeout.unchecked_into::<Function>();
+                _ = set_timeout.call1(&window, &task);
+            }
+        }
+    }
+}
@@ -0,0 +1,662 @@
+use crate::{
+    create_trigger, runtime::with_runtime, store_value, try_with_owner, Owner,
+    SignalGet, SignalGetUntracked, SignalSet, SignalUpdate, SignalWith,
+    SignalWithUntracked, StoredValue, Trigger,
+};
+use rustc_hash::{FxHashMap, FxHasher};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    marker::PhantomData,
+};
+
+// IMPLEMENTATION NOTE:
+// A store keeps its value in a single `StoredValue`, and keeps one `Trigger`
+// for each path into the value that has been read reactively. The triggers
+// are kept in a tree that mirrors the shape of the value, and are only created
+// the first time a path is tracked. Updating a path notifies the trigger for
+// that path, for every path that contains it (its ancestors), and for every
+// path it contains (its descendants, whose values may also have changed), but
+// not for any of its siblings.
+
+/// Creates a [`Store`], a reactive container for a nested value whose fields,
+/// `Vec` positions and map entries can each be tracked and updated on their own.
+///
+/// Reading a field subscribes only to that field: updating it notifies
+/// subscribers of the field itself, of anything that contains it, and of
+/// anything it contains, but not of any other field. This makes it possible to
+/// update a single row of a large table without rerunning every memo or effect
+/// that reads some other row.
+///
+/// Access to struct fields is generated with `#[derive(Store)]` (from
+/// `leptos_macro`). `Vec`s can be indexed with [`StoreVecExt::at`] and maps
+/// with [`StoreMapExt::at_key`].
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # let runtime = create_runtime();
+/// #[derive(Clone)]
+/// struct Row {
+///     label: String,
+/// }
+///
+/// let rows = create_store(vec![
+///     Row { label: "a".into() },
+///     Row { label: "b".into() },
+/// ]);
+///
+/// // without `#[derive(Store)]`, a field can be described with `Subfield::new`
+/// let label = |row| Subfield::new(row, 0, |r: &Row| &r.label, |r| &mut r.label);
+///
+/// let first = create_memo(move |_| label(rows.at(0)).get());
+/// let second_runs = create_rw_signal(0);
+/// create_isomorphic_effect(move |_| {
+///     label(rows.at(1)).track();
+///     second_runs.update(|n| *n += 1);
+/// });
+///
+/// label(rows.at(0)).set("c".into());
+/// assert_eq!(first.get(), "c");
+/// // the effect only reads the second row, so it did not run again
+/// assert_eq!(second_runs.get(), 1);
+/// # runtime.dispose();
+/// ```
+#[track_caller]
+pub fn create_store<T>(value: T) -> Store<T>
+where
+    T: 'static,
+{
+    Store {
+        value: store_value(value),
+        triggers: StoreTriggers {
+            owner: Owner::current(),
+            tree: store_value(TriggerTree::default()),
+        },
+    }
+}
+
+/// A reactive container for a nested value, which can be tracked and updated
+/// one field at a time.
+///
+/// See [`create_store`] for more.
+pub struct Store<T: 'static> {
+    value: StoredValue<T>,
+    triggers: StoreTriggers,
+}
+
+impl<T> Store<T> {
+    /// Creates a reactive container for a nested value, which can be tracked
+    /// and updated one field at a time.
+    ///
+    /// This is identical to [`create_store`].
+    #[inline(always)]
+    #[track_caller]
+    pub fn new(value: T) -> Self
+where {
+        create_store(value)
+    }
+}
+
+impl<T> Clone for Store<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Store<T> {}
+
+impl<T> fmt::Debug for Store<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Store").field("value", &self.value).finish()
+    }
+}
+
+impl<T> PartialEq for Store<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Store<T> {}
+
+/// One step of a path into a [`Store`]: a struct field, a position in a `Vec`,
+/// or a key in a map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StorePathSegment(u64);
+
+impl From<usize> for StorePathSegment {
+    fn from(value: usize) -> Self {
+        Self(value as u64)
+    }
+}
+
+/// The path from the root of a [`Store`] to one of the values inside it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StorePath(Vec<StorePathSegment>);
+
+impl StorePath {
+    /// Returns the path to a value inside the value at this path.
+    pub fn join(mut self, segment: impl Into<StorePathSegment>) -> Self {
+        self.0.push(segment.into());
+        self
+    }
+
+    /// The steps from the root of the store to this path.
+    pub fn segments(&self) -> &[StorePathSegment] {
+        &self.0
+    }
+}
+
+/// The triggers for the paths of a [`Store`] that have been read reactively.
+#[derive(Clone, Copy)]
+pub struct StoreTriggers {
+    owner: Option<Owner>,
+    tree: StoredValue<TriggerTree>,
+}
+
+#[derive(Default)]
+struct TriggerTree {
+    trigger: Option<Trigger>,
+    children: FxHashMap<StorePathSegment, TriggerTree>,
+}
+
+impl TriggerTree {
+    fn collect(&self, triggers: &mut Vec<Trigger>) {
+        triggers.extend(self.trigger);
+        for child in self.children.values() {
+            child.collect(triggers);
+        }
+    }
+}
+
+impl StoreTriggers {
+    fn track(&self, path: &StorePath) {
+        let owner = self.owner;
+        let trigger = self.tree.try_update_value(|tree| {
+            let node = path.segments().iter().fold(tree, |node, segment| {
+                node.children.entry(*segment).or_default()
+            });
+            *node.trigger.get_or_insert_with(|| {
+                // the trigger lives as long as the store, not as long as
+                // whatever happens to read the path first
+                owner
+                    .and_then(|owner| {
+                        try_with_owner(owner, create_trigger).ok()
+                    })
+                    .unwrap_or_else(create_trigger)
+            })
+        });
+        if let Some(trigger) = trigger {
+            trigger.track();
+        }
+    }
+
+    fn notify(&self, path: &StorePath) {
+        let triggers = self.tree.try_with_value(|tree| {
+            let mut triggers = Vec::new();
+            let mut node = Some(tree);
+            for segment in path.segments() {
+                let Some(current) = node else { break };
+                triggers.extend(current.trigger);
+                node = current.children.get(segment);
+            }
+            if let Some(node) = node {
+                node.collect(&mut triggers);
+            }
+            triggers
+        });
+
+        if let Some(triggers) = triggers {
+            _ = with_runtime(|runtime| {
+                for trigger in triggers {
+                    runtime.mark_dirty(trigger.id);
+                }
+                runtime.run_effects();
+            });
+        }
+    }
+}
+
+/// A path into a [`Store`], which can be read and updated like a signal.
+///
+/// This is implemented by the [`Store`] itself and by each of the types that
+/// describe a value inside it: [`Subfield`], [`AtIndex`] and [`AtKey`].
+pub trait StoreField: Clone {
+    /// The type of the value at this path.
+    type Value;
+
+    /// The path from the root of the store to this value.
+    fn path(&self) -> StorePath;
+
+    /// The triggers of the store this value belongs to.
+    fn triggers(&self) -> StoreTriggers;
+
+    /// Applies a function to the current value, without tracking it. Returns
+    /// [`None`] if the store has been disposed, or if a position or key along
+    /// the path doesn't exist.
+    fn try_with_value<U>(&self, f: impl FnOnce(&Self::Value) -> U)
+        -> Option<U>;
+
+    /// Applies a function to mutate the current value, without notifying any
+    /// subscribers. Returns [`None`] if the store has been disposed, or if a
+    /// position or key along the path doesn't exist.
+    fn try_update_value<U>(
+        &self,
+        f: impl FnOnce(&mut Self::Value) -> U,
+    ) -> Option<U>;
+}
+
+impl<T> StoreField for Store<T> {
+    type Value = T;
+
+    fn path(&self) -> StorePath {
+        StorePath::default()
+    }
+
+    fn triggers(&self) -> StoreTriggers {
+        self.triggers
+    }
+
+    fn try_with_value<U>(&self, f: impl FnOnce(&T) -> U) -> Option<U> {
+        self.value.try_with_value(f)
+    }
+
+    fn try_update_value<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.value.try_update_value(f)
+    }
+}
+
+/// A field of a struct inside a [`Store`].
+///
+/// These are usually generated by `#[derive(Store)]`, which adds a method for
+/// each field of the struct to every [`StoreField`] that contains it.
+pub struct Subfield<Inner, Prev, T> {
+    inner: Inner,
+    segment: StorePathSegment,
+    read: fn(&Prev) -> &T,
+    write: fn(&mut Prev) -> &mut T,
+}
+
+impl<Inner, Prev, T> Subfield<Inner, Prev, T> {
+    /// Describes the field at position `index` of the struct in `inner`, with
+    /// functions that borrow it from the struct.
+    #[inline(always)]
+    pub fn new(
+        inner: Inner,
+        index: usize,
+        read: fn(&Prev) -> &T,
+        write: fn(&mut Prev) -> &mut T,
+    ) -> Self {
+        Self {
+            inner,
+            segment: index.into(),
+            read,
+            write,
+        }
+    }
+}
+
+impl<Inner: Clone, Prev, T> Clone for Subfield<Inner, Prev, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            segment: self.segment,
+            read: self.read,
+            write: self.write,
+        }
+    }
+}
+
+impl<Inner: Copy, Prev, T> Copy for Subfield<Inner, Prev, T> {}
+
+impl<Inner, Prev, T> StoreField for Subfield<Inner, Prev, T>
+where
+    Inner: StoreField<Value = Prev>,
+{
+    type Value = T;
+
+    fn path(&self) -> StorePath {
+        self.inner.path().join(self.segment)
+    }
+
+    fn triggers(&self) -> StoreTriggers {
+        self.inner.triggers()
+    }
+
+    fn try_with_value<U>(&self, f: impl FnOnce(&T) -> U) -> Option<U> {
+        self.inner.try_with_value(|prev| f((self.read)(prev)))
+    }
+
+    fn try_update_value<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.inner.try_update_value(|prev| f((self.write)(prev)))
+    }
+}
+
+/// A position in a `Vec` inside a [`Store`].
+///
+/// See [`StoreVecExt::at`].
+pub struct AtIndex<Inner, T> {
+    inner: Inner,
+    index: usize,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<Inner: Clone, T> Clone for AtIndex<Inner, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            index: self.index,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<Inner: Copy, T> Copy for AtIndex<Inner, T> {}
+
+impl<Inner, T> StoreField for AtIndex<Inner, T>
+where
+    Inner: StoreField<Value = Vec<T>>,
+{
+    type Value = T;
+
+    fn path(&self) -> StorePath {
+        self.inner.path().join(self.index)
+    }
+
+    fn triggers(&self) -> StoreTriggers {
+        self.inner.triggers()
+    }
+
+    fn try_with_value<U>(&self, f: impl FnOnce(&T) -> U) -> Option<U> {
+        self.inner
+            .try_with_value(|vec| vec.get(self.index).map(f))
+            .flatten()
+    }
+
+    fn try_update_value<U>(&self, f: impl FnOnce(&mut T) -> U) -> Option<U> {
+        self.inner
+            .try_update_value(|vec| vec.get_mut(self.index).map(f))
+            .flatten()
+    }
+}
+
+/// Gives access to each position of a `Vec` inside a [`Store`].
+pub trait StoreVecExt<T>: StoreField<Value = Vec<T>> + Sized {
+    /// Returns the value at the given position, which is tracked and updated
+    /// separately from every other position.
+    ///
+    /// Adding or removing items by updating the `Vec` itself notifies every
+    /// position.
+    fn at(self, index: usize) -> AtIndex<Self, T>;
+
+    /// Returns each position that currently exists, and subscribes to the
+    /// `Vec` as a whole, so that this runs again when its length changes.
+    fn iter(self) -> impl Iterator<Item = AtIndex<Self, T>>;
+}
+
+impl<Inner, T> StoreVecExt<T> for Inner
+where
+    Inner: StoreField<Value = Vec<T>>,
+{
+    fn at(self, index: usize) -> AtIndex<Self, T> {
+        AtIndex {
+            inner: self,
+            index,
+            ty: PhantomData,
+        }
+    }
+
+    fn iter(self) -> impl Iterator<Item = AtIndex<Self, T>> {
+        self.triggers().track(&self.path());
+        let len = self.try_with_value(Vec::len).unwrap_or_default();
+        (0..len).map(move |index| self.clone().at(index))
+    }
+}
+
+/// A map type whose entries can be tracked separately in a [`Store`].
+pub trait StoreMap<K, V> {
+    /// Returns a reference to the value for the given key.
+    fn get_value(&self, key: &K) -> Option<&V>;
+
+    /// Returns a mutable reference to the value for the given key.
+    fn get_value_mut(&mut self, key: &K) -> Option<&mut V>;
+}
+
+impl<K, V, S> StoreMap<K, V> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn get_value(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_value_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+}
+
+impl<K: Ord, V> StoreMap<K, V> for BTreeMap<K, V> {
+    fn get_value(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn get_value_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.get_mut(key)
+    }
+}
+
+/// An entry of a map inside a [`Store`].
+///
+/// See [`StoreMapExt::at_key`].
+pub struct AtKey<Inner, K, V> {
+    inner: Inner,
+    key: K,
+    ty: PhantomData<fn() -> V>,
+}
+
+impl<Inner: Clone, K: Clone, V> Clone for AtKey<Inner, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            key: self.key.clone(),
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<Inner: Copy, K: Copy, V> Copy for AtKey<Inner, K, V> {}
+
+impl<Inner, K, V> StoreField for AtKey<Inner, K, V>
+where
+    Inner: StoreField,
+    Inner::Value: StoreMap<K, V>,
+    K: Clone + Hash,
+{
+    type Value = V;
+
+    fn path(&self) -> StorePath {
+        // keys are identified by their hash: a collision only means that
+        // both entries are notified when either of them changes
+        let mut hasher = FxHasher::default();
+        self.key.hash(&mut hasher);
+        self.inner.path().join(StorePathSegment(hasher.finish()))
+    }
+
+    fn triggers(&self) -> StoreTriggers {
+        self.inner.triggers()
+    }
+
+    fn try_with_value<U>(&self, f: impl FnOnce(&V) -> U) -> Option<U> {
+        self.inner
+            .try_with_value(|map| map.get_value(&self.key).map(f))
+            .flatten()
+    }
+
+    fn try_update_value<U>(&self, f: impl FnOnce(&mut V) -> U) -> Option<U> {
+        self.inner
+            .try_update_value(|map| map.get_value_mut(&self.key).map(f))
+            .flatten()
+    }
+}
+
+/// Gives access to each entry of a map inside a [`Store`].
+pub trait StoreMapExt<K, V>: StoreField + Sized {
+    /// Returns the value for the given key, which is tracked and updated
+    /// separately from every other entry.
+    ///
+    /// Inserting or removing entries by updating the map itself notifies
+    /// every entry.
+    fn at_key(self, key: K) -> AtKey<Self, K, V>;
+}
+
+impl<Inner, K, V> StoreMapExt<K, V> for Inner
+where
+    Inner: StoreField,
+    Inner::Value: StoreMap<K, V>,
+{
+    fn at_key(self, key: K) -> AtKey<Self, K, V> {
+        AtKey {
+            inner: self,
+            key,
+            ty: PhantomData,
+        }
+    }
+}
+
+#[track_caller]
+fn expect_value<T>(value: Option<T>) -> T {
+    value.expect(
+        "tried to access a store field that has been disposed, or a \
+         position or key that does not exist",
+    )
+}
+
+macro_rules! impl_signal_traits {
+    ($([$($generics:tt)*] $ty:ty;)*) => {
+        $(
+            impl<$($generics)*> SignalWithUntracked for $ty
+            where
+                Self: StoreField,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn with_untracked<O>(
+                    &self,
+                    f: impl FnOnce(&Self::Value) -> O,
+                ) -> O {
+                    expect_value(self.try_with_value(f))
+                }
+
+                fn try_with_untracked<O>(
+                    &self,
+                    f: impl FnOnce(&Self::Value) -> O,
+                ) -> Option<O> {
+                    self.try_with_value(f)
+                }
+            }
+
+            impl<$($generics)*> SignalGetUntracked for $ty
+            where
+                Self: StoreField,
+                <Self as StoreField>::Value: Clone,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn get_untracked(&self) -> Self::Value {
+                    expect_value(self.try_with_value(Clone::clone))
+                }
+
+                fn try_get_untracked(&self) -> Option<Self::Value> {
+                    self.try_with_value(Clone::clone)
+                }
+            }
+
+            impl<$($generics)*> SignalWith for $ty
+            where
+                Self: StoreField,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn with<O>(&self, f: impl FnOnce(&Self::Value) -> O) -> O {
+                    expect_value(SignalWith::try_with(self, f))
+                }
+
+                fn try_with<O>(
+                    &self,
+                    f: impl FnOnce(&Self::Value) -> O,
+                ) -> Option<O> {
+                    self.triggers().track(&self.path());
+                    self.try_with_value(f)
+                }
+            }
+
+            impl<$($generics)*> SignalGet for $ty
+            where
+                Self: StoreField,
+                <Self as StoreField>::Value: Clone,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn get(&self) -> Self::Value {
+                    expect_value(SignalGet::try_get(self))
+                }
+
+                fn try_get(&self) -> Option<Self::Value> {
+                    SignalWith::try_with(self, Clone::clone)
+                }
+            }
+
+            impl<$($generics)*> SignalUpdate for $ty
+            where
+                Self: StoreField,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn update(&self, f: impl FnOnce(&mut Self::Value)) {
+                    expect_value(self.try_update(f))
+                }
+
+                fn try_update<O>(
+                    &self,
+                    f: impl FnOnce(&mut Self::Value) -> O,
+                ) -> Option<O> {
+                    let value = self.try_update_value(f)?;
+                    self.triggers().notify(&self.path());
+                    Some(value)
+                }
+            }
+
+            impl<$($generics)*> SignalSet for $ty
+            where
+                Self: StoreField,
+            {
+                type Value = <Self as StoreField>::Value;
+
+                #[track_caller]
+                fn set(&self, new_value: Self::Value) {
+                    self.update(|n| *n = new_value);
+                }
+
+                fn try_set(&self, new_value: Self::Value) -> Option<Self::Value> {
+                    let mut new_value = Some(new_value);
+                    self.try_update(|n| *n = new_value.take().unwrap());
+                    new_value
+                }
+            }
+        )*
+    };
+}
+
+impl_signal_traits! {
+    [T] Store<T>;
+    [Inner, Prev, T] Subfield<Inner, Prev, T>;
+    [Inner, T] AtIndex<Inner, T>;
+    [Inner, K, V] AtKey<Inner, K, V>;
+}
@@ -0,0 +1,165 @@
+#[cfg(not(feature = "ssr"))]
+use leptos_reactive::{
+    create_async_memo, create_runtime, create_rw_signal, create_signal,
+    on_cleanup, SignalGet, SignalGetUntracked, SignalSet, SignalUpdate,
+};
+
+/// Returns `Poll::Pending` once, so that anything after it runs in a later
+/// poll of the same `Future`.
+#[cfg(not(feature = "ssr"))]
+async fn yield_now() {
+    let mut yielded = false;
+    futures::future::poll_fn(move |cx| {
+        if yielded {
+            std::task::Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn async_memo_tracks_signals_across_await_points() {
+    let runtime = create_runtime();
+
+    let (a, set_a) = create_signal(1);
+    let (b, set_b) = create_signal(10);
+    let runs = create_rw_signal(0);
+
+    let sum = create_async_memo(move || async move {
+        runs.update(|n| *n += 1);
+        let a = a.get();
+        yield_now().await;
+        a + b.get()
+    });
+    assert_eq!(sum.get(), Some(11));
+    assert_eq!(runs.get_untracked(), 1);
+
+    // read before the `.await`
+    set_a.set(2);
+    assert_eq!(sum.get(), Some(12));
+    assert_eq!(runs.get_untracked(), 2);
+
+    // read after the `.await`
+    set_b.set(20);
+    assert_eq!(sum.get(), Some(22));
+    assert_eq!(runs.get_untracked(), 3);
+
+    runtime.dispose();
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn async_memo_drops_dependencies_of_previous_runs() {
+    let runtime = create_runtime();
+
+    let (use_a, set_use_a) = create_signal(true);
+    let (a, set_a) = create_signal(1);
+    let (b, set_b) = create_signal(2);
+    let runs = create_rw_signal(0);
+
+    let value = create_async_memo(move || async move {
+        runs.update(|n| *n += 1);
+        yield_now().await;
+        if use_a.get() {
+            a.get()
+        } else {
+            b.get()
+        }
+    });
+    assert_eq!(value.get(), Some(1));
+
+    set_use_a.set(false);
+    assert_eq!(value.get(), Some(2));
+    assert_eq!(runs.get_untracked(), 2);
+
+    // `a` is no longer read, so this doesn't rerun the computation
+    set_a.set(3);
+    assert_eq!(runs.get_untracked(), 2);
+
+    set_b.set(4);
+    assert_eq!(value.get(), Some(4));
+    assert_eq!(runs.get_untracked(), 3);
+
+    runtime.dispose();
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn async_memo_runs_cleanups_before_rerunning() {
+    let runtime = create_runtime();
+
+    let (count, set_count) = create_signal(0);
+    let cleanups = create_rw_signal(0);
+
+    let value = create_async_memo(move || {
+        on_cleanup(move || cleanups.update(|n| *n += 1));
+        async move { count.get() }
+    });
+    assert_eq!(value.get(), Some(0));
+    assert_eq!(cleanups.get_untracked(), 0);
+
+    set_count.set(1);
+    assert_eq!(value.get(), Some(1));
+    assert_eq!(cleanups.get_untracked(), 1);
+
+    value.refetch();
+    assert_eq!(cleanups.get_untracked(), 2);
+
+    runtime.dispose();
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn async_memo_is_not_pending_once_settled() {
+    let runtime = create_runtime();
+
+    let (count, set_count) = create_signal(0);
+    let value = create_async_memo(move || async move {
+        yield_now().await;
+        count.get() * 2
+    });
+    let pending = value.pending();
+
+    assert!(!pending.get());
+    set_count.set(2);
+    assert!(!pending.get());
+    assert_eq!(value.get(), Some(4));
+
+    runtime.dispose();
+}
+
+#[cfg(feature = "ssr")]
+#[test]
+fn async_memo_runs_on_the_server() {
+    use leptos_reactive::{
+        create_async_memo, create_local_async_memo, create_runtime,
+        create_signal, SharedContext, SignalGet, SignalSet,
+    };
+    use tokio::task;
+    use tokio_test::block_on;
+
+    let runtime = create_runtime();
+
+    block_on(task::LocalSet::new().run_until(async move {
+        let (count, set_count) = create_signal(1);
+        let value = create_async_memo(move || async move { count.get() * 2 });
+        let local = create_local_async_memo(move || async move { count.get() });
+        task::yield_now().await;
+
+        assert_eq!(value.get(), Some(2));
+        assert_eq!(local.get(), None);
+        // only the serializable memo is sent to the client
+        assert_eq!(SharedContext::pending_resources().len(), 1);
+
+        set_count.set(2);
+        task::yield_now().await;
+        assert_eq!(value.get(), Some(4));
+    }));
+
+    runtime.dispose();
+}
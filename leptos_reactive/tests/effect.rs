@@ -1,6 +1,8 @@
 use leptos_reactive::{
     batch, create_isomorphic_effect, create_memo, create_runtime,
-    create_rw_signal, create_signal, untrack, SignalGet, SignalSet,
+    create_rw_signal, create_signal, flush_effects, set_effect_scheduler, tick,
+    untrack, EffectScheduler, SignalGet, SignalGetUntracked, SignalSet,
+    SignalUpdate, SignalWith,
 };
 
 #[test]
@@ -131,3 +133,98 @@ fn batching_actually_batches() {
 
     runtime.dispose();
 }
+
+#[test]
+fn manual_scheduler_waits_for_flush() {
+    let runtime = create_runtime();
+    set_effect_scheduler(EffectScheduler::Manual);
+
+    let (a, set_a) = create_signal(0);
+    let runs = create_rw_signal(0);
+    create_isomorphic_effect(move |_| {
+        a.track();
+        runs.update(|n| *n += 1);
+    });
+    assert_eq!(runs.get_untracked(), 1);
+
+    set_a.set(1);
+    set_a.set(2);
+    assert_eq!(runs.get_untracked(), 1);
+
+    flush_effects();
+    assert_eq!(runs.get_untracked(), 2);
+
+    // nothing left to run
+    flush_effects();
+    assert_eq!(runs.get_untracked(), 2);
+
+    set_a.set(3);
+    futures::executor::block_on(tick());
+    assert_eq!(runs.get_untracked(), 3);
+
+    runtime.dispose();
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn effects_run_in_order_of_priority() {
+    use leptos_reactive::{
+        create_effect, create_idle_effect, create_render_effect,
+    };
+
+    let runtime = create_runtime();
+    set_effect_scheduler(EffectScheduler::Manual);
+
+    let (a, set_a) = create_signal(0);
+    let log = create_rw_signal(Vec::new());
+    create_idle_effect(move |_| {
+        a.track();
+        log.update(|log| log.push("idle"));
+    });
+    create_effect(move |_| {
+        a.track();
+        log.update(|log| log.push("user"));
+    });
+    create_render_effect(move |_| {
+        a.track();
+        log.update(|log| log.push("render"));
+    });
+    // render effects run immediately when created
+    assert_eq!(log.get_untracked(), ["render"]);
+
+    flush_effects();
+    assert_eq!(log.get_untracked(), ["render", "user", "idle"]);
+
+    log.update(Vec::clear);
+    set_a.set(1);
+    flush_effects();
+    assert_eq!(log.get_untracked(), ["render", "user", "idle"]);
+
+    runtime.dispose();
+}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn idle_effects_run_after_other_effects_automatically() {
+    use leptos_reactive::create_idle_effect;
+
+    let runtime = create_runtime();
+
+    let (a, set_a) = create_signal(0);
+    let log = create_rw_signal(Vec::new());
+    create_idle_effect(move |_| {
+        a.track();
+        log.update(|log| log.push("idle"));
+    });
+    create_isomorphic_effect(move |_| {
+        a.track();
+        log.update(|log| log.push("user"));
+    });
+    assert_eq!(log.get_untracked(), ["idle", "user"]);
+
+    log.update(Vec::clear);
+    set_a.set(1);
+    assert_eq!(log.get_untracked(), ["user", "idle"]);
+
+    runtime.dispose();
+}
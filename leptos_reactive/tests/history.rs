@@ -0,0 +1,120 @@
+use leptos_reactive::{
+    batch, create_runtime, create_rw_signal, create_signal_history, SignalGet,
+    SignalHistory, SignalSet,
+};
+
+#[test]
+fn history_undoes_and_redoes_writes() {
+    let runtime = create_runtime();
+
+    let count = create_rw_signal(0);
+    let history = create_signal_history();
+    history.track(count);
+    let can_undo = history.can_undo();
+    let can_redo = history.can_redo();
+    assert!(!can_undo.get());
+    assert!(!can_redo.get());
+
+    count.set(1);
+    count.set(2);
+    // writing the same value again is not a change
+    count.set(2);
+    assert!(can_undo.get());
+
+    assert!(history.undo());
+    assert_eq!(count.get(), 1);
+    assert!(can_redo.get());
+    assert!(history.undo());
+    assert_eq!(count.get(), 0);
+    assert!(!can_undo.get());
+    assert!(!history.undo());
+
+    assert!(history.redo());
+    assert_eq!(count.get(), 1);
+
+    // a new write discards the entries that could be redone
+    count.set(5);
+    assert!(!can_redo.get());
+    assert!(!history.redo());
+    assert!(history.undo());
+    assert_eq!(count.get(), 1);
+
+    runtime.dispose();
+}
+
+#[test]
+fn history_coalesces_writes_in_a_batch() {
+    let runtime = create_runtime();
+
+    let a = create_rw_signal("a");
+    let b = create_rw_signal(1);
+    let history = create_signal_history();
+    history.track(a);
+    history.track(b);
+
+    batch(|| {
+        a.set("b");
+        b.set(2);
+    });
+    b.set(3);
+
+    history.undo();
+    assert_eq!((a.get(), b.get()), ("b", 2));
+    history.undo();
+    assert_eq!((a.get(), b.get()), ("a", 1));
+    history.redo();
+    assert_eq!((a.get(), b.get()), ("b", 2));
+
+    runtime.dispose();
+}
+
+#[test]
+fn history_with_limit_drops_oldest_entries() {
+    let runtime = create_runtime();
+
+    let count = create_rw_signal(0);
+    let history = SignalHistory::with_limit(2);
+    history.track(count);
+
+    for n in 1..=4 {
+        count.set(n);
+    }
+    assert!(history.undo());
+    assert!(history.undo());
+    assert!(!history.undo());
+    assert_eq!(count.get(), 2);
+
+    runtime.dispose();
+}
+
+#[test]
+fn history_goes_to_checkpoints() {
+    let runtime = create_runtime();
+
+    let count = create_rw_signal(0);
+    let history = create_signal_history();
+    history.track(count);
+
+    let start = history.checkpoint();
+    count.set(1);
+    let one = history.checkpoint();
+    count.set(2);
+    count.set(3);
+    let three = history.checkpoint();
+
+    assert!(history.go_to(one));
+    assert_eq!(count.get(), 1);
+    assert!(history.go_to(three));
+    assert_eq!(count.get(), 3);
+    assert!(history.go_to(start));
+    assert_eq!(count.get(), 0);
+
+    // branching off from `start` makes the old entries unreachable
+    count.set(10);
+    assert!(!history.go_to(three));
+    assert_eq!(count.get(), 10);
+    assert!(history.go_to(start));
+    assert_eq!(count.get(), 0);
+
+    runtime.dispose();
+}
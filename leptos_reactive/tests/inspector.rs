@@ -0,0 +1,160 @@
+#![cfg(feature = "inspector")]
+
+use leptos_reactive::{
+    as_child_of_current_owner, create_isomorphic_effect, create_memo,
+    create_runtime, create_signal,
+    inspector::{
+        assert_no_leaks, find_leaks, graph_events, snapshot_graph, GraphEvent,
+        LeakReason, NodeKind, NodeState,
+    },
+    SignalGet, SignalSet,
+};
+
+#[test]
+fn snapshot_contains_owners_and_edges() {
+    let runtime = create_runtime();
+
+    let (count, set_count) = create_signal(0);
+    let double = create_memo(move |_| count.get() * 2);
+    create_isomorphic_effect(move |_| {
+        double.get();
+    });
+
+    let graph = snapshot_graph();
+    let find =
+        |kind| graph.nodes.iter().find(|node| node.kind == kind).unwrap();
+    let signal = find(NodeKind::Signal);
+    let memo = find(NodeKind::Memo);
+    let effect = find(NodeKind::Effect);
+
+    assert_eq!(signal.type_name, Some("i32"));
+    assert_eq!(signal.defined_at.unwrap().file(), file!());
+    assert_eq!(signal.subscribers, vec![memo.id]);
+    assert_eq!(memo.sources, vec![signal.id]);
+    assert_eq!(memo.subscribers, vec![effect.id]);
+    assert_eq!(effect.sources, vec![memo.id]);
+
+    // everything is owned by the root of the runtime
+    let root = graph.roots().next().unwrap();
+    assert_eq!(root.kind, NodeKind::Trigger);
+    assert_eq!(signal.owner, Some(root.id));
+    assert!(root.children.contains(&effect.id));
+
+    // the effect reads the memo again as soon as the signal changes
+    set_count.set(1);
+    let graph = snapshot_graph();
+    assert_eq!(graph.get(memo.id).unwrap().state, NodeState::Clean);
+    assert_eq!(graph.get(effect.id).unwrap().state, NodeState::Clean);
+
+    runtime.dispose();
+}
+
+#[test]
+fn graph_events_are_streamed() {
+    use futures::{FutureExt, StreamExt};
+
+    let runtime = create_runtime();
+
+    let mut events = graph_events();
+    let (count, set_count) = create_signal(0);
+    let double = create_memo(move |_| count.get() * 2);
+    double.get();
+    set_count.set(1);
+
+    let mut next = || events.next().now_or_never().flatten();
+    let Some(GraphEvent::Created(signal)) = next() else {
+        panic!("expected the signal to be created");
+    };
+    let Some(GraphEvent::Created(memo)) = next() else {
+        panic!("expected the memo to be created");
+    };
+    assert_eq!(next(), Some(GraphEvent::Ran(memo)));
+    assert_eq!(
+        next(),
+        Some(GraphEvent::Subscribed {
+            source: signal,
+            subscriber: memo
+        })
+    );
+    assert_eq!(next(), Some(GraphEvent::Notified(signal)));
+    assert_eq!(next(), None);
+
+    runtime.dispose();
+}
+
+#[test]
+fn disposed_tree_does_not_leak() {
+    let runtime = create_runtime();
+
+    assert_no_leaks(|| {
+        let (_, disposer) = as_child_of_current_owner(|_| {
+            let (count, _) = create_signal(0);
+            let double = create_memo(move |_| count.get() * 2);
+            create_isomorphic_effect(move |_| {
+                double.get();
+            });
+        })(());
+        drop(disposer);
+    });
+
+    runtime.dispose();
+}
+
+#[test]
+#[should_panic(expected = "reactive nodes were leaked")]
+fn forgotten_tree_leaks() {
+    let runtime = create_runtime();
+
+    assert_no_leaks(|| {
+        let (_, disposer) = as_child_of_current_owner(|_| {
+            create_signal(0);
+        })(());
+        std::mem::forget(disposer);
+    });
+
+    runtime.dispose();
+}
+
+#[test]
+fn find_leaks_reports_long_lived_nodes() {
+    let runtime = create_runtime();
+
+    let mount = as_child_of_current_owner(|n: i32| create_signal(n));
+    let (_, forgotten) = mount(0);
+    std::mem::forget(forgotten);
+    assert!(find_leaks(Some(1)).is_empty());
+
+    // unmount two other trees
+    drop(mount(1).1);
+    drop(mount(2).1);
+
+    assert!(find_leaks(None).is_empty());
+    let leaks = find_leaks(Some(1));
+    let signal = leaks
+        .iter()
+        .find(|leak| leak.kind == NodeKind::Signal)
+        .unwrap();
+    assert_eq!(signal.type_name, Some("i32"));
+    assert_eq!(signal.reason, LeakReason::SurvivedDisposals(2));
+
+    runtime.dispose();
+}
+
+#[test]
+fn find_leaks_reports_nodes_that_outlive_their_owner() {
+    let runtime = create_runtime();
+
+    let (mount_later, disposer) = as_child_of_current_owner(|_| {
+        as_child_of_current_owner(|_| create_signal(0))
+    })(());
+    drop(disposer);
+
+    // this creates a new owner inside the one that was just disposed
+    let (_, _inner) = mount_later(());
+    let leaks = find_leaks(None);
+    assert_eq!(leaks.len(), 1);
+    assert_eq!(leaks[0].kind, NodeKind::Trigger);
+    assert_eq!(leaks[0].reason, LeakReason::OutlivedOwner);
+
+    runtime.dispose();
+}
@@ -0,0 +1,83 @@
+use leptos_reactive::{
+    create_persisted_signal, create_runtime, provide_context, CookieStorage,
+    MemoryStorage, RequestCookies, SameSite, SignalGet, SignalSet,
+    StorageBackend,
+};
+use std::time::Duration;
+
+#[test]
+fn persisted_signal_saves_and_loads_values() {
+    let runtime = create_runtime();
+
+    let storage = MemoryStorage::default();
+    let count = create_persisted_signal("count", 0, storage.clone());
+    assert_eq!(count.get(), 0);
+    // the default value isn't saved
+    assert_eq!(storage.get("count"), None);
+
+    count.set(5);
+    assert_eq!(storage.get("count").as_deref(), Some("5"));
+
+    let reloaded = create_persisted_signal("count", 0, storage.clone());
+    assert_eq!(reloaded.get(), 5);
+
+    runtime.dispose();
+}
+
+#[test]
+fn persisted_signal_ignores_invalid_values() {
+    let runtime = create_runtime();
+
+    let storage = MemoryStorage::default();
+    storage.set("count", "not a number");
+    let count = create_persisted_signal("count", 1, storage.clone());
+    assert_eq!(count.get(), 1);
+
+    count.set(2);
+    assert_eq!(storage.get("count").as_deref(), Some("2"));
+
+    runtime.dispose();
+}
+
+#[test]
+fn request_cookies_are_decoded() {
+    let cookies = RequestCookies::new("a=1; theme=%22dark%22;b=x%3By");
+    assert_eq!(cookies.get("theme").as_deref(), Some("\"dark\""));
+    assert_eq!(cookies.get("b").as_deref(), Some("x;y"));
+    assert_eq!(cookies.get("missing"), None);
+}
+
+#[test]
+fn cookie_storage_builds_set_cookie_headers() {
+    let storage = CookieStorage::default()
+        .path("/app")
+        .max_age(Some(Duration::from_secs(60)))
+        .same_site(SameSite::Strict);
+    assert_eq!(
+        storage.set_cookie_header("theme", "\"dark\""),
+        "theme=%22dark%22; Path=/app; SameSite=Strict; Max-Age=60"
+    );
+
+    let storage = CookieStorage::default()
+        .max_age(None)
+        .same_site(SameSite::None);
+    assert_eq!(
+        storage.set_cookie_header("a", "b"),
+        "a=b; Path=/; SameSite=None; Secure"
+    );
+}
+
+#[test]
+fn cookie_storage_reads_request_cookies_on_the_server() {
+    let runtime = create_runtime();
+
+    provide_context(RequestCookies::new("theme=%22dark%22"));
+    let theme = create_persisted_signal(
+        "theme",
+        "light".to_string(),
+        CookieStorage::default(),
+    );
+    assert_eq!(theme.get(), "dark");
+
+    runtime.dispose();
+}
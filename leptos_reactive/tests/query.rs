@@ -0,0 +1,306 @@
+#[test]
+fn queries_with_same_key_share_one_fetch() {
+    #[cfg(feature = "ssr")]
+    {
+        use leptos_reactive::{
+            create_query_with_options, create_runtime, provide_query_client,
+            QueryOptions, SignalGet,
+        };
+        use std::{cell::Cell, rc::Rc, time::Duration};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let client = provide_query_client();
+            let fetches = Rc::new(Cell::new(0));
+            let fetcher = {
+                let fetches = Rc::clone(&fetches);
+                move |id: u32| {
+                    fetches.set(fetches.get() + 1);
+                    async move { id * 2 }
+                }
+            };
+            let options = QueryOptions {
+                stale_time: Duration::from_secs(60),
+                ..Default::default()
+            };
+
+            let a = create_query_with_options(|| 1, fetcher.clone(), options);
+            let b = create_query_with_options(|| 1, fetcher.clone(), options);
+            task::yield_now().await;
+
+            assert_eq!(a.get(), Some(2));
+            assert_eq!(b.get(), Some(2));
+            assert_eq!(fetches.get(), 1);
+            assert_eq!(client.get_query_data::<u32, u32>(&1), Some(2));
+
+            // a fresh value is reused without fetching again
+            let c = create_query_with_options(|| 1, fetcher, options);
+            task::yield_now().await;
+            assert_eq!(c.get(), Some(2));
+            assert_eq!(fetches.get(), 1);
+
+            // invalidation refetches once for all observers
+            client.invalidate(&1u32);
+            task::yield_now().await;
+            assert_eq!(fetches.get(), 2);
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn query_client_updates_observers() {
+    #[cfg(feature = "ssr")]
+    {
+        use leptos_reactive::{
+            create_query, create_runtime, provide_query_client, SignalGet,
+        };
+        use std::{cell::Cell, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let client = provide_query_client();
+            let fetches = Rc::new(Cell::new(0));
+            let todos = create_query(|| ("todos".to_string(), 1), {
+                let fetches = Rc::clone(&fetches);
+                move |(_, id): (String, u32)| {
+                    fetches.set(fetches.get() + 1);
+                    async move { format!("todo {id}") }
+                }
+            });
+            task::yield_now().await;
+            assert_eq!(todos.get(), Some("todo 1".to_string()));
+
+            client.set_query_data(
+                &("todos".to_string(), 1),
+                "edited".to_string(),
+            );
+            assert_eq!(todos.get(), Some("edited".to_string()));
+
+            // only the keys that match are refetched, so `users` is not,
+            // even though its name starts with `user`
+            let users = create_query(|| ("users".to_string(), 1), {
+                let fetches = Rc::clone(&fetches);
+                move |(_, id): (String, u32)| {
+                    fetches.set(fetches.get() + 1);
+                    async move { format!("user {id}") }
+                }
+            });
+            task::yield_now().await;
+            assert_eq!(users.get(), Some("user 1".to_string()));
+            assert_eq!(fetches.get(), 2);
+
+            client.invalidate_where(|(kind, _): &(String, u32)| {
+                kind.starts_with("todo") || kind == "user"
+            });
+            task::yield_now().await;
+            assert_eq!(fetches.get(), 3);
+            assert_eq!(todos.get(), Some("todo 1".to_string()));
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn query_with_unserializable_key_is_not_cached() {
+    #[cfg(feature = "ssr")]
+    {
+        use leptos_reactive::{
+            create_query, create_runtime, provide_query_client, SignalGet,
+        };
+        use std::{cell::Cell, collections::BTreeMap, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let client = provide_query_client();
+            let fetches = Rc::new(Cell::new(0));
+            // JSON object keys must be strings, so this key can't be serialized
+            let key = BTreeMap::from([(vec![1u8], 1u32)]);
+            let query = create_query(
+                {
+                    let key = key.clone();
+                    move || key.clone()
+                },
+                {
+                    let fetches = Rc::clone(&fetches);
+                    move |_: BTreeMap<Vec<u8>, u32>| {
+                        fetches.set(fetches.get() + 1);
+                        async move { 42u32 }
+                    }
+                },
+            );
+            task::yield_now().await;
+
+            assert_eq!(query.get(), Some(42));
+            assert_eq!(fetches.get(), 1);
+            assert!(client.is_empty());
+            assert_eq!(client.get_query_data::<_, u32>(&key), None);
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn set_query_data_uses_observer_gc_time() {
+    #[cfg(feature = "ssr")]
+    {
+        use leptos_reactive::{
+            as_child_of_current_owner, create_query_with_options,
+            create_runtime, provide_query_client, QueryOptions,
+        };
+        use std::time::Duration;
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let client = provide_query_client();
+            let options = QueryOptions {
+                gc_time: Duration::ZERO,
+                ..Default::default()
+            };
+            let (_, disposer) = as_child_of_current_owner(move |_| {
+                create_query_with_options(
+                    || 1u32,
+                    |id| async move { id },
+                    options,
+                )
+            })(());
+            task::yield_now().await;
+
+            client.remove(&1u32);
+            client.set_query_data(&1u32, 2u32);
+            assert_eq!(client.len(), 1);
+
+            // the entry is collected right away, as its only observer asked
+            drop(disposer);
+            assert!(client.is_empty());
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn set_query_data_keeps_the_fetch_in_flight() {
+    #[cfg(feature = "ssr")]
+    {
+        use futures::{channel::oneshot, FutureExt};
+        use leptos_reactive::{
+            create_query, create_runtime, provide_query_client, SignalGet,
+        };
+        use std::{cell::Cell, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let client = provide_query_client();
+            let fetches = Rc::new(Cell::new(0));
+            let (release, done) = oneshot::channel::<()>();
+            let done = done.shared();
+            let fetcher = {
+                let fetches = Rc::clone(&fetches);
+                move |id: u32| {
+                    fetches.set(fetches.get() + 1);
+                    let done = done.clone();
+                    async move {
+                        _ = done.await;
+                        id * 10
+                    }
+                }
+            };
+
+            let a = create_query(|| 1u32, fetcher.clone());
+            task::yield_now().await;
+            assert_eq!(fetches.get(), 1);
+
+            // setting the data while the fetch runs doesn't forget it, so a
+            // new query shares it rather than fetching again
+            client.set_query_data(&1u32, 5u32);
+            assert_eq!(a.get(), Some(5));
+            let b = create_query(|| 1u32, fetcher);
+            task::yield_now().await;
+            assert_eq!(fetches.get(), 1);
+            assert_eq!(b.get(), Some(5));
+
+            release.send(()).unwrap();
+            task::yield_now().await;
+            task::yield_now().await;
+            assert_eq!(fetches.get(), 1);
+            assert_eq!(a.get(), Some(10));
+            assert_eq!(b.get(), Some(10));
+            assert_eq!(client.get_query_data::<u32, u32>(&1), Some(10));
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn superseded_fetch_is_dropped() {
+    #[cfg(feature = "ssr")]
+    {
+        use leptos_reactive::{
+            create_query, create_runtime, create_signal, provide_query_client,
+            SignalGet, SignalSet,
+        };
+        use std::{cell::Cell, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        struct SetOnDrop(Rc<Cell<bool>>);
+
+        impl Drop for SetOnDrop {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            provide_query_client();
+            let dropped = Rc::new(Cell::new(false));
+            let (id, set_id) = create_signal(1u32);
+            let _query = create_query(move || id.get(), {
+                let dropped = Rc::clone(&dropped);
+                move |id: u32| {
+                    // only the first fetch never finishes, and can only be
+                    // dropped by being superseded
+                    let guard =
+                        (id == 1).then(|| SetOnDrop(Rc::clone(&dropped)));
+                    async move {
+                        if let Some(_guard) = guard {
+                            futures::future::pending::<()>().await;
+                        }
+                        id
+                    }
+                }
+            });
+            task::yield_now().await;
+            assert!(!dropped.get());
+
+            set_id.set(2);
+            task::yield_now().await;
+            task::yield_now().await;
+            assert!(dropped.get());
+        }));
+
+        runtime.dispose();
+    }
+}
@@ -64,3 +64,158 @@ fn resource_returns_last_future() {
         runtime.dispose();
     }
 }
+
+#[test]
+fn resource_drops_superseded_future() {
+    #[cfg(feature = "ssr")]
+    {
+        use futures::{channel::oneshot::channel, FutureExt};
+        use leptos_reactive::{
+            create_resource, create_runtime, create_signal, SignalGet,
+            SignalSet,
+        };
+        use std::{cell::Cell, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        struct SetOnDrop(Rc<Cell<bool>>);
+
+        impl Drop for SetOnDrop {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            task::spawn_local(async move {
+                let (_tx_1, rx_1) = channel::<()>();
+                let (tx_2, rx_2) = channel::<()>();
+                let rx_1 = rx_1.shared();
+                let rx_2 = rx_2.shared();
+                let first_dropped = Rc::new(Cell::new(false));
+
+                let (channel_number, set_channel_number) = create_signal(1);
+
+                let resource = create_resource(move || channel_number.get(), {
+                    let first_dropped = Rc::clone(&first_dropped);
+                    move |channel_number| {
+                        let rx_1 = rx_1.clone();
+                        let rx_2 = rx_2.clone();
+                        let guard = (channel_number == 1)
+                            .then(|| SetOnDrop(Rc::clone(&first_dropped)));
+                        async move {
+                            let _guard = guard;
+                            match channel_number {
+                                1 => rx_1.await,
+                                2 => rx_2.await,
+                                _ => unreachable!(),
+                            }
+                            .unwrap();
+
+                            channel_number
+                        }
+                    }
+                });
+                task::yield_now().await;
+
+                // Switching the source supersedes the first future, which
+                // is dropped even though it never resolved
+                set_channel_number.set(2);
+                task::yield_now().await;
+                assert!(first_dropped.get());
+
+                tx_2.send(()).unwrap();
+                task::yield_now().await;
+                assert_eq!(resource.get(), Some(2));
+            })
+            .await
+            .unwrap();
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn stream_resource_updates_with_each_item() {
+    #[cfg(feature = "ssr")]
+    {
+        use futures::channel::mpsc::unbounded;
+        use leptos_reactive::{
+            create_runtime, create_stream_resource, SignalGet,
+        };
+        use std::{cell::RefCell, rc::Rc};
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            let (tx, rx) = unbounded::<u32>();
+            let rx = Rc::new(RefCell::new(Some(rx)));
+
+            let resource = create_stream_resource(
+                || (),
+                move |_| rx.borrow_mut().take().unwrap(),
+            );
+            task::yield_now().await;
+            assert_eq!(resource.get(), None);
+            assert!(resource.loading().get());
+
+            // the first item resolves the resource
+            tx.unbounded_send(1).unwrap();
+            task::yield_now().await;
+            assert_eq!(resource.get(), Some(1));
+            assert!(!resource.loading().get());
+
+            // later items keep updating it
+            tx.unbounded_send(2).unwrap();
+            tx.unbounded_send(3).unwrap();
+            task::yield_now().await;
+            assert_eq!(resource.get(), Some(3));
+            assert!(!resource.loading().get());
+        }));
+
+        runtime.dispose();
+    }
+}
+
+#[test]
+fn stream_resource_updates_are_only_kept_when_enabled() {
+    #[cfg(feature = "ssr")]
+    {
+        use futures::{stream, StreamExt};
+        use leptos_reactive::{
+            create_runtime, create_stream_resource, SharedContext,
+        };
+        use tokio::task;
+        use tokio_test::block_on;
+
+        let runtime = create_runtime();
+
+        block_on(task::LocalSet::new().run_until(async move {
+            // nothing reads the updates, so they are not kept
+            let _ =
+                create_stream_resource(|| (), |_| stream::iter([1u32, 2, 3]));
+            task::yield_now().await;
+            assert_eq!(
+                SharedContext::stream_resource_updates().count().await,
+                0
+            );
+
+            SharedContext::enable_stream_resource_updates();
+            let _ =
+                create_stream_resource(|| (), |_| stream::iter([1u32, 2, 3]));
+            task::yield_now().await;
+            let updates = SharedContext::stream_resource_updates()
+                .map(|(_, value)| value)
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(updates, ["2", "3"]);
+        }));
+
+        runtime.dispose();
+    }
+}
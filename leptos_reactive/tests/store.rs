@@ -0,0 +1,81 @@
+use leptos_reactive::{
+    create_isomorphic_effect, create_memo, create_runtime, create_rw_signal,
+    create_store, SignalGet, SignalGetUntracked, SignalSet, SignalUpdate,
+    SignalWith, StoreMapExt, StoreVecExt,
+};
+use std::collections::HashMap;
+
+#[test]
+fn vec_positions_are_tracked_separately() {
+    let runtime = create_runtime();
+
+    let rows = create_store((0..5000).collect::<Vec<_>>());
+    let runs = create_rw_signal(0);
+    create_isomorphic_effect(move |_| {
+        rows.at(10).track();
+        runs.update(|n| *n += 1);
+    });
+    let last = create_memo(move |_| rows.at(4999).get());
+
+    rows.at(4999).set(0);
+    assert_eq!(last.get(), 0);
+    assert_eq!(runs.get_untracked(), 1);
+
+    rows.at(10).update(|n| *n += 1);
+    assert_eq!(runs.get_untracked(), 2);
+
+    // changing the `Vec` itself notifies every position
+    rows.update(|rows| rows.truncate(20));
+    assert_eq!(runs.get_untracked(), 3);
+    assert_eq!(rows.at(4999).try_get(), None);
+
+    runtime.dispose();
+}
+
+#[test]
+fn iterating_tracks_the_length() {
+    let runtime = create_runtime();
+
+    let rows = create_store(vec![1, 2, 3]);
+    let sum =
+        create_memo(move |_| rows.iter().map(|row| row.get()).sum::<i32>());
+    assert_eq!(sum.get(), 6);
+
+    rows.at(0).set(10);
+    assert_eq!(sum.get(), 15);
+
+    rows.update(|rows| rows.push(4));
+    assert_eq!(sum.get(), 19);
+
+    runtime.dispose();
+}
+
+#[test]
+fn map_entries_are_tracked_separately() {
+    let runtime = create_runtime();
+
+    let scores = create_store(HashMap::from([
+        ("a".to_string(), 1),
+        ("b".to_string(), 2),
+    ]));
+    let runs = create_rw_signal(0);
+    create_isomorphic_effect(move |_| {
+        scores.at_key("a".to_string()).track();
+        runs.update(|n| *n += 1);
+    });
+    let total = create_memo(move |_| {
+        scores.with(|scores| scores.values().sum::<i32>())
+    });
+
+    scores.at_key("b".to_string()).set(5);
+    assert_eq!(runs.get_untracked(), 1);
+    assert_eq!(total.get(), 6);
+
+    scores.at_key("a".to_string()).set(5);
+    assert_eq!(runs.get_untracked(), 2);
+    assert_eq!(total.get(), 10);
+
+    assert_eq!(scores.at_key("c".to_string()).try_get(), None);
+
+    runtime.dispose();
+}
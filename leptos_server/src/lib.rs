@@ -117,6 +117,8 @@ pub use server_fn::{error::ServerFnErrorErr, ServerFnError};
 
 mod action;
 mod multi_action;
+mod queue;
 pub use action::*;
 pub use multi_action::*;
+pub use queue::*;
 extern crate tracing;
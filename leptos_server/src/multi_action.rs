@@ -1,6 +1,10 @@
+use crate::{
+    queue::{ActionQueue, KeyedActionFn, Queue},
+    QueueOptions,
+};
 use leptos_reactive::{
     is_suppressing_resource_load, signal_prelude::*, spawn_local, store_value,
-    untrack, StoredValue,
+    untrack, Serializable, StoredValue,
 };
 use server_fn::{ServerFn, ServerFnError};
 use std::{future::Future, pin::Pin, rc::Rc};
@@ -139,6 +143,122 @@ where
 
         self
     }
+
+    /// Immediately retries every submission that is [queued](Submission::queued),
+    /// instead of waiting for its next scheduled retry.
+    ///
+    /// This does nothing unless the action was put into queued mode with
+    /// [`MultiAction::queued`].
+    pub fn retry_queued(&self) {
+        if let Some(queue) = self.0.with_value(|a| a.queue.clone()) {
+            queue.retry_queued();
+        }
+    }
+}
+
+impl<I, T, E> MultiAction<I, Result<T, ServerFnError<E>>>
+where
+    I: Serializable + 'static,
+    T: 'static,
+    E: 'static,
+{
+    /// Puts this action into queued mode, so that submissions that cannot
+    /// reach the server are kept and retried, instead of failing.
+    ///
+    /// In queued mode:
+    /// - A submission that fails with [`ServerFnError::Request`], i.e., that
+    ///   never reached the server, is marked as [queued](Submission::queued)
+    ///   and stays [pending](Submission::pending).
+    /// - Queued submissions are retried with exponential backoff, and all at
+    ///   once when the browser comes back online. They can also be retried
+    ///   with [`MultiAction::retry_queued`].
+    /// - The input of every unresolved submission is saved to the
+    ///   [`QueueOptions::storage`] (`localStorage` by default), and loaded
+    ///   again when the queued action is next created, e.g., after the page
+    ///   is reloaded, so that no submission is lost.
+    /// - Every submission has an
+    ///   [idempotency key](QueueOptions::idempotency_key), and dispatching an
+    ///   input with the same key as a submission that is still unresolved
+    ///   does nothing.
+    /// - An action created with [`create_server_multi_action`] sends the key
+    ///   in the [`Idempotency-Key`](crate::IDEMPOTENCY_KEY_HEADER) header, so that the
+    ///   server can recognize a submission it has already handled, e.g., if
+    ///   the connection was lost before the response arrived.
+    ///
+    /// ```rust
+    /// # use leptos::*;
+    /// # let runtime = create_runtime();
+    /// #[server(SaveNote)]
+    /// async fn save_note(text: String) -> Result<(), ServerFnError> {
+    ///     todo!()
+    /// }
+    ///
+    /// let save_note = create_server_multi_action::<SaveNote>()
+    ///     .queued(QueueOptions::new("notes"));
+    /// # runtime.dispose();
+    /// ```
+    ///
+    /// ```rust
+    /// # use leptos::*;
+    /// # use std::{cell::Cell, rc::Rc};
+    /// # let runtime = create_runtime();
+    /// let online = Rc::new(Cell::new(false));
+    /// let storage = MemoryStorage::default();
+    /// let send = create_multi_action({
+    ///     let online = Rc::clone(&online);
+    ///     move |note: &String| {
+    ///         let result: Result<_, ServerFnError> = if online.get() {
+    ///             Ok(note.len())
+    ///         } else {
+    ///             Err(ServerFnError::Request("offline".into()))
+    ///         };
+    ///         async move { result }
+    ///     }
+    /// })
+    /// .queued(
+    ///     QueueOptions::new("notes")
+    ///         .storage(storage.clone())
+    ///         .idempotency_key(|note: &String| note.clone()),
+    /// );
+    ///
+    /// # if !cfg!(feature = "ssr") {
+    /// send.dispatch("hello".to_string());
+    /// // the same note is only queued once
+    /// send.dispatch("hello".to_string());
+    /// let submissions = send.submissions().get();
+    /// assert_eq!(submissions.len(), 1);
+    /// let submission = submissions[0];
+    /// assert!(submission.queued().get());
+    /// assert!(submission.pending().get());
+    /// assert!(storage.get("notes").is_some());
+    ///
+    /// online.set(true);
+    /// send.retry_queued();
+    /// assert!(!submission.queued().get());
+    /// assert!(!submission.pending().get());
+    /// assert_eq!(submission.value.get(), Some(Ok(5)));
+    /// # }
+    /// # runtime.dispose();
+    /// ```
+    #[track_caller]
+    pub fn queued(self, options: QueueOptions<I>) -> Self {
+        let (submissions, version, action_fn) = self.0.with_value(|a| {
+            let action_fn = a.keyed_action_fn.clone().unwrap_or_else(|| {
+                let action_fn = Rc::clone(&a.action_fn);
+                Rc::new(move |input: &I, _key: &str| action_fn(input))
+            });
+            (a.submissions, a.version, action_fn)
+        });
+        let queue = ActionQueue::new(
+            options,
+            submissions,
+            version,
+            action_fn,
+            |output| matches!(output, Err(ServerFnError::Request(_))),
+        );
+        self.0.update_value(|a| a.queue = Some(queue));
+        self
+    }
 }
 
 struct MultiActionState<I, O>
@@ -152,6 +272,10 @@ where
     url: Option<String>,
     #[allow(clippy::complexity)]
     action_fn: Rc<dyn Fn(&I) -> Pin<Box<dyn Future<Output = O>>>>,
+    /// Like `action_fn`, but also sends the idempotency key of a queued
+    /// submission to the server.
+    keyed_action_fn: Option<KeyedActionFn<I, O>>,
+    queue: Option<Rc<dyn Queue<I>>>,
 }
 
 /// An action that has been submitted by dispatching it to a [MultiAction](crate::MultiAction).
@@ -166,6 +290,7 @@ where
     /// The most recent return value of the `async` function.
     pub value: RwSignal<Option<O>>,
     pub(crate) pending: RwSignal<bool>,
+    pub(crate) queued: RwSignal<bool>,
     /// Controls this submission has been canceled.
     pub canceled: RwSignal<bool>,
 }
@@ -188,6 +313,15 @@ where
         self.pending.read_only()
     }
 
+    /// Whether this submission failed to reach the server, and is waiting to
+    /// be retried. Only [queued](MultiAction::queued) actions retry their
+    /// submissions.
+    ///
+    /// A queued submission is also [pending](Submission::pending).
+    pub fn queued(&self) -> ReadSignal<bool> {
+        self.queued.read_only()
+    }
+
     /// Cancels the submission, preventing it from resolving.
     pub fn cancel(&self) {
         self.canceled.set(true);
@@ -205,13 +339,18 @@ where
         tracing::instrument(level = "trace", skip_all,)
     )]
     pub fn dispatch(&self, input: I) {
-        if !is_suppressing_resource_load() {
+        if let Some(queue) = &self.queue {
+            if !is_suppressing_resource_load() {
+                Rc::clone(queue).dispatch(input);
+            }
+        } else if !is_suppressing_resource_load() {
             let fut = (self.action_fn)(&input);
 
             let submission = Submission {
                 input: create_rw_signal(Some(input)),
                 value: create_rw_signal(None),
                 pending: create_rw_signal(true),
+                queued: create_rw_signal(false),
                 canceled: create_rw_signal(false),
             };
 
@@ -316,6 +455,8 @@ where
         submissions,
         url: None,
         action_fn,
+        keyed_action_fn: None,
+        queue: None,
     }))
 }
 
@@ -346,5 +487,18 @@ where
     let c = move |args: &S| S::run_body(args.clone());
     #[cfg(not(feature = "ssr"))]
     let c = move |args: &S| S::run_on_client(args.clone());
-    create_multi_action(c).using_server_fn::<S>()
+    let action = create_multi_action(c).using_server_fn::<S>();
+
+    #[cfg(not(feature = "ssr"))]
+    action.0.update_value(|a| {
+        a.keyed_action_fn = Some(Rc::new(|args: &S, key: &str| {
+            Box::pin(S::run_on_client_with_header(
+                args.clone(),
+                crate::IDEMPOTENCY_KEY_HEADER,
+                key.to_string(),
+            ))
+        }));
+    });
+
+    action
 }
@@ -0,0 +1,498 @@
+use crate::Submission;
+#[cfg(debug_assertions)]
+use leptos_reactive::console_warn;
+use leptos_reactive::{
+    batch, create_effect, signal_prelude::*, spawn_local, untrack,
+    LocalStorage, Serializable, StorageBackend,
+};
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+/// The header that a queued [`MultiAction`](crate::MultiAction) created with
+/// [`create_server_multi_action`](crate::create_server_multi_action) sends the
+/// [idempotency key](QueueOptions::idempotency_key) of each submission in.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Calls an action with the idempotency key of the submission.
+pub(crate) type KeyedActionFn<I, O> =
+    Rc<dyn Fn(&I, &str) -> Pin<Box<dyn Future<Output = O>>>>;
+
+/// Configures a [`MultiAction`](crate::MultiAction) in queued mode.
+///
+/// See [`MultiAction::queued`](crate::MultiAction::queued) for more.
+pub struct QueueOptions<I> {
+    name: String,
+    storage: Rc<dyn StorageBackend>,
+    #[allow(clippy::type_complexity)]
+    idempotency_key: Option<Rc<dyn Fn(&I) -> String>>,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl<I> QueueOptions<I> {
+    /// Creates the options for a queue called `name`, which is used as the
+    /// key its submissions are saved under, so it should be unique to this
+    /// action.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            storage: Rc::new(LocalStorage),
+            idempotency_key: None,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets where the inputs of unresolved submissions are saved. Defaults
+    /// to [`LocalStorage`].
+    pub fn storage(mut self, storage: impl StorageBackend) -> Self {
+        self.storage = Rc::new(storage);
+        self
+    }
+
+    /// Sets the function that returns the idempotency key of an input.
+    ///
+    /// Dispatching an input whose key matches a submission that is still
+    /// unresolved does nothing. The key of a submission stays the same when
+    /// it is retried or restored, so the server can use it to recognize a
+    /// submission it has already handled.
+    ///
+    /// Defaults to a new, random key for every dispatch.
+    pub fn idempotency_key(
+        mut self,
+        key: impl Fn(&I) -> String + 'static,
+    ) -> Self {
+        self.idempotency_key = Some(Rc::new(key));
+        self
+    }
+
+    /// Sets how long to wait before the first retry of a queued submission,
+    /// and the longest to wait between retries. The wait doubles after each
+    /// failed retry. Defaults to 1 second and 1 minute.
+    pub fn backoff(
+        mut self,
+        initial_delay: Duration,
+        max_delay: Duration,
+    ) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+/// The submissions of a queued [`MultiAction`](crate::MultiAction) that have
+/// not resolved yet.
+pub(crate) struct ActionQueue<I, O>
+where
+    I: 'static,
+    O: 'static,
+{
+    options: QueueOptions<I>,
+    submissions: RwSignal<Vec<Submission<I, O>>>,
+    version: RwSignal<usize>,
+    action_fn: KeyedActionFn<I, O>,
+    should_retry: fn(&O) -> bool,
+    entries: RefCell<Vec<QueueEntry<I, O>>>,
+}
+
+/// The operations of an [`ActionQueue`] that don't depend on its output type,
+/// or on the bounds that are needed to create it.
+pub(crate) trait Queue<I> {
+    fn dispatch(self: Rc<Self>, input: I);
+
+    fn retry_queued(self: Rc<Self>);
+}
+
+impl<I, O> Queue<I> for ActionQueue<I, O>
+where
+    I: Serializable + 'static,
+    O: 'static,
+{
+    fn dispatch(self: Rc<Self>, input: I) {
+        ActionQueue::dispatch(&self, input);
+    }
+
+    fn retry_queued(self: Rc<Self>) {
+        ActionQueue::retry_queued(&self);
+    }
+}
+
+struct QueueEntry<I, O>
+where
+    I: 'static,
+    O: 'static,
+{
+    key: String,
+    /// The serialized input, if it could be serialized.
+    serialized: Option<String>,
+    submission: Submission<I, O>,
+    attempts: u32,
+}
+
+impl<I, O> ActionQueue<I, O>
+where
+    I: Serializable + 'static,
+    O: 'static,
+{
+    pub fn new(
+        options: QueueOptions<I>,
+        submissions: RwSignal<Vec<Submission<I, O>>>,
+        version: RwSignal<usize>,
+        action_fn: KeyedActionFn<I, O>,
+        should_retry: fn(&O) -> bool,
+    ) -> Rc<Self> {
+        let queue = Rc::new(Self {
+            options,
+            submissions,
+            version,
+            action_fn,
+            should_retry,
+            entries: Default::default(),
+        });
+
+        // effects don't run on the server, and run after hydration in the
+        // browser, so the restored submissions don't cause a mismatch
+        create_effect({
+            let queue = Rc::downgrade(&queue);
+            move |_| {
+                if let Some(queue) = queue.upgrade() {
+                    untrack(|| queue.restore());
+                }
+            }
+        });
+        on_online(Rc::downgrade(&queue));
+
+        queue
+    }
+
+    pub fn dispatch(self: &Rc<Self>, input: I) {
+        let serialized = match input.ser() {
+            Ok(serialized) => Some(serialized),
+            Err(e) => {
+                #[cfg(debug_assertions)]
+                console_warn(&format!(
+                    "[MultiAction::queued] could not serialize the input \
+                     for {:?}, so it will not be saved: {e}",
+                    self.options.name
+                ));
+                _ = e;
+                None
+            }
+        };
+        let key = match &self.options.idempotency_key {
+            Some(key) => key(&input),
+            None => generate_key(),
+        };
+        if self.entries.borrow().iter().any(|entry| entry.key == key) {
+            return;
+        }
+
+        let submission = Submission {
+            input: create_rw_signal(Some(input)),
+            value: create_rw_signal(None),
+            pending: create_rw_signal(true),
+            queued: create_rw_signal(false),
+            canceled: create_rw_signal(false),
+        };
+        self.submissions.update(|subs| subs.push(submission));
+        self.entries.borrow_mut().push(QueueEntry {
+            key: key.clone(),
+            serialized,
+            submission,
+            attempts: 0,
+        });
+        self.save();
+        self.attempt(key);
+    }
+
+    pub fn retry_queued(self: &Rc<Self>) {
+        let keys = self
+            .entries
+            .borrow()
+            .iter()
+            .filter(|entry| entry.submission.queued.get_untracked())
+            .map(|entry| entry.key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.attempt(key);
+        }
+    }
+
+    /// Adds the submissions that were saved by a previous queue with the same
+    /// name, and retries them.
+    fn restore(self: &Rc<Self>) {
+        let Some(saved) = self.options.storage.get(&self.options.name) else {
+            return;
+        };
+        let saved = decode_entries(&saved).unwrap_or_default();
+        for (key, serialized) in saved {
+            if self.entries.borrow().iter().any(|entry| entry.key == key) {
+                continue;
+            }
+            let Ok(input) = I::de(&serialized) else {
+                continue;
+            };
+            let submission = Submission {
+                input: create_rw_signal(Some(input)),
+                value: create_rw_signal(None),
+                pending: create_rw_signal(true),
+                queued: create_rw_signal(true),
+                canceled: create_rw_signal(false),
+            };
+            self.submissions.update(|subs| subs.push(submission));
+            self.entries.borrow_mut().push(QueueEntry {
+                key,
+                serialized: Some(serialized),
+                submission,
+                attempts: 0,
+            });
+        }
+        self.retry_queued();
+    }
+
+    /// Sends the submission with the given key to the server, unless the
+    /// browser is known to be offline.
+    fn attempt(self: &Rc<Self>, key: String) {
+        let Some(submission) = self.submission(&key) else {
+            return;
+        };
+        if submission.canceled.get_untracked() {
+            self.resolve(&key, None);
+            return;
+        }
+        if !is_online() {
+            submission.queued.set(true);
+            return;
+        }
+        let Some(fut) = submission.input.with_untracked(|input| {
+            input.as_ref().map(|i| (self.action_fn)(i, &key))
+        }) else {
+            return;
+        };
+        submission.queued.set(false);
+        let queue = Rc::clone(self);
+        spawn_local(async move {
+            let output = fut.await;
+            queue.settle(key, output);
+        });
+    }
+
+    fn settle(self: &Rc<Self>, key: String, output: O) {
+        let Some(submission) = self.submission(&key) else {
+            return;
+        };
+        if submission.canceled.get_untracked() {
+            self.resolve(&key, None);
+        } else if (self.should_retry)(&output) {
+            let attempts = {
+                let mut entries = self.entries.borrow_mut();
+                let Some(entry) =
+                    entries.iter_mut().find(|entry| entry.key == key)
+                else {
+                    return;
+                };
+                entry.attempts += 1;
+                entry.attempts
+            };
+            submission.queued.set(true);
+            self.schedule_retry(key, attempts);
+        } else {
+            self.resolve(&key, Some(output));
+        }
+    }
+
+    /// Removes the submission from the queue, setting its value if it was
+    /// not canceled.
+    fn resolve(&self, key: &str, output: Option<O>) {
+        let entry = {
+            let mut entries = self.entries.borrow_mut();
+            let Some(index) = entries.iter().position(|entry| entry.key == key)
+            else {
+                return;
+            };
+            entries.remove(index)
+        };
+        self.save();
+
+        let submission = entry.submission;
+        let version = self.version;
+        batch(move || {
+            if let Some(output) = output {
+                submission.value.set(Some(output));
+            }
+            submission.input.set(None);
+            submission.pending.set(false);
+            submission.queued.set(false);
+            version.update(|n| *n += 1);
+        });
+    }
+
+    fn schedule_retry(self: &Rc<Self>, key: String, attempts: u32) {
+        let factor = 2_u32.saturating_pow(attempts.saturating_sub(1));
+        let delay = self
+            .options
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.options.max_delay);
+        let queue = Rc::downgrade(self);
+        set_timeout(
+            move || {
+                let Some(queue) = queue.upgrade() else {
+                    return;
+                };
+                let still_queued = queue
+                    .submission(&key)
+                    .is_some_and(|sub| sub.queued.get_untracked());
+                if still_queued {
+                    queue.attempt(key);
+                }
+            },
+            delay,
+        );
+    }
+
+    fn submission(&self, key: &str) -> Option<Submission<I, O>> {
+        self.entries
+            .borrow()
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.submission)
+    }
+
+    fn save(&self) {
+        let entries = self.entries.borrow();
+        let saved = encode_entries(entries.iter().filter_map(|entry| {
+            Some((entry.key.as_str(), entry.serialized.as_deref()?))
+        }));
+        self.options.storage.set(&self.options.name, &saved);
+    }
+}
+
+/// Saves `(key, input)` pairs as a single string, each part prefixed with its
+/// length, so that they can contain any character.
+fn encode_entries<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> String {
+    let mut encoded = String::new();
+    for (key, input) in entries {
+        for part in [key, input] {
+            encoded.push_str(&part.len().to_string());
+            encoded.push(':');
+            encoded.push_str(part);
+        }
+    }
+    encoded
+}
+
+fn decode_entries(mut encoded: &str) -> Option<Vec<(String, String)>> {
+    fn part<'a>(encoded: &mut &'a str) -> Option<&'a str> {
+        let (len, rest) = encoded.split_once(':')?;
+        let len = len.parse::<usize>().ok()?;
+        let part = rest.get(..len)?;
+        *encoded = &rest[len..];
+        Some(part)
+    }
+
+    let mut entries = Vec::new();
+    while !encoded.is_empty() {
+        let key = part(&mut encoded)?;
+        let input = part(&mut encoded)?;
+        entries.push((key.to_string(), input.to_string()));
+    }
+    Some(entries)
+}
+
+/// A new, random idempotency key.
+fn generate_key() -> String {
+    format!("{:016x}{:016x}", random_u64(), random_u64())
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(all(
+        target_arch = "wasm32",
+        any(feature = "hydrate", feature = "csr")
+    ))] {
+        fn random_u64() -> u64 {
+            // `Math.random()` has 53 bits of randomness
+            let high = (js_sys::Math::random() * (1u64 << 32) as f64) as u64;
+            let low = (js_sys::Math::random() * (1u64 << 32) as f64) as u64;
+            (high << 32) | low
+        }
+
+        fn is_online() -> bool {
+            web_sys::window()
+                .map(|window| window.navigator().on_line())
+                .unwrap_or(true)
+        }
+
+        fn set_timeout(task: impl FnOnce() + 'static, delay: Duration) {
+            use wasm_bindgen::{prelude::Closure, JsCast};
+
+            if let Some(window) = web_sys::window() {
+                let task = Closure::once_into_js(task);
+                _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                    task.unchecked_ref::<js_sys::Function>(),
+                    delay.as_millis().try_into().unwrap_or(i32::MAX),
+                );
+            }
+        }
+
+        /// Retries every queued submission when the browser comes back online.
+        fn on_online<I, O>(queue: Weak<ActionQueue<I, O>>)
+        where
+            I: Serializable + 'static,
+            O: 'static,
+        {
+            use wasm_bindgen::{prelude::Closure, JsCast};
+
+            let Some(window) = web_sys::window() else {
+                return;
+            };
+            let listener = Closure::<dyn Fn()>::new(move || {
+                if let Some(queue) = queue.upgrade() {
+                    queue.retry_queued();
+                }
+            });
+            _ = window.add_event_listener_with_callback(
+                "online",
+                listener.as_ref().unchecked_ref(),
+            );
+            leptos_reactive::on_cleanup(move || {
+                _ = window.remove_event_listener_with_callback(
+                    "online",
+                    listener.as_ref().unchecked_ref(),
+                );
+            });
+        }
+    } else {
+        fn random_u64() -> u64 {
+            use std::{
+                collections::hash_map::RandomState,
+                hash::{BuildHasher, Hasher},
+                sync::atomic::{AtomicU64, Ordering},
+            };
+
+            // every `RandomState` is seeded differently, and the counter
+            // makes sure two keys are never hashed from the same input
+            static COUNTER: AtomicU64 = AtomicU64::new(0);
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+            hasher.finish()
+        }
+
+        fn is_online() -> bool {
+            true
+        }
+
+        // outside the browser, queued submissions are only retried by
+        // `MultiAction::retry_queued`
+        fn set_timeout(_task: impl FnOnce() + 'static, _delay: Duration) {}
+
+        fn on_online<I, O>(_queue: Weak<ActionQueue<I, O>>) {}
+    }
+}
@@ -0,0 +1,106 @@
+use leptos::*;
+use std::{cell::Cell, future::Future, rc::Rc};
+use tokio::task;
+
+/// An action that fails with [`ServerFnError::Request`], as if the server
+/// could not be reached, until `online` is set.
+fn note_action(
+    online: Rc<Cell<bool>>,
+) -> MultiAction<String, Result<usize, ServerFnError>> {
+    create_multi_action(move |note: &String| {
+        let result = if online.get() {
+            Ok(note.len())
+        } else {
+            Err(ServerFnError::Request("offline".into()))
+        };
+        async move { result }
+    })
+}
+
+/// Runs the test on a `LocalSet`, which actions are spawned onto with the
+/// `ssr` feature.
+fn run_local(test: impl Future<Output = ()>) {
+    tokio_test::block_on(task::LocalSet::new().run_until(test));
+}
+
+#[test]
+fn every_dispatch_gets_its_own_key_by_default() {
+    let runtime = create_runtime();
+
+    run_local(async {
+        let online = Rc::new(Cell::new(false));
+        let send = note_action(Rc::clone(&online)).queued(
+            QueueOptions::new("notes").storage(MemoryStorage::default()),
+        );
+
+        // the same input can be sent twice on purpose
+        send.dispatch("hello".to_string());
+        send.dispatch("hello".to_string());
+        task::yield_now().await;
+        let submissions = send.submissions().get();
+        assert_eq!(submissions.len(), 2);
+        assert!(submissions.iter().all(|sub| sub.queued().get()));
+
+        online.set(true);
+        send.retry_queued();
+        task::yield_now().await;
+        assert!(submissions.iter().all(|sub| sub.value.get() == Some(Ok(5))));
+    });
+
+    runtime.dispose();
+}
+
+#[test]
+fn submissions_with_the_same_key_are_only_queued_once() {
+    let runtime = create_runtime();
+
+    run_local(async {
+        let send = note_action(Rc::new(Cell::new(false))).queued(
+            QueueOptions::new("notes")
+                .storage(MemoryStorage::default())
+                .idempotency_key(|note: &String| note.clone()),
+        );
+
+        send.dispatch("hello".to_string());
+        send.dispatch("hello".to_string());
+        send.dispatch("world".to_string());
+        task::yield_now().await;
+        assert_eq!(send.submissions().get().len(), 2);
+    });
+
+    runtime.dispose();
+}
+
+#[test]
+fn queued_submissions_are_restored_with_their_keys() {
+    let storage = MemoryStorage::default();
+
+    let runtime = create_runtime();
+    run_local(async {
+        let send = note_action(Rc::new(Cell::new(false)))
+            .queued(QueueOptions::new("notes").storage(storage.clone()));
+        send.dispatch("hello".to_string());
+        task::yield_now().await;
+        assert!(storage.get("notes").is_some());
+    });
+    runtime.dispose();
+
+    // submissions are restored by an effect, which doesn't run on the server
+    if cfg!(feature = "ssr") {
+        return;
+    }
+
+    // e.g., after the page is reloaded
+    let runtime = create_runtime();
+    run_local(async {
+        let online = Rc::new(Cell::new(true));
+        let send = note_action(Rc::clone(&online))
+            .queued(QueueOptions::new("notes").storage(storage.clone()));
+        task::yield_now().await;
+        let submissions = send.submissions().get();
+        assert_eq!(submissions.len(), 1);
+        assert_eq!(submissions[0].value.get(), Some(Ok(5)));
+        assert_eq!(storage.get("notes").as_deref(), Some(""));
+    });
+    runtime.dispose();
+}
@@ -531,8 +531,11 @@ where
         .attr("method", "post")
         .attr("id", id)
         .attr("class", class)
-        .on(ev::submit, on_submit)
-        .child(children());
+        .on(ev::submit, on_submit);
+    if server_fn::csrf::ENABLED {
+        action_form = action_form.child(csrf_field());
+    }
+    action_form = action_form.child(children());
     if let Some(node_ref) = node_ref {
         action_form = action_form.node_ref(node_ref)
     };
@@ -626,8 +629,11 @@ where
         .attr("method", "post")
         .attr("id", id)
         .attr("class", class)
-        .on(ev::submit, on_submit)
-        .child(children());
+        .on(ev::submit, on_submit);
+    if server_fn::csrf::ENABLED {
+        action_form = action_form.child(csrf_field());
+    }
+    action_form = action_form.child(children());
     if let Some(node_ref) = node_ref {
         action_form = action_form.node_ref(node_ref)
     };
@@ -637,6 +643,23 @@ where
     action_form
 }
 
+/// A hidden field with a token that protects the form against cross-site
+/// request forgery. The token is only added on the server, as the form is
+/// only submitted without JavaScript before it has been hydrated, and is tied
+/// to the [`ClientSecret`](server_fn::csrf::ClientSecret) that the server
+/// integration provides.
+fn csrf_field() -> HtmlElement<html::Input> {
+    let field = html::input()
+        .attr("type", "hidden")
+        .attr("name", server_fn::csrf::CSRF_FIELD);
+    let token = use_context::<server_fn::csrf::ClientSecret>()
+        .and_then(|secret| secret.form_token());
+    match token {
+        Some(token) => field.attr("value", token),
+        None => field,
+    }
+}
+
 fn form_data_from_event(
     ev: &SubmitEvent,
 ) -> Result<FormData, FromFormDataError> {
@@ -0,0 +1,87 @@
+use super::{navigate_from_history, History, LocationChange, State};
+use leptos::*;
+use wasm_bindgen::UnwrapThrowExt;
+
+/// An integration for the browser that keeps the current route in the
+/// [`hash`](https://developer.mozilla.org/en-US/docs/Web/API/Location/hash)
+/// of the URL, like `https://example.com/#/posts/1`.
+///
+/// This is useful for apps served by static hosts that cannot send the same
+/// page for every path, because the server only ever sees the path before the
+/// `#`. Links are still written as ordinary paths, like `<A href="/posts/1">`,
+/// and the router moves them into the hash when they are followed.
+///
+/// The server never sees the hash, so this is meant for apps that are only
+/// rendered in the browser.
+///
+/// ```
+/// # use leptos_router::*;
+/// # use leptos::*;
+/// # let rt = create_runtime();
+/// provide_context(RouterIntegrationContext::new(HashHistory::default()));
+/// # rt.dispose();
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HashHistory {}
+
+impl HashHistory {
+    fn current() -> LocationChange {
+        let hash = leptos_dom::helpers::location().hash().unwrap_or_default();
+        let path = hash.strip_prefix('#').unwrap_or_default();
+        let state = window()
+            .history()
+            .and_then(|h| h.state())
+            .ok()
+            .and_then(|s| (!s.is_null()).then_some(s));
+
+        LocationChange {
+            value: if path.starts_with('/') {
+                path.to_string()
+            } else {
+                format!("/{path}")
+            },
+            replace: true,
+            scroll: true,
+            state: State(state),
+        }
+    }
+}
+
+impl History for HashHistory {
+    fn location(&self) -> ReadSignal<LocationChange> {
+        let (location, set_location) = create_signal(Self::current());
+
+        // changing the hash, with the back button or by editing the URL,
+        // fires `popstate` as well as `hashchange`
+        leptos::window_event_listener_untyped("popstate", move |_| {
+            if navigate_from_history(Self::current()) {
+                set_location.set(Self::current());
+            }
+        });
+
+        location
+    }
+
+    fn navigate(&self, loc: &LocationChange) {
+        let history = leptos_dom::window().history().unwrap_throw();
+        let url = format!("#{}", loc.value);
+
+        if loc.replace {
+            history
+                .replace_state_with_url(
+                    &loc.state.to_js_value(),
+                    "",
+                    Some(&url),
+                )
+                .unwrap_throw();
+        } else {
+            history
+                .push_state_with_url(&loc.state.to_js_value(), "", Some(&url))
+                .unwrap_throw();
+        }
+        // the hash holds the route, so it can't be used to scroll to an element
+        if loc.scroll {
+            leptos_dom::window().scroll_to_with_x_and_y(0.0, 0.0);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use super::{History, LocationChange};
+use leptos::*;
+use std::{cell::RefCell, rc::Rc};
+
+/// An integration that keeps its own stack of locations in memory, rather
+/// than using the URL of the browser.
+///
+/// This is useful where there is no URL bar, like an app embedded in a
+/// webview, and for driving a [`Router`](crate::Router) from tests that run
+/// without a browser. The stack can be moved through with
+/// [`push`](MemoryHistory::push), [`back`](MemoryHistory::back) and
+/// [`forward`](MemoryHistory::forward), like a user would with the URL bar and
+/// the back and forward buttons, and the router follows along. On the server,
+/// where effects do not run, the app has to be rendered again to see the new
+/// location.
+///
+/// ```
+/// # use leptos_router::*;
+/// # use leptos::*;
+/// # let rt = create_runtime();
+/// let history = MemoryHistory::new("/");
+/// provide_context(RouterIntegrationContext::new(history.clone()));
+///
+/// history.push("/posts/1");
+/// history.back();
+/// assert_eq!(history.current().value, "/");
+/// assert!(history.can_go_forward());
+/// # rt.dispose();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MemoryHistory {
+    inner: Rc<RefCell<MemoryStack>>,
+}
+
+#[derive(Debug)]
+struct MemoryStack {
+    entries: Vec<LocationChange>,
+    index: usize,
+    location: Option<RwSignal<LocationChange>>,
+}
+
+impl MemoryHistory {
+    /// Creates a history with a single entry for the given path.
+    pub fn new(initial: impl Into<String>) -> Self {
+        let entry = LocationChange {
+            value: initial.into(),
+            ..Default::default()
+        };
+        Self {
+            inner: Rc::new(RefCell::new(MemoryStack {
+                entries: vec![entry],
+                index: 0,
+                location: None,
+            })),
+        }
+    }
+
+    /// The current entry.
+    pub fn current(&self) -> LocationChange {
+        let stack = self.inner.borrow();
+        stack.entries[stack.index].clone()
+    }
+
+    /// Whether there is an entry before the current one.
+    pub fn can_go_back(&self) -> bool {
+        self.inner.borrow().index > 0
+    }
+
+    /// Whether there is an entry after the current one.
+    pub fn can_go_forward(&self) -> bool {
+        let stack = self.inner.borrow();
+        stack.index + 1 < stack.entries.len()
+    }
+
+    /// Adds a new entry for the given path after the current one, dropping
+    /// any entries that could have been reached with
+    /// [`forward`](MemoryHistory::forward).
+    pub fn push(&self, path: impl Into<String>) {
+        self.push_entry(LocationChange {
+            value: path.into(),
+            replace: false,
+            ..Default::default()
+        });
+    }
+
+    /// Replaces the current entry with one for the given path.
+    pub fn replace(&self, path: impl Into<String>) {
+        self.replace_entry(LocationChange {
+            value: path.into(),
+            ..Default::default()
+        });
+    }
+
+    /// Moves to the previous entry, if there is one.
+    pub fn back(&self) {
+        self.go(-1);
+    }
+
+    /// Moves to the next entry, if there is one.
+    pub fn forward(&self) {
+        self.go(1);
+    }
+
+    /// Moves by `delta` entries, backwards if it is negative. Like
+    /// [`History.go()`](https://developer.mozilla.org/en-US/docs/Web/API/History/go)
+    /// in the browser, this does nothing if there is no such entry.
+    pub fn go(&self, delta: isize) {
+        self.update(|stack| {
+            let Some(index) = stack
+                .index
+                .checked_add_signed(delta)
+                .filter(|index| *index < stack.entries.len())
+            else {
+                return false;
+            };
+            stack.index = index;
+            true
+        });
+    }
+
+    fn push_entry(&self, entry: LocationChange) {
+        self.update(|stack| {
+            stack.entries.truncate(stack.index + 1);
+            stack.entries.push(entry);
+            stack.index += 1;
+            true
+        });
+    }
+
+    fn replace_entry(&self, entry: LocationChange) {
+        self.update(|stack| {
+            stack.entries[stack.index] = entry;
+            true
+        });
+    }
+
+    /// Changes the stack, then tells the router about the new current entry
+    /// if `f` returns `true`.
+    fn update(&self, f: impl FnOnce(&mut MemoryStack) -> bool) {
+        let (location, current) = {
+            let mut stack = self.inner.borrow_mut();
+            if !f(&mut stack) {
+                return;
+            }
+            (stack.location, stack.entries[stack.index].clone())
+        };
+        // the stack is no longer borrowed, because the router may navigate
+        // again in response
+        if let Some(location) = location {
+            location.try_set(current);
+        }
+    }
+}
+
+impl History for MemoryHistory {
+    fn location(&self) -> ReadSignal<LocationChange> {
+        let mut stack = self.inner.borrow_mut();
+        let current = stack.entries[stack.index].clone();
+        // a signal from a runtime that has since been disposed is replaced
+        let location = match stack.location {
+            Some(location) if location.try_get_untracked().is_some() => {
+                location
+            }
+            _ => create_rw_signal(current),
+        };
+        stack.location = Some(location);
+        location.read_only()
+    }
+
+    fn navigate(&self, loc: &LocationChange) {
+        if loc.replace {
+            self.replace_entry(loc.clone());
+        } else {
+            self.push_entry(loc.clone());
+        }
+    }
+}
@@ -2,13 +2,17 @@ use leptos::*;
 use std::rc::Rc;
 use wasm_bindgen::UnwrapThrowExt;
 
+mod hash;
 mod location;
+mod memory;
 mod params;
 mod state;
 mod url;
 
 pub use self::url::*;
+pub use hash::*;
 pub use location::*;
+pub use memory::*;
 pub use params::*;
 pub use state::*;
 
@@ -56,49 +60,11 @@ impl BrowserIntegration {
 
 impl History for BrowserIntegration {
     fn location(&self) -> ReadSignal<LocationChange> {
-        use crate::{NavigateOptions, RouterContext};
-
         let (location, set_location) = create_signal(Self::current());
 
         leptos::window_event_listener_untyped("popstate", move |_| {
-            let router = use_context::<RouterContext>();
-            if let Some(router) = router {
-                let path_stack = router.inner.path_stack;
-
-                let is_back = router.inner.is_back;
-                let change = Self::current();
-
-                let is_navigating_back = path_stack.with_value(|stack| {
-                    stack.len() == 1
-                        || (stack.len() >= 2
-                            && stack.get(stack.len() - 2)
-                                == Some(&change.value))
-                });
-                if is_navigating_back {
-                    path_stack.update_value(|stack| {
-                        stack.pop();
-                    });
-                }
-
-                is_back.set(is_navigating_back);
-
-                request_animation_frame(move || {
-                    is_back.set(false);
-                });
-                if let Err(e) = router.inner.navigate_from_route(
-                    &change.value,
-                    &NavigateOptions {
-                        resolve: false,
-                        replace: change.replace,
-                        scroll: change.scroll,
-                        state: change.state,
-                    },
-                ) {
-                    leptos::logging::error!("{e:#?}");
-                }
+            if navigate_from_history(Self::current()) {
                 set_location.set(Self::current());
-            } else {
-                leptos::logging::warn!("RouterContext not found");
             }
         });
 
@@ -128,6 +94,50 @@ impl History for BrowserIntegration {
     }
 }
 
+/// Tells the router about a change of location that came from the browser,
+/// like the back button, rather than from the router itself. Returns `false`
+/// if there is no router to tell.
+pub(crate) fn navigate_from_history(change: LocationChange) -> bool {
+    use crate::{NavigateOptions, RouterContext};
+
+    let Some(router) = use_context::<RouterContext>() else {
+        leptos::logging::warn!("RouterContext not found");
+        return false;
+    };
+    let path_stack = router.inner.path_stack;
+
+    let is_back = router.inner.is_back;
+
+    let is_navigating_back = path_stack.with_value(|stack| {
+        stack.len() == 1
+            || (stack.len() >= 2
+                && stack.get(stack.len() - 2) == Some(&change.value))
+    });
+    if is_navigating_back {
+        path_stack.update_value(|stack| {
+            stack.pop();
+        });
+    }
+
+    is_back.set(is_navigating_back);
+
+    request_animation_frame(move || {
+        is_back.set(false);
+    });
+    if let Err(e) = router.inner.navigate_from_route(
+        &change.value,
+        &NavigateOptions {
+            resolve: false,
+            replace: change.replace,
+            scroll: change.scroll,
+            state: change.state,
+        },
+    ) {
+        leptos::logging::error!("{e:#?}");
+    }
+    true
+}
+
 pub(crate) fn scroll_to_el(loc_scroll: bool) {
     if let Ok(hash) = leptos_dom::helpers::location().hash() {
         if !hash.is_empty() {
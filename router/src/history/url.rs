@@ -106,7 +106,10 @@ impl TryFrom<&str> for Url {
     type Error = String;
 
     fn try_from(url: &str) -> Result<Self, Self::Error> {
-        let url = url::Url::parse(url).map_err(|e| e.to_string())?;
+        // paths are resolved against a placeholder origin, as in the browser
+        let url = url::Url::parse("http://leptos")
+            .and_then(|base| base.join(url))
+            .map_err(|e| e.to_string())?;
         Ok(Self {
             origin: url.origin().unicode_serialization(),
             pathname: url.path().to_string(),
@@ -0,0 +1,107 @@
+use leptos::*;
+use leptos_router::*;
+
+#[test]
+fn memory_history_moves_through_stack() {
+    let rt = create_runtime();
+    let history = MemoryHistory::new("/");
+    let location = history.location();
+
+    history.push("/a");
+    history.push("/b");
+    assert_eq!(location.get_untracked().value, "/b");
+    assert!(history.can_go_back());
+    assert!(!history.can_go_forward());
+
+    history.back();
+    assert_eq!(location.get_untracked().value, "/a");
+    history.go(-1);
+    assert_eq!(history.current().value, "/");
+    assert!(!history.can_go_back());
+
+    // moving past either end does nothing
+    history.back();
+    assert_eq!(history.current().value, "/");
+    history.go(5);
+    assert_eq!(history.current().value, "/");
+
+    history.go(2);
+    assert_eq!(location.get_untracked().value, "/b");
+
+    rt.dispose();
+}
+
+#[test]
+fn memory_history_push_drops_forward_entries() {
+    let rt = create_runtime();
+    let history = MemoryHistory::new("/");
+
+    history.push("/a");
+    history.push("/b");
+    history.back();
+    history.back();
+    history.push("/c");
+    assert!(!history.can_go_forward());
+    history.back();
+    assert_eq!(history.current().value, "/");
+
+    history.forward();
+    history.replace("/d");
+    history.back();
+    history.forward();
+    assert_eq!(history.current().value, "/d");
+
+    rt.dispose();
+}
+
+#[test]
+fn memory_history_follows_router_navigation() {
+    let rt = create_runtime();
+    let history = MemoryHistory::new("/");
+    let location = history.location();
+
+    history.navigate(&LocationChange {
+        value: "/a".to_string(),
+        replace: false,
+        ..Default::default()
+    });
+    history.navigate(&LocationChange {
+        value: "/b".to_string(),
+        replace: true,
+        ..Default::default()
+    });
+    assert_eq!(location.get_untracked().value, "/b");
+
+    history.back();
+    assert_eq!(location.get_untracked().value, "/");
+
+    rt.dispose();
+}
+
+#[cfg(feature = "ssr")]
+#[test]
+fn memory_history_drives_router() {
+    #[component]
+    fn App() -> impl IntoView {
+        view! {
+            <Router>
+                <Routes>
+                    <Route path="/" view=|| "home"/>
+                    <Route path="/posts/:id" view=|| "post"/>
+                </Routes>
+            </Router>
+        }
+    }
+
+    let rt = create_runtime();
+    let history = MemoryHistory::new("/");
+    provide_context(RouterIntegrationContext::new(history.clone()));
+
+    assert!(App().into_view().render_to_string().contains("home"));
+    history.push("/posts/1");
+    assert!(App().into_view().render_to_string().contains("post"));
+    history.back();
+    assert!(App().into_view().render_to_string().contains("home"));
+
+    rt.dispose();
+}
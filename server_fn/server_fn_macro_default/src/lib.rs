@@ -5,7 +5,7 @@
 //! See the [server_fn_macro] crate for more information.
 
 use proc_macro::TokenStream;
-use server_fn_macro::server_macro_impl;
+use server_fn_macro::{schema_derive_impl, server_macro_impl};
 use syn::__private::ToTokens;
 
 /// Declares that a function is a [server function](https://docs.rs/server_fn/).
@@ -82,3 +82,18 @@ pub fn server(args: proc_macro::TokenStream, s: TokenStream) -> TokenStream {
         Ok(s) => s.to_token_stream().into(),
     }
 }
+
+/// Derives [`Schema`](https://docs.rs/server_fn/latest/server_fn/openapi/trait.Schema.html),
+/// which describes the type in the OpenAPI document generated for server
+/// functions. This does nothing unless server_fn's `openapi` feature is
+/// enabled.
+///
+/// The schema follows the way the type is serialized by `serde`, including
+/// its `rename`, `rename_all`, `default` and `skip` attributes.
+#[proc_macro_derive(Schema, attributes(serde))]
+pub fn schema_derive(s: TokenStream) -> TokenStream {
+    match schema_derive_impl(s.into(), Some(syn::parse_quote!(server_fns))) {
+        Err(e) => e.to_compile_error().into(),
+        Ok(s) => s.to_token_stream().into(),
+    }
+}
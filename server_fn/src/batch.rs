@@ -0,0 +1,358 @@
+//! Batches calls to several server functions into a single HTTP request.
+//!
+//! Batching is turned off by default, and turned on for the whole client with
+//! [`set_batching`]. Once it is on, the calls that are made together (for
+//! example, by the resources created while a page hydrates) are sent to
+//! [`BATCH_PATH`] in one request, rather than each to its own endpoint. The
+//! server runs each call as if it had been sent on its own, and the client
+//! hands each response back to the call that made it, so that an error in one
+//! call does not affect the others.
+//!
+//! Server functions that stream their arguments or their output, that take
+//! multipart form data, or that use a websocket, are always sent on their own.
+//! A batch only applies the global
+//! [`ClientPolicy`](crate::client::ClientPolicy), rather than that of each
+//! server function, but the [`Interceptor`](crate::client::Interceptor)s of
+//! each call still see its own request and response.
+//!
+//! The server only accepts batches sent as `application/json`, and only keeps
+//! the [`BATCH_CALL_HEADERS`] of each call. Every other header, like `Cookie`,
+//! `Origin` or `Host`, is taken from the batch itself, so that a call cannot
+//! claim to have been sent from somewhere else.
+//!
+//! A batch holds at most [`MAX_BATCH_CALLS`] calls, and its body is at most
+//! [`MAX_BATCH_SIZE`] bytes long. The client splits the calls into as many
+//! batches as it needs, and the server rejects batches that are larger.
+//!
+//! Batching is only supported by the Axum integration, which handles batches
+//! in [`handle_server_fn`](crate::axum::handle_server_fn). Running a call in a
+//! batch means building a new request for it, and Actix does not let an
+//! `HttpRequest` be built outside of the server while keeping the app data
+//! that extractors rely on. The Actix integration responds to batches with
+//! `501 Not Implemented` instead, and the client then sends each call on its
+//! own, and turns batching off. Only turn batching on for an app that is
+//! served with Axum.
+
+// only the clients send batches
+#![cfg_attr(
+    not(any(feature = "browser", feature = "reqwest")),
+    allow(dead_code)
+)]
+
+use crate::{
+    client::{get_client_policy, Client},
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    request::ClientReq,
+    response::ClientRes,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use futures::{channel::oneshot, future::join_all, Future};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+/// The path that batches of server function calls are sent to.
+pub const BATCH_PATH: &str = "/api/server_fn_batch";
+
+/// The most calls that a single batch can hold.
+pub const MAX_BATCH_CALLS: usize = 32;
+
+/// The largest body that a batch can have, in bytes.
+pub const MAX_BATCH_SIZE: usize = 2 * 1024 * 1024;
+
+/// The headers of a call in a batch that the server passes on to its server
+/// function. Any other header of the call is ignored.
+pub const BATCH_CALL_HEADERS: [&str; 3] =
+    ["content-type", "accept", "idempotency-key"];
+
+static BATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Turns batching on or off for every server function called after this.
+///
+/// Batches are only handled by the Axum integration, so this should only be
+/// turned on for an app that is served with Axum.
+pub fn set_batching(enabled: bool) {
+    BATCHING.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether batching is turned on.
+pub fn is_batching() -> bool {
+    BATCHING.load(Ordering::Relaxed)
+}
+
+/// A server function call, as sent in a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchCall {
+    /// The HTTP method of the call.
+    pub method: String,
+    /// The path of the server function, including the query string.
+    pub path: String,
+    /// The headers of the call. Those in [`BATCH_CALL_HEADERS`] replace the
+    /// headers of the batch, and the others are ignored.
+    pub headers: Vec<(String, String)>,
+    /// The body of the call.
+    #[serde(serialize_with = "ser_body", deserialize_with = "de_body")]
+    pub body: Bytes,
+}
+
+/// The response to a server function call, as received in a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// The status code of the response.
+    pub status: u16,
+    /// The headers of the response.
+    pub headers: Vec<(String, String)>,
+    /// The body of the response.
+    #[serde(serialize_with = "ser_body", deserialize_with = "de_body")]
+    pub body: Bytes,
+}
+
+fn ser_body<S: Serializer>(body: &Bytes, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&STANDARD.encode(body))
+}
+
+fn de_body<'de, D: Deserializer<'de>>(d: D) -> Result<Bytes, D::Error> {
+    let body = String::deserialize(d)?;
+    STANDARD
+        .decode(body)
+        .map(Bytes::from)
+        .map_err(serde::de::Error::custom)
+}
+
+/// A call that is waiting for its batch to be sent.
+pub(crate) struct Queued<C: BatchClient> {
+    call: BatchCall,
+    tx: oneshot::Sender<Result<C::Response, ServerFnError>>,
+}
+
+/// What a client needs in order to send its requests in batches.
+pub(crate) trait BatchClient:
+    Client<NoCustomError> + Sized + 'static
+{
+    /// Reads a request into a call.
+    fn into_call(
+        req: Self::Request,
+    ) -> impl Future<Output = Result<BatchCall, ServerFnError>> + Send;
+
+    /// Turns a call back into a request, to send it on its own.
+    fn from_call(call: BatchCall) -> Result<Self::Request, ServerFnError>;
+
+    /// Turns the response to a call into a response of this client.
+    fn into_response(
+        res: BatchResponse,
+    ) -> Result<Self::Response, ServerFnError>;
+
+    /// The calls waiting to be sent by this client.
+    fn queue() -> &'static Mutex<Vec<Queued<Self>>>;
+
+    /// Runs the future once the calls that are being made at the same time
+    /// have been queued.
+    fn spawn(fut: impl Future<Output = ()> + Send + 'static);
+}
+
+/// Queues the request, to be sent along with the others that are made at the
+/// same time, and returns its response once the batch has been sent.
+pub(crate) async fn send<C: BatchClient>(
+    req: C::Request,
+) -> Result<C::Response, ServerFnError> {
+    let call = C::into_call(req).await?;
+    let (tx, rx) = oneshot::channel();
+    let first = {
+        let mut queue = C::queue().lock().unwrap_or_else(|e| e.into_inner());
+        queue.push(Queued { call, tx });
+        queue.len() == 1
+    };
+    if first {
+        C::spawn(flush::<C>());
+    }
+    rx.await.unwrap_or_else(|_| {
+        Err(ServerFnError::Request(
+            "The batch was cancelled.".to_string(),
+        ))
+    })
+}
+
+/// Sends the queued calls, in as many batches as they need.
+async fn flush<C: BatchClient>() {
+    let queued = std::mem::take(
+        &mut *C::queue().lock().unwrap_or_else(|e| e.into_inner()),
+    );
+    let mut encoded = Vec::with_capacity(queued.len());
+    for queued in queued {
+        match serde_json::to_string(&queued.call) {
+            Ok(call) => encoded.push((call, queued)),
+            Err(e) => {
+                _ = queued
+                    .tx
+                    .send(Err(ServerFnError::Serialization(e.to_string())));
+            }
+        }
+    }
+    let batches = split_batches(encoded, |(call, _)| call.len());
+    join_all(batches.into_iter().map(send_queued::<C>)).await;
+}
+
+/// Splits the encoded calls into batches of at most [`MAX_BATCH_CALLS`]
+/// calls, whose bodies are at most [`MAX_BATCH_SIZE`] bytes long, unless a
+/// single call is longer.
+fn split_batches<T>(calls: Vec<T>, len: impl Fn(&T) -> usize) -> Vec<Vec<T>> {
+    let mut batches = Vec::new();
+    let mut batch = Vec::new();
+    // the brackets around the calls
+    let mut size = 2;
+    for call in calls {
+        // and the comma before each call but the first
+        let call_size = len(&call) + 1;
+        if batch.len() == MAX_BATCH_CALLS
+            || (!batch.is_empty() && size + call_size > MAX_BATCH_SIZE)
+        {
+            batches.push(std::mem::take(&mut batch));
+            size = 2;
+        }
+        size += call_size;
+        batch.push(call);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+/// Sends the encoded calls as a batch, or on their own if there is only one
+/// of them, or if the server does not support batches.
+async fn send_queued<C: BatchClient>(queued: Vec<(String, Queued<C>)>) {
+    if queued.len() == 1 {
+        let (_, queued) = queued.into_iter().next().unwrap();
+        send_alone(queued).await;
+        return;
+    }
+
+    let (calls, queued): (Vec<_>, Vec<_>) = queued.into_iter().unzip();
+    let body = format!("[{}]", calls.join(","));
+    match send_batch::<C>(body, calls.len()).await {
+        Ok(Some(responses)) => {
+            for (queued, res) in queued.into_iter().zip(responses) {
+                _ = queued.tx.send(C::into_response(res));
+            }
+        }
+        Ok(None) => {
+            set_batching(false);
+            join_all(queued.into_iter().map(send_alone)).await;
+        }
+        Err(err) => {
+            for queued in queued {
+                _ = queued.tx.send(Err(err.clone()));
+            }
+        }
+    }
+}
+
+/// Sends a queued call on its own.
+async fn send_alone<C: BatchClient>(queued: Queued<C>) {
+    let Queued { call, tx } = queued;
+    let res = match C::from_call(call) {
+        Ok(req) => C::send_with_policy(req, get_client_policy()).await,
+        Err(err) => Err(err),
+    };
+    _ = tx.send(res);
+}
+
+/// Sends the encoded calls to [`BATCH_PATH`], and returns their responses,
+/// or `None` if the server does not support batches.
+async fn send_batch<C: BatchClient>(
+    body: String,
+    calls: usize,
+) -> Result<Option<Vec<BatchResponse>>, ServerFnError> {
+    let req = <C::Request as ClientReq<NoCustomError>>::try_new_post(
+        BATCH_PATH,
+        "application/json",
+        "application/json",
+        body,
+    )?;
+    let res = C::send_with_policy(req, get_client_policy()).await?;
+    let status = res.status();
+    if status == 501 {
+        return Ok(None);
+    }
+    let body = res.try_into_bytes().await?;
+    if !(200..=299).contains(&status) {
+        return Err(ServerFnError::de(&String::from_utf8_lossy(&body)));
+    }
+    let responses = serde_json::from_slice::<Vec<BatchResponse>>(&body)
+        .map_err(|e| {
+            ServerFnError::<NoCustomError>::Deserialization(e.to_string())
+        })?;
+    if responses.len() != calls {
+        return Err(ServerFnError::Deserialization(format!(
+            "Expected {calls} responses in the batch, but received {}.",
+            responses.len()
+        )));
+    }
+    Ok(Some(responses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_encoded_with_base64_bodies() {
+        let call = BatchCall {
+            method: "POST".to_string(),
+            path: "/api/add_todo".to_string(),
+            headers: vec![("x-trace".to_string(), "1".to_string())],
+            body: Bytes::from_static(b"\0\xffbinary"),
+        };
+        let encoded = serde_json::to_value(&call).unwrap();
+        assert_eq!(encoded["body"], STANDARD.encode(b"\0\xffbinary"));
+        assert_eq!(serde_json::from_value::<BatchCall>(encoded).unwrap(), call);
+
+        let res = BatchResponse {
+            status: 500,
+            headers: vec![],
+            body: Bytes::from_static(b"ServerError|oops"),
+        };
+        let encoded = serde_json::to_string(&res).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BatchResponse>(&encoded).unwrap(),
+            res
+        );
+        assert!(serde_json::from_str::<BatchResponse>(
+            r#"{"status":200,"headers":[],"body":"not base64!"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn batches_hold_a_limited_number_of_calls() {
+        let batches = split_batches((0..70).collect(), |_| 10);
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            [MAX_BATCH_CALLS, MAX_BATCH_CALLS, 70 - 2 * MAX_BATCH_CALLS]
+        );
+        assert_eq!(batches.concat(), (0..70).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn batches_have_a_limited_size() {
+        let third = MAX_BATCH_SIZE / 3;
+        let sizes = [third, third, MAX_BATCH_SIZE * 2, 1, third, third];
+        let batches = split_batches(sizes.to_vec(), |size| *size);
+        assert_eq!(
+            batches,
+            [
+                vec![third, third],
+                // a call that is too large on its own is sent on its own
+                vec![MAX_BATCH_SIZE * 2],
+                vec![1, third, third],
+            ]
+        );
+        for batch in batches.iter().filter(|batch| batch.len() > 1) {
+            let body = 2 + batch.iter().map(|size| size + 1).sum::<usize>();
+            assert!(body <= MAX_BATCH_SIZE);
+        }
+    }
+}
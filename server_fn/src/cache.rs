@@ -0,0 +1,204 @@
+//! HTTP caching for the responses of server functions that are called with
+//! `GET`, like those that use [`GetUrl`](crate::codec::GetUrl).
+//!
+//! A server function with a [`CachePolicy`] sets the `Cache-Control` header of
+//! its successful responses, along with an `ETag` computed from the body. A
+//! request whose `If-None-Match` header matches that `ETag` is answered with
+//! `304 Not Modified` and no body, so that the client can reuse the body it
+//! already has. The server function still runs, so this saves sending the
+//! body, but not the work of producing it. Caches in front of the server, like
+//! a CDN, can avoid that too, for as long as the policy allows.
+//!
+//! In the browser, `fetch` reuses the results through the browser's cache.
+//! The `reqwest` client keeps results in memory for as long as the policy
+//! allows, and then revalidates them with their `ETag`. As these are shared by
+//! the whole process, it only reuses a result for requests with the same
+//! `Authorization` and `Cookie` headers, and does not keep the results of a
+//! [`CachePolicy::private`] at all.
+//!
+//! ```rust,ignore
+//! #[server(input = GetUrl, cache = CachePolicy::public(Duration::from_secs(60)))]
+//! pub async fn list_posts() -> Result<Vec<Post>, ServerFnError> {
+//!     // ...
+//! }
+//! ```
+
+use std::time::Duration;
+
+/// How the response of a server function may be cached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// How long the response can be reused without checking with the server.
+    /// If this is zero, the response must be revalidated every time.
+    pub max_age: Duration,
+    /// How long shared caches, like a CDN, can reuse the response, if that is
+    /// different from [`max_age`](CachePolicy::max_age).
+    pub shared_max_age: Option<Duration>,
+    /// How long a cache can keep using the response after it has expired,
+    /// while it revalidates it in the background.
+    pub stale_while_revalidate: Option<Duration>,
+    /// Whether the response is specific to the user, so that only the client
+    /// may store it, and not shared caches.
+    pub private: bool,
+}
+
+impl CachePolicy {
+    /// Allows any cache to reuse the response for `max_age`.
+    pub fn public(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            shared_max_age: None,
+            stale_while_revalidate: None,
+            private: false,
+        }
+    }
+
+    /// Allows only the client to reuse the response for `max_age`, for
+    /// responses that are specific to the user.
+    pub fn private(max_age: Duration) -> Self {
+        Self {
+            private: true,
+            ..Self::public(max_age)
+        }
+    }
+
+    /// Requires the response to be revalidated every time it is used, which
+    /// saves sending the body again while it has not changed.
+    pub fn revalidate() -> Self {
+        Self::public(Duration::ZERO)
+    }
+
+    /// Sets how long shared caches can reuse the response.
+    pub fn shared_max_age(mut self, shared_max_age: Duration) -> Self {
+        self.shared_max_age = Some(shared_max_age);
+        self
+    }
+
+    /// Sets how long an expired response can be used while it is revalidated.
+    pub fn stale_while_revalidate(mut self, stale: Duration) -> Self {
+        self.stale_while_revalidate = Some(stale);
+        self
+    }
+
+    /// The value of the `Cache-Control` header for this policy.
+    pub fn header_value(&self) -> String {
+        let mut value =
+            String::from(if self.private { "private" } else { "public" });
+        if self.max_age.is_zero() {
+            value.push_str(", no-cache");
+        } else {
+            value.push_str(&format!(", max-age={}", self.max_age.as_secs()));
+        }
+        if let Some(shared_max_age) = self.shared_max_age {
+            value.push_str(&format!(", s-maxage={}", shared_max_age.as_secs()));
+        }
+        if let Some(stale) = self.stale_while_revalidate {
+            value.push_str(&format!(
+                ", stale-while-revalidate={}",
+                stale.as_secs()
+            ));
+        }
+        value
+    }
+}
+
+/// Computes a strong `ETag` for a response body.
+pub fn etag(body: &[u8]) -> String {
+    let hash = xxhash_rust::const_xxh64::xxh64(body, 0);
+    format!("\"{hash:016x}\"")
+}
+
+/// Whether the value of an `If-None-Match` header matches `etag`, so that the
+/// client already has the current body.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.trim_start_matches("W/") == etag
+    })
+}
+
+/// Returns how long a response with the given `Cache-Control` header can be
+/// reused without checking with the server, or `None` if it should not be
+/// stored at all.
+pub fn max_age(cache_control: &str) -> Option<Duration> {
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+        let (name, value) =
+            directive.split_once('=').unwrap_or((directive, ""));
+        match name.to_ascii_lowercase().as_str() {
+            "no-store" => return None,
+            "no-cache" => max_age = Some(Duration::ZERO),
+            "max-age" if max_age.is_none() => {
+                max_age = value
+                    .trim_matches('"')
+                    .parse()
+                    .ok()
+                    .map(Duration::from_secs);
+            }
+            _ => {}
+        }
+    }
+    max_age
+}
+
+/// Whether a response with the given `Cache-Control` header is specific to
+/// the user, so that only a cache that belongs to that user may store it.
+pub fn is_private(cache_control: &str) -> bool {
+    cache_control.split(',').any(|directive| {
+        let name = directive.split('=').next().unwrap_or_default();
+        name.trim().eq_ignore_ascii_case("private")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_age_follows_the_directives() {
+        let secs = |secs| Some(Duration::from_secs(secs));
+        assert_eq!(max_age("public, max-age=60"), secs(60));
+        assert_eq!(max_age("Max-Age=\"30\""), secs(30));
+        assert_eq!(max_age("max-age=60, max-age=10"), secs(60));
+        assert_eq!(max_age("public, no-cache"), secs(0));
+        assert_eq!(max_age("no-cache, max-age=60"), secs(0));
+        assert_eq!(max_age("max-age=60, no-store"), None);
+        assert_eq!(max_age("max-age=soon"), None);
+        assert_eq!(max_age("public"), None);
+        assert_eq!(max_age(""), None);
+    }
+
+    #[test]
+    fn policies_are_read_back() {
+        let policy = CachePolicy::private(Duration::from_secs(60))
+            .shared_max_age(Duration::from_secs(600))
+            .stale_while_revalidate(Duration::from_secs(5));
+        let header = policy.header_value();
+        assert_eq!(
+            header,
+            "private, max-age=60, s-maxage=600, stale-while-revalidate=5"
+        );
+        assert_eq!(max_age(&header), Some(Duration::from_secs(60)));
+        assert!(is_private(&header));
+
+        let header = CachePolicy::revalidate().header_value();
+        assert_eq!(max_age(&header), Some(Duration::ZERO));
+        assert!(!is_private(&header));
+        assert!(is_private("max-age=60, Private=\"Set-Cookie\""));
+    }
+
+    #[test]
+    fn etags_match_any_candidate() {
+        let tag = etag(b"hello");
+        assert_eq!(tag, etag(b"hello"));
+        assert_ne!(tag, etag(b"hello!"));
+
+        assert!(etag_matches(&tag, &tag));
+        assert!(etag_matches(&format!("\"other\", {tag}"), &tag));
+        assert!(etag_matches(&format!("W/{tag}"), &tag));
+        assert!(etag_matches(&tag, &format!("W/{tag}")));
+        assert!(etag_matches("*", &tag));
+        assert!(!etag_matches("\"other\"", &tag));
+        assert!(!etag_matches("", &tag));
+    }
+}
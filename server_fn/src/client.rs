@@ -1,7 +1,23 @@
-use crate::{error::ServerFnError, request::ClientReq, response::ClientRes};
-use std::{future::Future, sync::OnceLock};
+use crate::{
+    codec::{parse_events, RawEvent},
+    error::{ServerFnError, ServerFnErrorSerde},
+    request::ClientReq,
+    response::ClientRes,
+};
+#[cfg(feature = "websocket")]
+use bytes::Bytes;
+use futures::Stream;
+use std::{
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::Duration,
+};
 
 static ROOT_URL: OnceLock<&'static str> = OnceLock::new();
+static CLIENT_POLICY: RwLock<Option<ClientPolicy>> = RwLock::new(None);
 
 /// Set the root server URL that all server function paths are relative to for the client.
 ///
@@ -15,6 +31,99 @@ pub fn get_server_url() -> &'static str {
     ROOT_URL.get().copied().unwrap_or("")
 }
 
+/// Controls how long the client waits for a server function to respond, and
+/// whether it sends the request again if it fails.
+///
+/// The default policy waits indefinitely and never retries, which is how
+/// server functions behave unless a policy is set, either for all of them with
+/// [`set_client_policy`] or for one of them with the `retries`, `timeout_ms`
+/// and `client_policy` arguments of the `#[server]` macro.
+///
+/// A request that fails without a response, or that times out, is only
+/// retried if its method is idempotent (like `GET`), because the server may
+/// have handled it anyway. A request that receives one of the
+/// [`retry_statuses`](ClientPolicy::retry_statuses) is retried whatever its
+/// method. Requests with a streaming body cannot be retried.
+///
+/// ```rust,ignore
+/// set_client_policy(ClientPolicy {
+///     timeout: Some(Duration::from_secs(10)),
+///     retries: 3,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientPolicy {
+    /// How long to wait for each attempt to receive a response, or `None` to
+    /// wait indefinitely.
+    pub timeout: Option<Duration>,
+    /// How many times to send the request again after the first attempt.
+    pub retries: u32,
+    /// How long to wait before the first retry. The delay doubles after each
+    /// retry.
+    pub backoff: Duration,
+    /// The longest delay between two attempts.
+    pub max_backoff: Duration,
+    /// Whether to shorten each delay by a random amount, up to half of it, so
+    /// that clients that failed at the same time do not all retry at the same
+    /// time.
+    pub jitter: bool,
+    /// The response statuses after which the request is retried, whatever its
+    /// method. By default, these are `429 Too Many Requests` and
+    /// `503 Service Unavailable`, which mean that the server has not handled
+    /// the request.
+    pub retry_statuses: Vec<u16>,
+}
+
+impl Default for ClientPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            retries: 0,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_statuses: vec![429, 503],
+        }
+    }
+}
+
+impl ClientPolicy {
+    /// Returns the delay before the retry that follows the given number of
+    /// failed attempts, where `random` is between `0.0` and `1.0`.
+    #[cfg(any(feature = "browser", feature = "reqwest"))]
+    fn delay(&self, attempts: u32, random: f64) -> Duration {
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter {
+            delay.mul_f64(1.0 - random.clamp(0.0, 1.0) / 2.0)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Sets the [`ClientPolicy`] used by every server function that does not set
+/// its own.
+///
+/// Unlike the server URL, this can be changed at any time, and applies to the
+/// calls made after it has been changed.
+pub fn set_client_policy(policy: ClientPolicy) {
+    *CLIENT_POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+}
+
+/// Returns the [`ClientPolicy`] used by every server function that does not
+/// set its own.
+pub fn get_client_policy() -> ClientPolicy {
+    CLIENT_POLICY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
 /// A client defines a pair of request/response types and the logic to send
 /// and receive them.
 ///
@@ -31,18 +140,467 @@ pub trait Client<CustErr> {
     fn send(
         req: Self::Request,
     ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send;
+
+    /// Sends the request and receives a response, following the timeout and
+    /// retries of the given [`ClientPolicy`].
+    ///
+    /// By default, this ignores the policy and calls [`Client::send`] once.
+    fn send_with_policy(
+        req: Self::Request,
+        policy: ClientPolicy,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>> + Send
+    {
+        _ = policy;
+        Self::send(req)
+    }
+
+    /// Sends the request as part of a [batch](crate::batch), along with the
+    /// other requests that are made at the same time.
+    ///
+    /// By default, this sends the request on its own, following the global
+    /// [`ClientPolicy`].
+    fn send_batched(
+        req: Self::Request,
+    ) -> impl Future<Output = Result<Self::Response, ServerFnError>> + Send
+    where
+        CustErr: Display,
+    {
+        let res = Self::send_with_policy(req, get_client_policy());
+        async move {
+            res.await.map_err(|err| {
+                map_custom_error(err, |err| {
+                    ServerFnError::ServerError(err.to_string())
+                })
+            })
+        }
+    }
+
+    /// The [`Interceptor`]s that apply to every server function called with
+    /// this client, before those of the server function itself.
+    ///
+    /// The built-in clients return the interceptors added with their
+    /// `add_interceptor` functions.
+    fn interceptors() -> Vec<Arc<dyn Interceptor<Self::Request, Self::Response>>>
+    {
+        Vec::new()
+    }
+
+    /// Opens a websocket connection to the server function at `path`, and
+    /// sends it the binary messages in `input`.
+    ///
+    /// Returns the stream of binary messages received from the server. The
+    /// messages in `input` are sent while that stream is being polled.
+    ///
+    /// Clients that do not support websockets return an error.
+    #[cfg(feature = "websocket")]
+    fn open_websocket(
+        path: &str,
+        input: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> impl Future<
+        Output = Result<
+            impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        _ = (path, input);
+        async {
+            Err::<futures::stream::Empty<_>, _>(ServerFnError::Request(
+                "This client does not support websocket connections."
+                    .to_string(),
+            ))
+        }
+    }
+
+    /// Sends a request for a stream of
+    /// [server-sent events](crate::codec::ServerSentEvents), and returns the
+    /// events as they are received.
+    ///
+    /// By default, this sends the request with [`Client::send`] and reads the
+    /// events from the body of the response.
+    fn open_event_stream(
+        req: Self::Request,
+    ) -> impl Future<
+        Output = Result<
+            impl Stream<Item = Result<RawEvent, ServerFnError>> + Send + 'static,
+            ServerFnError<CustErr>,
+        >,
+    > + Send
+    where
+        CustErr: FromStr + Display,
+    {
+        async move {
+            let res = Self::send(req).await?;
+            read_event_stream(res).await
+        }
+    }
+}
+
+/// Intercepts the requests that a client sends to server functions, and the
+/// responses it receives, in the same way that a
+/// [`Layer`](crate::middleware::Layer) wraps a server function on the server.
+///
+/// An interceptor can change the request before passing it on with
+/// [`Next::run`], inspect or replace the response it returns, call
+/// [`Next::run`] again to send the request again, or return a response
+/// without calling it at all.
+///
+/// ```rust,ignore
+/// struct BearerToken;
+///
+/// impl Interceptor<Request, Response> for BearerToken {
+///     fn intercept<'a>(
+///         &'a self,
+///         mut req: Request,
+///         next: Next<'a, Request, Response>,
+///     ) -> Pin<Box<dyn Future<Output = Result<Response, ServerFnError>> + Send + 'a>> {
+///         Box::pin(async move {
+///             let token = format!("Bearer {}", current_token());
+///             req.headers_mut().insert(AUTHORIZATION, token.parse()?);
+///             let retry = req.try_clone();
+///             let res = next.run(req).await?;
+///             match retry {
+///                 // the token has expired, so refresh it and send the
+///                 // request again
+///                 Some(mut retry) if res.status() == StatusCode::UNAUTHORIZED => {
+///                     let token = format!("Bearer {}", refresh_token().await?);
+///                     retry.headers_mut().insert(AUTHORIZATION, token.parse()?);
+///                     next.run(retry).await
+///                 }
+///                 _ => Ok(res),
+///             }
+///         })
+///     }
+/// }
+///
+/// // for every server function called with `reqwest`
+/// server_fn::client::reqwest::add_interceptor(BearerToken);
+/// ```
+///
+/// Interceptors can also be added to a single server function, with the
+/// `#[interceptor(...)]` attribute. They run after the client's own
+/// interceptors, and around the timeout and retries of the
+/// [`ClientPolicy`].
+///
+/// Interceptors do not know the custom error type of a server function, so
+/// they see a custom error as a [`ServerFnError::ServerError`] with its
+/// message. If an interceptor passes that error on, the caller still receives
+/// the custom error.
+pub trait Interceptor<Req, Res>: Send + Sync {
+    /// Handles the request, usually by passing it on to `next`.
+    fn intercept<'a>(
+        &'a self,
+        req: Req,
+        next: Next<'a, Req, Res>,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send + 'a>>;
+}
+
+/// An [`Interceptor`] for the requests and responses of the client `C`.
+pub type ClientInterceptor<C, CustErr> = Arc<
+    dyn Interceptor<
+        <C as Client<CustErr>>::Request,
+        <C as Client<CustErr>>::Response,
+    >,
+>;
+
+type SendFn<'a, Req, Res> = dyn Fn(
+        Req,
+    )
+        -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send + 'a>>
+    + Send
+    + Sync
+    + 'a;
+
+/// The rest of the [`Interceptor`]s that a request goes through, followed by
+/// the client that sends it.
+pub struct Next<'a, Req, Res> {
+    interceptors: &'a [Arc<dyn Interceptor<Req, Res>>],
+    send: &'a SendFn<'a, Req, Res>,
+}
+
+impl<Req, Res> Clone for Next<'_, Req, Res> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Req, Res> Copy for Next<'_, Req, Res> {}
+
+impl<'a, Req, Res> Next<'a, Req, Res> {
+    /// Passes the request to the next interceptor, or sends it if there are
+    /// none left. This can be called more than once.
+    pub fn run(
+        &self,
+        req: Req,
+    ) -> Pin<Box<dyn Future<Output = Result<Res, ServerFnError>> + Send + 'a>>
+    {
+        match self.interceptors.split_first() {
+            Some((interceptor, interceptors)) => interceptor.intercept(
+                req,
+                Next {
+                    interceptors,
+                    send: self.send,
+                },
+            ),
+            None => (self.send)(req),
+        }
+    }
+}
+
+/// Sends the request through the client's interceptors, then through those
+/// of the server function, following the given policy, or as part of a
+/// batch if `batch` is set.
+pub(crate) async fn send_intercepted<C, CustErr>(
+    req: C::Request,
+    policy: ClientPolicy,
+    interceptors: Vec<ClientInterceptor<C, CustErr>>,
+    batch: bool,
+) -> Result<C::Response, ServerFnError<CustErr>>
+where
+    C: Client<CustErr> + ?Sized,
+    CustErr: FromStr + Display,
+{
+    let mut all = C::interceptors();
+    all.extend(interceptors);
+    if all.is_empty() && !batch {
+        return C::send_with_policy(req, policy).await;
+    }
+    // interceptors only see errors without a custom type, so a custom error
+    // is passed through them as its message, and turned back into the custom
+    // error if it comes out of them unchanged
+    let custom = Mutex::new(Vec::new());
+    let send = |req| {
+        if batch {
+            return Box::pin(C::send_batched(req))
+                as Pin<Box<dyn Future<Output = _> + Send>>;
+        }
+        let res = C::send_with_policy(req, policy.clone());
+        let custom = &custom;
+        Box::pin(async move {
+            res.await.map_err(|err| {
+                map_custom_error(err, |err| {
+                    let msg = err.to_string();
+                    custom
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(msg.clone());
+                    ServerFnError::ServerError(msg)
+                })
+            })
+        })
+    };
+    let next = Next {
+        interceptors: &all,
+        send: &send,
+    };
+    let res = next.run(req).await;
+    res.map_err(|err| match err {
+        ServerFnError::ServerError(msg)
+            if custom
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .contains(&msg) =>
+        {
+            match msg.parse() {
+                Ok(err) => ServerFnError::WrappedServerError(err),
+                Err(_) => ServerFnError::ServerError(msg),
+            }
+        }
+        err => map_custom_error(err, |err| {
+            ServerFnError::ServerError(err.to_string())
+        }),
+    })
+}
+
+/// Changes the custom error type of an error, converting the custom error
+/// with `wrapped`.
+fn map_custom_error<A, B>(
+    err: ServerFnError<A>,
+    wrapped: impl FnOnce(A) -> ServerFnError<B>,
+) -> ServerFnError<B> {
+    match err {
+        ServerFnError::WrappedServerError(err) => wrapped(err),
+        ServerFnError::Registration(s) => ServerFnError::Registration(s),
+        ServerFnError::Request(s) => ServerFnError::Request(s),
+        ServerFnError::Response(s) => ServerFnError::Response(s),
+        ServerFnError::ServerError(s) => ServerFnError::ServerError(s),
+        ServerFnError::Deserialization(s) => ServerFnError::Deserialization(s),
+        ServerFnError::Serialization(s) => ServerFnError::Serialization(s),
+        ServerFnError::Args(s) => ServerFnError::Args(s),
+        ServerFnError::MissingArg(s) => ServerFnError::MissingArg(s),
+    }
+}
+
+/// Reads the server-sent events from the body of a response, or the error if
+/// the response has an error status.
+async fn read_event_stream<CustErr>(
+    res: impl ClientRes<CustErr> + Send,
+) -> Result<
+    impl Stream<Item = Result<RawEvent, ServerFnError>> + Send + 'static,
+    ServerFnError<CustErr>,
+>
+where
+    CustErr: FromStr + Display,
+{
+    let status = res.status();
+    if (400..=599).contains(&status) {
+        let text = res.try_into_string().await?;
+        return Err(ServerFnError::de(&text));
+    }
+    Ok(parse_events(res.try_into_stream()?))
+}
+
+/// What [`send_with_retries`] needs from a client, besides sending requests.
+#[cfg(any(feature = "browser", feature = "reqwest"))]
+trait RetryClient<CustErr>: Client<CustErr> {
+    /// Returns the method of the request.
+    fn method(req: &Self::Request) -> http::Method;
+
+    /// Returns the request, along with a copy of it that can be sent once
+    /// the original has been, if its body allows it.
+    fn try_clone(req: Self::Request) -> (Self::Request, Option<Self::Request>);
+
+    /// Waits for the given duration.
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Returns a random number between `0.0` and `1.0`.
+    fn random() -> f64;
+}
+
+/// Sends the request, waiting up to the timeout of the policy for each
+/// attempt, and retrying while the policy allows it.
+#[cfg(any(feature = "browser", feature = "reqwest"))]
+async fn send_with_retries<C, CustErr>(
+    req: C::Request,
+    policy: ClientPolicy,
+) -> Result<C::Response, ServerFnError<CustErr>>
+where
+    C: RetryClient<CustErr>,
+{
+    use futures::future::{select, Either};
+    use http::Method;
+    use std::pin::pin;
+
+    let idempotent = matches!(
+        C::method(&req),
+        Method::GET
+            | Method::HEAD
+            | Method::PUT
+            | Method::DELETE
+            | Method::OPTIONS
+            | Method::TRACE
+    );
+    let mut req = req;
+    let mut attempts = 0;
+    let mut delay = None;
+    loop {
+        if let Some(delay) = delay.take() {
+            C::sleep(delay).await;
+        }
+        attempts += 1;
+        let (current, next) = if attempts <= policy.retries {
+            C::try_clone(req)
+        } else {
+            (req, None)
+        };
+        let send = C::send(current);
+        let res = match policy.timeout {
+            Some(timeout) => {
+                match select(pin!(send), pin!(C::sleep(timeout))).await {
+                    Either::Left((res, _)) => res,
+                    // dropping the request cancels it
+                    Either::Right(_) => Err(ServerFnError::Request(
+                        "The request timed out.".to_string(),
+                    )),
+                }
+            }
+            None => send.await,
+        };
+        let retry = match &res {
+            Ok(res) => policy.retry_statuses.contains(&res.status()),
+            Err(_) => idempotent,
+        };
+        match next {
+            Some(next) if retry => {
+                delay = Some(policy.delay(attempts, C::random()));
+                req = next;
+            }
+            _ => return res,
+        }
+    }
+}
+
+/// Merges the future that sends a websocket's outgoing messages into the
+/// stream of its incoming messages, so that both make progress while the
+/// stream is polled. The stream ends as soon as the incoming messages end.
+#[cfg(all(
+    feature = "websocket",
+    any(feature = "browser", feature = "reqwest")
+))]
+fn with_sender<S>(
+    incoming: S,
+    send: impl Future<Output = ()>,
+) -> impl Stream<Item = S::Item>
+where
+    S: Stream,
+{
+    use futures::{future::ready, stream, StreamExt};
+
+    let send = stream::once(send).filter_map(|_| ready(None));
+    stream::select(incoming.map(Some).chain(stream::once(ready(None))), send)
+        .take_while(|msg| ready(msg.is_some()))
+        .filter_map(ready)
 }
 
 #[cfg(feature = "browser")]
 /// Implements [`Client`] for a `fetch` request in the browser.
 pub mod browser {
-    use super::Client;
+    use super::{
+        get_server_url, read_event_stream, send_with_retries, Client,
+        ClientPolicy, Interceptor, RetryClient,
+    };
     use crate::{
-        error::ServerFnError, request::browser::BrowserRequest,
+        batch::{self, BatchCall, BatchClient, BatchResponse, Queued},
+        codec::{RawEvent, END_EVENT, ERROR_EVENT},
+        error::{NoCustomError, ServerFnError},
+        request::browser::{BrowserRequest, RequestInner},
         response::browser::BrowserResponse,
     };
+    use bytes::Bytes;
+    use futures::{
+        future::ready, stream, stream::BoxStream, Stream, StreamExt,
+    };
+    use gloo_net::{
+        eventsource::futures::EventSource,
+        http::{Headers, Method, Request, RequestBuilder},
+    };
+    use js_sys::Uint8Array;
     use send_wrapper::SendWrapper;
-    use std::future::Future;
+    use std::{
+        fmt::Display,
+        future::Future,
+        str::FromStr,
+        sync::{Arc, Mutex, RwLock},
+        time::Duration,
+    };
+    use url::Url;
+    use wasm_bindgen_futures::JsFuture;
+
+    type BrowserInterceptor =
+        Arc<dyn Interceptor<BrowserRequest, BrowserResponse>>;
+
+    static INTERCEPTORS: RwLock<Vec<BrowserInterceptor>> =
+        RwLock::new(Vec::new());
+
+    /// Adds an [`Interceptor`] to every server function called with the
+    /// [`BrowserClient`]. Interceptors run in the order they were added.
+    pub fn add_interceptor(
+        interceptor: impl Interceptor<BrowserRequest, BrowserResponse> + 'static,
+    ) {
+        INTERCEPTORS
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(interceptor));
+    }
 
     /// Implements [`Client`] for a `fetch` request in the browser.    
     pub struct BrowserClient;
@@ -56,25 +614,335 @@ pub mod browser {
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
             SendWrapper::new(async move {
-                req.0
-                    .take()
+                let RequestInner {
+                    request,
+                    abort_ctrl,
+                } = req.0.take();
+                // the abort guard is kept by the response until its body has
+                // been read, so dropping it early still cancels the request
+                request
                     .send()
                     .await
-                    .map(|res| BrowserResponse(SendWrapper::new(res)))
+                    .map(|res| BrowserResponse::new(res, abort_ctrl))
                     .map_err(|e| ServerFnError::Request(e.to_string()))
             })
         }
+
+        fn send_with_policy(
+            req: Self::Request,
+            policy: ClientPolicy,
+        ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
+               + Send {
+            SendWrapper::new(send_with_retries::<Self, CustErr>(req, policy))
+        }
+
+        fn send_batched(
+            req: Self::Request,
+        ) -> impl Future<Output = Result<Self::Response, ServerFnError>> + Send
+        {
+            SendWrapper::new(batch::send::<Self>(req))
+        }
+
+        fn interceptors() -> Vec<BrowserInterceptor> {
+            INTERCEPTORS
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone()
+        }
+
+        #[cfg(feature = "websocket")]
+        fn open_websocket(
+            path: &str,
+            input: impl Stream<Item = Bytes> + Send + 'static,
+        ) -> impl Future<
+            Output = Result<
+                impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+                ServerFnError<CustErr>,
+            >,
+        > + Send {
+            use super::with_sender;
+            use futures::SinkExt;
+            use gloo_net::websocket::{futures::WebSocket, Message};
+
+            let url = websocket_url(path);
+            SendWrapper::new(async move {
+                let url = url?;
+                let socket = WebSocket::open(&url)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (mut sink, stream) = socket.split();
+                let incoming = stream.filter_map(|msg| {
+                    ready(match msg {
+                        Ok(Message::Bytes(data)) => Some(Ok(Bytes::from(data))),
+                        Ok(Message::Text(_)) => None,
+                        Err(e) => {
+                            Some(Err(ServerFnError::Request(e.to_string())))
+                        }
+                    })
+                });
+                let send = async move {
+                    let mut input = Box::pin(input);
+                    while let Some(data) = input.next().await {
+                        let msg = Message::Bytes(data.into());
+                        if sink.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+                Ok(SendWrapper::new(with_sender(incoming, send)))
+            })
+        }
+
+        fn open_event_stream(
+            req: Self::Request,
+        ) -> impl Future<
+            Output = Result<
+                impl Stream<Item = Result<RawEvent, ServerFnError>> + Send + 'static,
+                ServerFnError<CustErr>,
+            >,
+        > + Send
+        where
+            CustErr: FromStr + Display,
+        {
+            SendWrapper::new(async move {
+                // an `EventSource` can only make `GET` requests
+                if req.0.request.method() != Method::GET {
+                    let res = <Self as Client<CustErr>>::send(req).await?;
+                    let events: BoxStream<'static, _> =
+                        read_event_stream(res).await?.boxed();
+                    return Ok(events);
+                }
+
+                let url = req.0.request.url();
+                let mut source = EventSource::new(&url)
+                    .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let subscriptions = [ERROR_EVENT, END_EVENT, "message"]
+                    .into_iter()
+                    .map(|event| {
+                        source
+                            .subscribe(event)
+                            .map_err(|e| ServerFnError::Request(e.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let events = stream::select_all(subscriptions)
+                    .map(|msg| {
+                        msg.map(|(event, msg)| RawEvent {
+                            event,
+                            id: Some(msg.last_event_id())
+                                .filter(|id| !id.is_empty()),
+                            data: msg.data().as_string().unwrap_or_default(),
+                        })
+                        .map_err(|e| ServerFnError::Request(e.to_string()))
+                    })
+                    // the browser reconnects on its own, so an error means
+                    // the connection has been closed for good. the source
+                    // is kept alive until the stream is dropped, which
+                    // closes it
+                    .scan((source, false), |(_, failed), event| {
+                        if *failed {
+                            return ready(None);
+                        }
+                        *failed = event.is_err();
+                        ready(Some(event))
+                    });
+                Ok(SendWrapper::new(events).boxed())
+            })
+        }
+    }
+
+    impl<CustErr> RetryClient<CustErr> for BrowserClient {
+        fn method(req: &Self::Request) -> Method {
+            req.0.request.method()
+        }
+
+        fn try_clone(
+            req: Self::Request,
+        ) -> (Self::Request, Option<Self::Request>) {
+            req.try_clone()
+        }
+
+        fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+            let ms = duration.as_millis().min(i32::MAX as u128) as i32;
+            let timeout = js_sys::Promise::new(&mut |resolve, _| {
+                let scheduled = web_sys::window().and_then(|window| {
+                    window
+                        .set_timeout_with_callback_and_timeout_and_arguments_0(
+                            &resolve, ms,
+                        )
+                        .ok()
+                });
+                // without a timer, there is nothing to wait for
+                if scheduled.is_none() {
+                    _ = resolve.call0(&resolve);
+                }
+            });
+            SendWrapper::new(async move {
+                _ = JsFuture::from(timeout).await;
+            })
+        }
+
+        fn random() -> f64 {
+            js_sys::Math::random()
+        }
+    }
+
+    static QUEUE: Mutex<Vec<Queued<BrowserClient>>> = Mutex::new(Vec::new());
+
+    impl BatchClient for BrowserClient {
+        fn into_call(
+            req: Self::Request,
+        ) -> impl Future<Output = Result<BatchCall, ServerFnError>> + Send
+        {
+            let request = Request::from(req);
+            SendWrapper::new(async move {
+                let url = Url::parse(&request.url()).map_err(|e| {
+                    ServerFnError::<NoCustomError>::Request(e.to_string())
+                })?;
+                let path = match url.query() {
+                    Some(query) => format!("{}?{query}", url.path()),
+                    None => url.path().to_string(),
+                };
+                let method = request.method();
+                let headers = request.headers().entries().collect();
+                let body = if method == Method::GET || method == Method::HEAD {
+                    Bytes::new()
+                } else {
+                    request.binary().await.map(Bytes::from).map_err(|e| {
+                        ServerFnError::<NoCustomError>::Request(e.to_string())
+                    })?
+                };
+                Ok(BatchCall {
+                    method: method.to_string(),
+                    path,
+                    headers,
+                    body,
+                })
+            })
+        }
+
+        fn from_call(call: BatchCall) -> Result<Self::Request, ServerFnError> {
+            let method =
+                Method::from_bytes(call.method.as_bytes()).map_err(|e| {
+                    ServerFnError::<NoCustomError>::Request(e.to_string())
+                })?;
+            let url = format!("{}{}", get_server_url(), call.path);
+            let headers = Headers::new();
+            for (name, value) in &call.headers {
+                headers.append(name, value);
+            }
+            let builder = RequestBuilder::new(&url)
+                .method(method.clone())
+                .headers(headers);
+            let request = if method == Method::GET || method == Method::HEAD {
+                builder.build()
+            } else {
+                builder.body(Uint8Array::from(call.body.as_ref()))
+            }
+            .map_err(|e| {
+                ServerFnError::<NoCustomError>::Request(e.to_string())
+            })?;
+            Ok(request.into())
+        }
+
+        fn into_response(
+            res: BatchResponse,
+        ) -> Result<Self::Response, ServerFnError> {
+            let headers = Headers::new();
+            for (name, value) in &res.headers {
+                headers.append(name, value);
+            }
+            let mut body = res.body.to_vec();
+            gloo_net::http::Response::builder()
+                .status(res.status)
+                .headers(headers)
+                .body(Some(body.as_mut_slice()))
+                .map(BrowserResponse::from)
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        }
+
+        fn queue() -> &'static Mutex<Vec<Queued<Self>>> {
+            &QUEUE
+        }
+
+        fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
+            // this runs after the tasks that are already queued, which
+            // includes those that will make the other calls in the batch
+            wasm_bindgen_futures::spawn_local(fut);
+        }
+    }
+
+    /// Resolves `path` against the server URL, or against the current page if
+    /// no server URL has been set, using the matching websocket scheme.
+    #[cfg(feature = "websocket")]
+    fn websocket_url<CustErr>(
+        path: &str,
+    ) -> Result<String, ServerFnError<CustErr>> {
+        let root = get_server_url();
+        if let Some(rest) = root.strip_prefix("http") {
+            return Ok(format!("ws{rest}{path}"));
+        }
+        let location = web_sys::window()
+            .ok_or_else(|| {
+                ServerFnError::Request("`window` is not available.".to_string())
+            })?
+            .location();
+        let protocol = location
+            .protocol()
+            .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+        let host = location
+            .host()
+            .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+        let scheme = if protocol == "https:" { "wss:" } else { "ws:" };
+        Ok(format!("{scheme}//{host}{root}{path}"))
     }
 }
 
 #[cfg(feature = "reqwest")]
 /// Implements [`Client`] for a request made by [`reqwest`].
 pub mod reqwest {
-    use super::Client;
-    use crate::{error::ServerFnError, request::reqwest::CLIENT};
+    use super::{
+        get_server_url, send_with_retries, Client, ClientPolicy, Interceptor,
+        RetryClient,
+    };
+    use crate::{
+        batch::{self, BatchCall, BatchClient, BatchResponse, Queued},
+        cache,
+        error::{NoCustomError, ServerFnError},
+        request::reqwest::CLIENT,
+    };
+    use bytes::Bytes;
+    #[cfg(feature = "websocket")]
+    use futures::Stream;
     use futures::TryFutureExt;
-    use reqwest::{Request, Response};
-    use std::future::Future;
+    use once_cell::sync::Lazy;
+    use reqwest::{
+        header::{
+            HeaderMap, HeaderName, HeaderValue, AGE, AUTHORIZATION,
+            CACHE_CONTROL, COOKIE, ETAG, IF_NONE_MATCH, PROXY_AUTHORIZATION,
+            VARY,
+        },
+        Method, Request, Response, ResponseBuilderExt, StatusCode, Url,
+    };
+    use std::{
+        collections::{hash_map::RandomState, HashMap},
+        future::Future,
+        hash::{BuildHasher, Hasher},
+        sync::{Arc, Mutex, RwLock},
+        time::{Duration, Instant},
+    };
+
+    static INTERCEPTORS: RwLock<Vec<Arc<dyn Interceptor<Request, Response>>>> =
+        RwLock::new(Vec::new());
+
+    /// Adds an [`Interceptor`] to every server function called with the
+    /// [`ReqwestClient`]. Interceptors run in the order they were added.
+    pub fn add_interceptor(
+        interceptor: impl Interceptor<Request, Response> + 'static,
+    ) {
+        INTERCEPTORS
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(interceptor));
+    }
 
     /// Implements [`Client`] for a request made by [`reqwest`].
     pub struct ReqwestClient;
@@ -87,9 +955,728 @@ pub mod reqwest {
             req: Self::Request,
         ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
                + Send {
-            CLIENT
-                .execute(req)
+            send_cached(req).map_err(|e| ServerFnError::Request(e.to_string()))
+        }
+
+        fn send_with_policy(
+            req: Self::Request,
+            policy: ClientPolicy,
+        ) -> impl Future<Output = Result<Self::Response, ServerFnError<CustErr>>>
+               + Send {
+            send_with_retries::<Self, CustErr>(req, policy)
+        }
+
+        fn send_batched(
+            req: Self::Request,
+        ) -> impl Future<Output = Result<Self::Response, ServerFnError>> + Send
+        {
+            batch::send::<Self>(req)
+        }
+
+        fn interceptors() -> Vec<Arc<dyn Interceptor<Request, Response>>> {
+            INTERCEPTORS
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone()
+        }
+
+        #[cfg(feature = "websocket")]
+        fn open_websocket(
+            path: &str,
+            input: impl Stream<Item = Bytes> + Send + 'static,
+        ) -> impl Future<
+            Output = Result<
+                impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+                ServerFnError<CustErr>,
+            >,
+        > + Send {
+            use super::with_sender;
+            use futures::{future::ready, SinkExt, StreamExt};
+            use tokio_tungstenite::tungstenite::Message;
+
+            let url = format!("{}{}", get_server_url(), path);
+            async move {
+                // the server URL uses an `http` or `https` scheme
+                let url = match url.strip_prefix("http") {
+                    Some(rest) => format!("ws{rest}"),
+                    None => url,
+                };
+                let (socket, _) =
+                    tokio_tungstenite::connect_async(url)
+                        .await
+                        .map_err(|e| ServerFnError::Request(e.to_string()))?;
+                let (mut sink, stream) = socket.split();
+                let incoming = stream.filter_map(|msg| {
+                    ready(match msg {
+                        Ok(Message::Binary(data)) => {
+                            Some(Ok(Bytes::from(data)))
+                        }
+                        Ok(_) => None,
+                        Err(e) => {
+                            Some(Err(ServerFnError::Request(e.to_string())))
+                        }
+                    })
+                });
+                let send = async move {
+                    let mut input = Box::pin(input);
+                    while let Some(data) = input.next().await {
+                        let msg = Message::Binary(data.into());
+                        if sink.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+                Ok(with_sender(incoming, send))
+            }
+        }
+    }
+    impl<CustErr> RetryClient<CustErr> for ReqwestClient {
+        fn method(req: &Self::Request) -> Method {
+            req.method().clone()
+        }
+
+        fn try_clone(
+            req: Self::Request,
+        ) -> (Self::Request, Option<Self::Request>) {
+            // streaming bodies cannot be copied
+            let copy = req.try_clone();
+            (req, copy)
+        }
+
+        fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+            tokio::time::sleep(duration)
+        }
+
+        fn random() -> f64 {
+            let random = RandomState::new().build_hasher().finish();
+            (random >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    /// The most responses that are stored at the same time.
+    const MAX_CACHED: usize = 256;
+
+    static CACHE: Lazy<Mutex<HashMap<CacheKey, Cached>>> =
+        Lazy::new(Default::default);
+
+    /// The headers that say who sent a request. Responses are only reused for
+    /// requests that send the same values, so that a response for one user is
+    /// never given to another, even when they share a process.
+    const CREDENTIALS: [HeaderName; 3] =
+        [AUTHORIZATION, PROXY_AUTHORIZATION, COOKIE];
+
+    /// What a stored response is looked up by: the URL of the request, and the
+    /// credentials it was sent with.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct CacheKey {
+        url: String,
+        credentials: Vec<(HeaderName, HeaderValue)>,
+    }
+
+    impl CacheKey {
+        fn new(url: &Url, headers: &HeaderMap) -> Self {
+            let credentials = CREDENTIALS
+                .iter()
+                .flat_map(|name| {
+                    headers
+                        .get_all(name)
+                        .iter()
+                        .map(|value| (name.clone(), value.clone()))
+                })
+                .collect();
+            Self {
+                url: url.to_string(),
+                credentials,
+            }
+        }
+    }
+
+    /// A response to a `GET` request that has been stored, as its
+    /// `Cache-Control` header allows.
+    #[derive(Clone)]
+    struct Cached {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: Bytes,
+        expires: Instant,
+        /// The request headers named by the `Vary` header of the response,
+        /// with the values they had, which a request must match to reuse it.
+        varies: Vec<(HeaderName, Option<HeaderValue>)>,
+    }
+
+    impl Cached {
+        /// Whether the response can be reused for a request with the given
+        /// headers.
+        fn matches(&self, headers: &HeaderMap) -> bool {
+            self.varies
+                .iter()
+                .all(|(name, value)| headers.get(name) == value.as_ref())
+        }
+
+        fn to_response(&self, url: Url) -> Response {
+            let mut res = http::Response::builder()
+                .status(self.status)
+                .url(url)
+                .body(self.body.clone())
+                .expect("a stored response is valid");
+            *res.headers_mut() = self.headers.clone();
+            Response::from(res)
+        }
+    }
+
+    /// When a response with the given headers has to be revalidated.
+    fn expires(max_age: Duration, headers: &HeaderMap) -> Instant {
+        // a shared cache may have stored the response for a while already
+        let age = headers
+            .get(AGE)
+            .and_then(|age| age.to_str().ok()?.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        Instant::now() + max_age.saturating_sub(age)
+    }
+
+    /// The request headers named by the `Vary` header of a response, with the
+    /// values they had in the request, or `None` if the response varies on
+    /// something other than headers, so that it cannot be reused.
+    fn varies(
+        res: &HeaderMap,
+        req: &HeaderMap,
+    ) -> Option<Vec<(HeaderName, Option<HeaderValue>)>> {
+        let mut varies = Vec::new();
+        for value in res.get_all(VARY) {
+            for name in value.to_str().ok()?.split(',').map(str::trim) {
+                if name.is_empty() {
+                    continue;
+                }
+                if name == "*" {
+                    return None;
+                }
+                let name = HeaderName::try_from(name).ok()?;
+                let value = req.get(&name).cloned();
+                varies.push((name, value));
+            }
+        }
+        Some(varies)
+    }
+
+    fn store(key: CacheKey, cached: Cached) {
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= MAX_CACHED && !cache.contains_key(&key) {
+            // make room by dropping the response that expires first
+            let first = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.expires)
+                .map(|(key, _)| key.clone());
+            if let Some(first) = first {
+                cache.remove(&first);
+            }
+        }
+        cache.insert(key, cached);
+    }
+
+    /// Sends the request, reusing the response to an earlier `GET` request
+    /// for the same URL for as long as its `Cache-Control` header allows, and
+    /// revalidating it with its `ETag` after that.
+    ///
+    /// The stored responses are shared by everything in the process, so a
+    /// response is only reused for a request with the same credentials and
+    /// the same values of the headers its `Vary` header names, and responses
+    /// marked `private` are not stored at all.
+    async fn send_cached(mut req: Request) -> Result<Response, reqwest::Error> {
+        if req.method() != Method::GET {
+            return CLIENT.execute(req).await;
+        }
+        let url = req.url().clone();
+        let key = CacheKey::new(&url, req.headers());
+        let req_headers = req.headers().clone();
+        let cached = CACHE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&key)
+            .filter(|cached| cached.matches(&req_headers))
+            .cloned();
+        let mut revalidating = false;
+        if let Some(cached) = &cached {
+            if cached.expires > Instant::now() {
+                return Ok(cached.to_response(url));
+            }
+            if let Some(etag) = cached.headers.get(ETAG) {
+                if !req.headers().contains_key(IF_NONE_MATCH) {
+                    req.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+                    revalidating = true;
+                }
+            }
+        }
+
+        let res = CLIENT.execute(req).await?;
+        let cache_control = res
+            .headers()
+            .get(CACHE_CONTROL)
+            .and_then(|value| value.to_str().ok());
+        let max_age = cache_control
+            .filter(|value| !cache::is_private(value))
+            .and_then(cache::max_age);
+        let varies = varies(res.headers(), &req_headers);
+        match (res.status(), cached) {
+            // the stored response is still current
+            (StatusCode::NOT_MODIFIED, Some(mut cached)) if revalidating => {
+                let res = cached.to_response(url);
+                if let Some(max_age) = max_age {
+                    cached.expires = expires(max_age, res.headers());
+                    store(key, cached);
+                }
+                Ok(res)
+            }
+            (StatusCode::OK, _) => match max_age.zip(varies) {
+                Some((max_age, varies)) => {
+                    let status = res.status();
+                    let headers = res.headers().clone();
+                    let cached = Cached {
+                        expires: expires(max_age, &headers),
+                        status,
+                        headers,
+                        body: res.bytes().await?,
+                        varies,
+                    };
+                    let res = cached.to_response(url);
+                    store(key, cached);
+                    Ok(res)
+                }
+                None => Ok(res),
+            },
+            _ => Ok(res),
+        }
+    }
+
+    static QUEUE: Mutex<Vec<Queued<ReqwestClient>>> = Mutex::new(Vec::new());
+
+    impl BatchClient for ReqwestClient {
+        async fn into_call(
+            req: Self::Request,
+        ) -> Result<BatchCall, ServerFnError> {
+            let url = req.url();
+            let path = match url.query() {
+                Some(query) => format!("{}?{query}", url.path()),
+                None => url.path().to_string(),
+            };
+            let headers = req
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            let body = match req.body() {
+                None => Bytes::new(),
+                Some(body) => body
+                    .as_bytes()
+                    .map(Bytes::copy_from_slice)
+                    .ok_or_else(|| {
+                        ServerFnError::<NoCustomError>::Request(
+                        "A request with a streaming body cannot be batched."
+                            .to_string(),
+                    )
+                    })?,
+            };
+            Ok(BatchCall {
+                method: req.method().to_string(),
+                path,
+                headers,
+                body,
+            })
+        }
+
+        fn from_call(call: BatchCall) -> Result<Self::Request, ServerFnError> {
+            let method =
+                Method::from_bytes(call.method.as_bytes()).map_err(|e| {
+                    ServerFnError::<NoCustomError>::Request(e.to_string())
+                })?;
+            let url = format!("{}{}", get_server_url(), call.path);
+            let mut req = CLIENT.request(method, url);
+            for (name, value) in call.headers {
+                req = req.header(name, value);
+            }
+            req.body(call.body)
+                .build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))
         }
+
+        fn into_response(
+            res: BatchResponse,
+        ) -> Result<Self::Response, ServerFnError> {
+            let mut builder = http::Response::builder().status(res.status);
+            for (name, value) in res.headers {
+                builder = builder.header(name, value);
+            }
+            builder
+                .body(res.body)
+                .map(Response::from)
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        }
+
+        fn queue() -> &'static Mutex<Vec<Queued<Self>>> {
+            &QUEUE
+        }
+
+        fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
+            tokio::spawn(async move {
+                // give the tasks that make the other calls in the batch a
+                // chance to queue them
+                tokio::task::yield_now().await;
+                fut.await
+            });
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn headers(pairs: &[(HeaderName, &'static str)]) -> HeaderMap {
+            pairs
+                .iter()
+                .map(|(name, value)| {
+                    (name.clone(), HeaderValue::from_static(value))
+                })
+                .collect()
+        }
+
+        #[test]
+        fn responses_are_kept_apart_by_credentials() {
+            let url = Url::parse("http://localhost/api/todos").unwrap();
+            let anonymous = CacheKey::new(&url, &HeaderMap::new());
+            let alice = CacheKey::new(
+                &url,
+                &headers(&[(AUTHORIZATION, "Bearer alice")]),
+            );
+            let bob =
+                CacheKey::new(&url, &headers(&[(AUTHORIZATION, "Bearer bob")]));
+            let session =
+                CacheKey::new(&url, &headers(&[(COOKIE, "session=alice")]));
+            assert_ne!(anonymous, alice);
+            assert_ne!(alice, bob);
+            assert_ne!(anonymous, session);
+
+            // other headers don't matter
+            let accept = headers(&[
+                (AUTHORIZATION, "Bearer alice"),
+                (http::header::ACCEPT, "application/json"),
+            ]);
+            assert_eq!(CacheKey::new(&url, &accept), alice);
+        }
+
+        #[test]
+        fn responses_are_reused_for_the_same_varying_headers() {
+            let req = headers(&[(http::header::ACCEPT_LANGUAGE, "en")]);
+            let res = headers(&[(VARY, "Accept-Language, X-Theme")]);
+            let cached = Cached {
+                status: StatusCode::OK,
+                headers: HeaderMap::new(),
+                body: Bytes::new(),
+                expires: Instant::now(),
+                varies: varies(&res, &req).unwrap(),
+            };
+            assert!(cached.matches(&req));
+            let other = headers(&[(http::header::ACCEPT_LANGUAGE, "fr")]);
+            assert!(!cached.matches(&other));
+            assert!(!cached.matches(&HeaderMap::new()));
+
+            let res = headers(&[(VARY, "*")]);
+            assert!(varies(&res, &req).is_none());
+            assert_eq!(varies(&HeaderMap::new(), &req), Some(Vec::new()));
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "browser", feature = "reqwest")))]
+mod tests {
+    use super::ClientPolicy;
+    use std::time::Duration;
+
+    fn policy(jitter: bool) -> ClientPolicy {
+        ClientPolicy {
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            jitter,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn delay_doubles_after_each_attempt() {
+        let policy = policy(false);
+        let delays = (1..=4)
+            .map(|attempts| policy.delay(attempts, 0.5))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[test]
+    fn delay_is_capped() {
+        let policy = policy(false);
+        assert_eq!(policy.delay(5, 0.0), Duration::from_secs(1));
+        // does not overflow
+        assert_eq!(policy.delay(u32::MAX, 0.0), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_shortens_the_delay_by_up_to_half() {
+        let policy = policy(true);
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(200));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(150));
+        assert_eq!(policy.delay(2, 1.0), Duration::from_millis(100));
+        // out-of-range random numbers are clamped
+        assert_eq!(policy.delay(2, 7.0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2, -1.0), Duration::from_millis(200));
+    }
+
+    #[cfg(feature = "reqwest")]
+    mod interceptors {
+        use crate::{
+            client::{
+                send_intercepted, Client, ClientInterceptor, ClientPolicy,
+                Interceptor, Next,
+            },
+            ServerFnError,
+        };
+        use futures::{executor::block_on, future::ready, Future};
+        use http::{header::AUTHORIZATION, HeaderValue, StatusCode};
+        use reqwest::{Method, Request, Response, Url};
+        use std::{cell::RefCell, fmt, pin::Pin, str::FromStr, sync::Arc};
+
+        thread_local! {
+            static LOG: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+            static GLOBAL: RefCell<Vec<Arc<dyn Interceptor<Request, Response>>>> =
+                const { RefCell::new(Vec::new()) };
+        }
+
+        fn log(entry: impl Into<String>) {
+            LOG.with(|log| log.borrow_mut().push(entry.into()));
+        }
+
+        fn take_log() -> Vec<String> {
+            LOG.with(|log| log.take())
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Unavailable;
+
+        impl fmt::Display for Unavailable {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("unavailable")
+            }
+        }
+
+        impl FromStr for Unavailable {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                (s == "unavailable").then_some(Unavailable).ok_or(())
+            }
+        }
+
+        /// A client that answers requests itself, rather than sending them.
+        ///
+        /// It fails with a custom error if the request has an `x-fail`
+        /// header, and answers `401 Unauthorized` unless it has a fresh
+        /// token.
+        struct MockClient;
+
+        impl Client<Unavailable> for MockClient {
+            type Request = Request;
+            type Response = Response;
+
+            fn send(
+                req: Request,
+            ) -> impl Future<Output = Result<Response, ServerFnError<Unavailable>>>
+                   + Send {
+                log("send");
+                let res = if req.headers().contains_key("x-fail") {
+                    Err(ServerFnError::WrappedServerError(Unavailable))
+                } else if req.headers().get(AUTHORIZATION)
+                    != Some(&HeaderValue::from_static("Bearer fresh"))
+                {
+                    Ok(response(StatusCode::UNAUTHORIZED, "stale"))
+                } else {
+                    Ok(response(StatusCode::OK, "sent"))
+                };
+                ready(res)
+            }
+
+            fn interceptors() -> Vec<Arc<dyn Interceptor<Request, Response>>> {
+                GLOBAL.with(|global| global.borrow().clone())
+            }
+        }
+
+        fn response(status: StatusCode, body: &'static str) -> Response {
+            http::Response::builder()
+                .status(status)
+                .body(body)
+                .unwrap()
+                .into()
+        }
+
+        fn request() -> Request {
+            let url = Url::parse("http://localhost:3000/api/test").unwrap();
+            let mut req = Request::new(Method::POST, url);
+            req.headers_mut().insert(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer fresh"),
+            );
+            req
+        }
+
+        fn send(
+            req: Request,
+            interceptors: Vec<ClientInterceptor<MockClient, Unavailable>>,
+        ) -> Result<Response, ServerFnError<Unavailable>> {
+            block_on(send_intercepted::<MockClient, _>(
+                req,
+                ClientPolicy::default(),
+                interceptors,
+                false,
+            ))
+        }
+
+        type Intercepted<'a> = Pin<
+            Box<
+                dyn Future<Output = Result<Response, ServerFnError>>
+                    + Send
+                    + 'a,
+            >,
+        >;
+
+        /// Logs the request on its way in and the response on its way out.
+        struct Log(&'static str);
+
+        impl Interceptor<Request, Response> for Log {
+            fn intercept<'a>(
+                &'a self,
+                req: Request,
+                next: Next<'a, Request, Response>,
+            ) -> Intercepted<'a> {
+                log(format!("{} request", self.0));
+                Box::pin(async move {
+                    let res = next.run(req).await;
+                    log(format!("{} response", self.0));
+                    res
+                })
+            }
+        }
+
+        /// Sends the request with a stale token, then refreshes the token and
+        /// sends it again if the server rejects it.
+        struct RefreshToken;
+
+        impl Interceptor<Request, Response> for RefreshToken {
+            fn intercept<'a>(
+                &'a self,
+                mut req: Request,
+                next: Next<'a, Request, Response>,
+            ) -> Intercepted<'a> {
+                Box::pin(async move {
+                    req.headers_mut().insert(
+                        AUTHORIZATION,
+                        HeaderValue::from_static("Bearer stale"),
+                    );
+                    let retry = req.try_clone();
+                    let res = next.run(req).await?;
+                    match retry {
+                        Some(mut retry)
+                            if res.status() == StatusCode::UNAUTHORIZED =>
+                        {
+                            log("refresh");
+                            retry.headers_mut().insert(
+                                AUTHORIZATION,
+                                HeaderValue::from_static("Bearer fresh"),
+                            );
+                            next.run(retry).await
+                        }
+                        _ => Ok(res),
+                    }
+                })
+            }
+        }
+
+        /// Answers every request from a cache, without sending it.
+        struct Cached;
+
+        impl Interceptor<Request, Response> for Cached {
+            fn intercept<'a>(
+                &'a self,
+                _req: Request,
+                _next: Next<'a, Request, Response>,
+            ) -> Intercepted<'a> {
+                Box::pin(ready(Ok(response(StatusCode::OK, "cached"))))
+            }
+        }
+
+        #[test]
+        fn client_interceptors_run_before_those_of_the_server_fn() {
+            GLOBAL.with(|global| {
+                *global.borrow_mut() =
+                    vec![Arc::new(Log("client 1")), Arc::new(Log("client 2"))]
+            });
+            let res = send(request(), vec![Arc::new(Log("server fn"))]);
+            GLOBAL.with(|global| global.borrow_mut().clear());
+
+            assert_eq!(res.unwrap().status(), StatusCode::OK);
+            assert_eq!(
+                take_log(),
+                [
+                    "client 1 request",
+                    "client 2 request",
+                    "server fn request",
+                    "send",
+                    "server fn response",
+                    "client 2 response",
+                    "client 1 response",
+                ]
+            );
+        }
+
+        #[test]
+        fn interceptors_can_send_the_request_again() {
+            let res = send(request(), vec![Arc::new(RefreshToken)]).unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(block_on(res.text()).unwrap(), "sent");
+            assert_eq!(take_log(), ["send", "refresh", "send"]);
+        }
+
+        #[test]
+        fn interceptors_can_respond_without_sending() {
+            let res = send(request(), vec![Arc::new(Cached)]).unwrap();
+            assert_eq!(block_on(res.text()).unwrap(), "cached");
+            assert!(take_log().is_empty());
+        }
+
+        #[test]
+        fn custom_errors_pass_through_interceptors() {
+            let mut req = request();
+            req.headers_mut()
+                .insert("x-fail", HeaderValue::from_static("1"));
+            let err = send(req, vec![Arc::new(Log("server fn"))]).unwrap_err();
+            assert_eq!(err, ServerFnError::WrappedServerError(Unavailable));
+
+            // an error of the interceptor's own is passed on as it is
+            struct Offline;
+
+            impl Interceptor<Request, Response> for Offline {
+                fn intercept<'a>(
+                    &'a self,
+                    _req: Request,
+                    _next: Next<'a, Request, Response>,
+                ) -> Intercepted<'a> {
+                    Box::pin(ready(Err(ServerFnError::Request(
+                        "offline".to_string(),
+                    ))))
+                }
+            }
+            let err = send(request(), vec![Arc::new(Offline)]).unwrap_err();
+            assert_eq!(err, ServerFnError::Request("offline".to_string()));
+        }
     }
 }
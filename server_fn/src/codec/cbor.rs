@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -171,3 +171,21 @@ where
     }
 }
  */
+
+impl<T> MessageEncoding<T> for Cbor
+where
+    T: Serialize + DeserializeOwned,
+{
+    const BINARY: bool = true;
+
+    fn encode(value: &T) -> Result<Bytes, String> {
+        let mut buffer: Vec<u8> = Vec::new();
+        ciborium::ser::into_writer(value, &mut buffer)
+            .map_err(|e| e.to_string())?;
+        Ok(Bytes::from(buffer))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, String> {
+        ciborium::de::from_reader(bytes).map_err(|e| e.to_string())
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, Streaming};
+use super::{Encoding, FromReq, FromRes, MessageEncoding, Streaming};
 use crate::{
     error::{NoCustomError, ServerFnError},
     request::{ClientReq, Req},
@@ -218,3 +218,18 @@ where
         })))
     }
 }
+
+impl<T> MessageEncoding<T> for Json
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<Bytes, String> {
+        serde_json::to_vec(value)
+            .map(Bytes::from)
+            .map_err(|e| e.to_string())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, String> {
+        serde_json::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
@@ -49,11 +49,21 @@ mod msgpack;
 #[cfg(feature = "msgpack")]
 pub use msgpack::*;
 
+#[cfg(feature = "protobuf")]
+mod protobuf;
+#[cfg(feature = "protobuf")]
+pub use protobuf::*;
+
 mod stream;
+#[cfg(feature = "websocket")]
+mod websocket;
 use crate::error::ServerFnError;
+use bytes::Bytes;
 use futures::Future;
 use http::Method;
 pub use stream::*;
+#[cfg(feature = "websocket")]
+pub use websocket::*;
 
 /// Serializes a data type into an HTTP request, on the client.
 ///
@@ -212,3 +222,17 @@ pub trait Encoding {
     /// This should be `POST` in most cases.
     const METHOD: Method;
 }
+
+/// Encodes and decodes individual messages, for protocols like [`Websocket`]
+/// and [`ServerSentEvents`] that send a stream of them.
+pub trait MessageEncoding<T> {
+    /// Whether the encoded messages are arbitrary bytes, rather than UTF-8
+    /// text.
+    const BINARY: bool = false;
+
+    /// Encodes a message into bytes.
+    fn encode(value: &T) -> Result<Bytes, String>;
+
+    /// Decodes a message from bytes.
+    fn decode(bytes: &[u8]) -> Result<T, String>;
+}
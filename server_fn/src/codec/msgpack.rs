@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -72,3 +72,20 @@ where
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 }
+
+impl<T> MessageEncoding<T> for MsgPack
+where
+    T: Serialize + DeserializeOwned,
+{
+    const BINARY: bool = true;
+
+    fn encode(value: &T) -> Result<Bytes, String> {
+        rmp_serde::to_vec(value)
+            .map(Bytes::from)
+            .map_err(|e| e.to_string())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, String> {
+        rmp_serde::from_slice(bytes).map_err(|e| e.to_string())
+    }
+}
@@ -1,12 +1,17 @@
-use super::{Encoding, FromReq};
+use super::{ByteStream, Encoding, FromReq};
 use crate::{
     error::ServerFnError,
     request::{browser::BrowserFormData, ClientReq, Req},
     IntoReq,
 };
-use futures::StreamExt;
+use bytes::Bytes;
+use futures::{future::ready, stream, Stream, StreamExt};
 use http::Method;
 use multer::Multipart;
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
 use web_sys::FormData;
 
 /// Encodes multipart form data.
@@ -82,15 +87,337 @@ where
     CustErr: 'static,
 {
     async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
-        let boundary = req
-            .to_content_type()
-            .and_then(|ct| multer::parse_boundary(ct).ok())
-            .expect("couldn't parse boundary");
-        let stream = req.try_into_stream()?;
-        let data = multer::Multipart::new(
-            stream.map(|data| data.map_err(|e| e.to_string())),
-            boundary,
-        );
+        let data = multipart_from_req(req)?;
         Ok(MultipartData::Server(data).into())
     }
 }
+
+/// Parses the body of a multipart request as it arrives.
+fn multipart_from_req<CustErr, Request>(
+    req: Request,
+) -> Result<Multipart<'static>, ServerFnError<CustErr>>
+where
+    Request: Req<CustErr>,
+{
+    let boundary = req
+        .to_content_type()
+        .and_then(|ct| multer::parse_boundary(ct).ok())
+        .ok_or_else(|| {
+            ServerFnError::Args("couldn't parse boundary".to_string())
+        })?;
+    let stream = req.try_into_stream()?;
+    Ok(Multipart::new(
+        stream.map(|data| data.map_err(|e| e.to_string())),
+        boundary,
+    ))
+}
+
+/// Streams multipart form data in both directions.
+///
+/// Unlike [`MultipartFormData`], this does not need the browser’s `FormData`:
+/// the client builds the body from a list of [`MultipartField`]s, reading the
+/// contents of each one from a [`ByteStream`] as the request is sent, so it
+/// can be used with any client. On the server, the fields are parsed as they
+/// arrive, so large uploads do not need to be held in memory.
+///
+/// A server function that uses this as its input encoding should take a
+/// single [`MultipartStream`] argument.
+///
+/// As with [`Streaming`](super::Streaming), browsers only send streaming
+/// requests over HTTP2 or HTTP3, and do not begin handling the response until
+/// the whole request has been sent.
+pub struct StreamingMultipart;
+
+impl Encoding for StreamingMultipart {
+    const CONTENT_TYPE: &'static str = "multipart/form-data";
+    const METHOD: Method = Method::POST;
+}
+
+/// The fields of a multipart form, streamed from the client to the server.
+#[derive(Debug)]
+pub enum MultipartStream {
+    /// The fields to be sent by the client.
+    Client(Vec<MultipartField>),
+    /// Generic multipart form using [`multer`], which parses the fields as
+    /// they are received. This implements [`Stream`].
+    Server(Multipart<'static>),
+}
+
+impl MultipartStream {
+    /// Extracts the inner data to handle as a stream.
+    ///
+    /// On the server side, this always returns `Some(_)`. On the client side, always returns `None`.
+    pub fn into_inner(self) -> Option<Multipart<'static>> {
+        match self {
+            MultipartStream::Client(_) => None,
+            MultipartStream::Server(data) => Some(data),
+        }
+    }
+}
+
+impl From<Vec<MultipartField>> for MultipartStream {
+    fn from(value: Vec<MultipartField>) -> Self {
+        MultipartStream::Client(value)
+    }
+}
+
+impl FromIterator<MultipartField> for MultipartStream {
+    fn from_iter<I: IntoIterator<Item = MultipartField>>(iter: I) -> Self {
+        MultipartStream::Client(iter.into_iter().collect())
+    }
+}
+
+/// A single field of a [`MultipartStream`], sent by the client.
+#[derive(Debug)]
+pub struct MultipartField {
+    name: String,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    body: ByteStream,
+}
+
+impl MultipartField {
+    /// Creates a text field.
+    pub fn text(name: impl Into<String>, value: impl Into<String>) -> Self {
+        let value: String = value.into();
+        Self {
+            name: name.into(),
+            file_name: None,
+            content_type: None,
+            body: stream::once(ready(value)).into(),
+        }
+    }
+
+    /// Creates a file field, whose contents are read from `body` as the
+    /// request is sent.
+    ///
+    /// Use [`ByteStream::with_progress`] to report the progress of the
+    /// upload.
+    pub fn file(
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        body: impl Into<ByteStream>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            file_name: Some(file_name.into()),
+            content_type: Some(content_type.into()),
+            body: body.into(),
+        }
+    }
+
+    /// The headers that start this field in the body of the request.
+    fn headers(&self, boundary: &str) -> Bytes {
+        let mut headers = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"",
+            escape_field_name(&self.name)
+        );
+        if let Some(file_name) = &self.file_name {
+            headers.push_str("; filename=\"");
+            headers.push_str(&escape_field_name(file_name));
+            headers.push('"');
+        }
+        if let Some(content_type) = &self.content_type {
+            headers.push_str("\r\nContent-Type: ");
+            headers.push_str(content_type);
+        }
+        headers.push_str("\r\n\r\n");
+        headers.into()
+    }
+}
+
+/// Escapes a field name or file name in the way browsers do, so that it
+/// cannot end the header early.
+fn escape_field_name(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Creates a boundary that is very unlikely to appear in the body.
+fn boundary() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("server-fn-boundary-{:016x}{:016x}", random(), random())
+}
+
+/// Builds the body of a multipart request from its fields.
+///
+/// If reading one of the fields fails, the body ends before the final
+/// boundary, so that the server sees an incomplete form rather than a
+/// truncated field.
+fn multipart_body(
+    boundary: String,
+    fields: Vec<MultipartField>,
+) -> impl Stream<Item = Bytes> + Send {
+    let end = Bytes::from(format!("--{boundary}--\r\n"));
+    stream::iter(fields)
+        .flat_map(move |field| {
+            stream::once(ready(Ok(field.headers(&boundary))))
+                .chain(field.body.into_inner())
+                .chain(stream::once(ready(Ok(Bytes::from_static(b"\r\n")))))
+        })
+        .chain(stream::once(ready(Ok(end))))
+        .take_while(|chunk| ready(chunk.is_ok()))
+        .filter_map(|chunk| ready(chunk.ok()))
+}
+
+impl<CustErr, T, Request> IntoReq<StreamingMultipart, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+    T: Into<MultipartStream>,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        let fields = match self.into() {
+            MultipartStream::Client(fields) => fields,
+            MultipartStream::Server(_) => {
+                return Err(ServerFnError::Request(
+                    "A multipart form received by the server cannot be sent \
+                     again."
+                        .to_string(),
+                ))
+            }
+        };
+        let boundary = boundary();
+        let content_type = format!(
+            "{}; boundary={boundary}",
+            StreamingMultipart::CONTENT_TYPE
+        );
+        Request::try_new_streaming(
+            path,
+            accepts,
+            &content_type,
+            multipart_body(boundary, fields),
+        )
+    }
+}
+
+impl<CustErr, T, Request> FromReq<StreamingMultipart, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+    T: From<MultipartStream>,
+    CustErr: 'static,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        let data = multipart_from_req(req)?;
+        Ok(MultipartStream::Server(data).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    /// The name, file name, content type and contents of a parsed field.
+    type Parsed = (String, Option<String>, Option<String>, Bytes);
+
+    fn parse(
+        fields: Vec<MultipartField>,
+    ) -> Result<Vec<Parsed>, multer::Error> {
+        let boundary = boundary();
+        let body = multipart_body(boundary.clone(), fields)
+            .map(Ok::<_, std::convert::Infallible>);
+        let mut multipart = Multipart::new(body, boundary);
+        block_on(async {
+            let mut fields = Vec::new();
+            while let Some(field) = multipart.next_field().await? {
+                let name = field.name().unwrap_or_default().to_string();
+                let file_name = field.file_name().map(ToString::to_string);
+                let content_type =
+                    field.content_type().map(ToString::to_string);
+                fields.push((
+                    name,
+                    file_name,
+                    content_type,
+                    field.bytes().await?,
+                ));
+            }
+            Ok(fields)
+        })
+    }
+
+    #[test]
+    fn fields_are_parsed_back() {
+        let chunks = stream::iter([
+            Ok(Bytes::from_static(b"first chunk, ")),
+            Ok(Bytes::from_static(b"second chunk")),
+        ]);
+        let fields = parse(vec![
+            MultipartField::text("title", "notes"),
+            MultipartField::file(
+                "upload",
+                "notes.txt",
+                "text/plain",
+                ByteStream::new(chunks),
+            ),
+        ])
+        .unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                ("title".into(), None, None, "notes".into()),
+                (
+                    "upload".into(),
+                    Some("notes.txt".into()),
+                    Some("text/plain".into()),
+                    "first chunk, second chunk".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn names_cannot_end_the_header() {
+        let field = MultipartField::text("a\"\r\nb", "value");
+        assert_eq!(
+            field.headers("x"),
+            "--x\r\nContent-Disposition: form-data; \
+             name=\"a%22%0D%0Ab\"\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn body_is_incomplete_if_a_field_fails() {
+        let chunks = stream::iter([
+            Ok(Bytes::from_static(b"partial")),
+            Err(ServerFnError::Request("disk error".into())),
+        ]);
+        let result = parse(vec![
+            MultipartField::text("title", "notes"),
+            MultipartField::file(
+                "upload",
+                "notes.txt",
+                "text/plain",
+                ByteStream::new(chunks),
+            ),
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn progress_counts_the_bytes_read() {
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let chunks = stream::iter([
+            Ok(Bytes::from_static(b"1234")),
+            Ok(Bytes::from_static(b"56")),
+        ]);
+        let body = ByteStream::new(chunks).with_progress({
+            let progress = Arc::clone(&progress);
+            move |read| progress.lock().unwrap().push(read)
+        });
+        parse(vec![MultipartField::file(
+            "upload",
+            "digits.txt",
+            "text/plain",
+            body,
+        )])
+        .unwrap();
+        assert_eq!(*progress.lock().unwrap(), [4, 6]);
+    }
+}
@@ -0,0 +1,90 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding};
+use crate::{
+    error::ServerFnError,
+    request::{ClientReq, Req},
+    response::{ClientRes, Res},
+};
+use bytes::Bytes;
+use http::Method;
+use prost::Message;
+
+/// A codec for [Protocol Buffers](https://protobuf.dev/), using [`prost`].
+///
+/// As an input encoding, the server function must take a single argument that
+/// implements [`Message`], which is sent as the body of the request, so that
+/// the same message types can be shared with gRPC services.
+pub struct Protobuf;
+
+impl Encoding for Protobuf {
+    const CONTENT_TYPE: &'static str = "application/x-protobuf";
+    const METHOD: Method = Method::POST;
+}
+
+impl<T, Request, Err> IntoReq<Protobuf, Request, Err> for T
+where
+    Request: ClientReq<Err>,
+    T: Message,
+{
+    fn into_req(
+        self,
+        path: &str,
+        accepts: &str,
+    ) -> Result<Request, ServerFnError<Err>> {
+        let data = self.encode_to_vec();
+        Request::try_new_post_bytes(
+            path,
+            Protobuf::CONTENT_TYPE,
+            accepts,
+            Bytes::from(data),
+        )
+    }
+}
+
+impl<T, Request, Err> FromReq<Protobuf, Request, Err> for T
+where
+    Request: Req<Err> + Send,
+    T: Message + Default,
+{
+    async fn from_req(req: Request) -> Result<Self, ServerFnError<Err>> {
+        let data = req.try_into_bytes().await?;
+        T::decode(data).map_err(|e| ServerFnError::Args(e.to_string()))
+    }
+}
+
+impl<T, Response, Err> IntoRes<Protobuf, Response, Err> for T
+where
+    Response: Res<Err>,
+    T: Message,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<Err>> {
+        let data = self.encode_to_vec();
+        Response::try_from_bytes(Protobuf::CONTENT_TYPE, Bytes::from(data))
+    }
+}
+
+impl<T, Response, Err> FromRes<Protobuf, Response, Err> for T
+where
+    Response: ClientRes<Err> + Send,
+    T: Message + Default,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<Err>> {
+        let data = res.try_into_bytes().await?;
+        T::decode(data)
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+    }
+}
+
+impl<T> MessageEncoding<T> for Protobuf
+where
+    T: Message + Default,
+{
+    const BINARY: bool = true;
+
+    fn encode(value: &T) -> Result<Bytes, String> {
+        Ok(Bytes::from(value.encode_to_vec()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, String> {
+        T::decode(bytes).map_err(|e| e.to_string())
+    }
+}
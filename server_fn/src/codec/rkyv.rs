@@ -1,4 +1,4 @@
-use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes};
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding};
 use crate::{
     error::ServerFnError,
     request::{ClientReq, Req},
@@ -99,3 +99,26 @@ where
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 }
+
+impl<T> MessageEncoding<T> for Rkyv
+where
+    T: Serialize<AllocSerializer<1024>>,
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<DefaultValidator<'a>>
+        + Deserialize<T, SharedDeserializeMap>,
+{
+    const BINARY: bool = true;
+
+    fn encode(value: &T) -> Result<Bytes, String> {
+        let encoded =
+            rkyv::to_bytes::<T, 1024>(value).map_err(|e| e.to_string())?;
+        Ok(Bytes::copy_from_slice(encoded.as_ref()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, String> {
+        // the archived data has to be aligned before it can be read
+        let mut aligned = AlignedVec::new();
+        aligned.extend_from_slice(bytes);
+        rkyv::from_bytes::<T>(aligned.as_ref()).map_err(|e| e.to_string())
+    }
+}
@@ -1,14 +1,22 @@
-use super::{Encoding, FromReq, FromRes, IntoReq};
+use super::{Encoding, FromReq, FromRes, IntoReq, MessageEncoding};
 use crate::{
-    error::{NoCustomError, ServerFnError},
+    client::Client,
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
     request::{ClientReq, Req},
     response::{ClientRes, Res},
-    IntoRes,
+    IntoRes, ServerFn,
 };
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use bytes::Bytes;
-use futures::{Stream, StreamExt};
+use futures::{future::ready, stream, Stream, StreamExt};
 use http::Method;
-use std::{fmt::Debug, pin::Pin};
+use std::{
+    fmt::{Debug, Display},
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    str::FromStr,
+};
 
 /// An encoding that represents a stream of bytes.
 ///
@@ -32,14 +40,20 @@ impl Encoding for Streaming {
 impl<CustErr, T, Request> IntoReq<Streaming, Request, CustErr> for T
 where
     Request: ClientReq<CustErr>,
-    T: Stream<Item = Bytes> + Send + Sync + 'static,
+    T: Into<ByteStream>,
 {
     fn into_req(
         self,
         path: &str,
         accepts: &str,
     ) -> Result<Request, ServerFnError<CustErr>> {
-        Request::try_new_streaming(path, accepts, Streaming::CONTENT_TYPE, self)
+        let data = self.into();
+        Request::try_new_streaming(
+            path,
+            accepts,
+            Streaming::CONTENT_TYPE,
+            data.into_body(),
+        )
     }
 }
 
@@ -78,6 +92,27 @@ impl<CustErr> ByteStream<CustErr> {
     ) -> impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>> + Send {
         self.0
     }
+
+    /// Calls `on_progress` with the total number of bytes read from the stream
+    /// so far, each time a chunk is read from it.
+    ///
+    /// The body of a request is read as it is sent, so this can be used to
+    /// report the progress of an upload.
+    pub fn with_progress(
+        self,
+        mut on_progress: impl FnMut(u64) + Send + 'static,
+    ) -> Self
+    where
+        CustErr: 'static,
+    {
+        let mut read = 0;
+        Self(Box::pin(self.0.inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                read += chunk.len() as u64;
+                on_progress(read);
+            }
+        })))
+    }
 }
 
 impl<CustErr> Debug for ByteStream<CustErr> {
@@ -96,6 +131,37 @@ impl ByteStream {
     {
         Self(Box::pin(value.map(|value| value.map(Into::into))))
     }
+
+    /// Creates a new `ByteStream` from a browser
+    /// [`ReadableStream`](web_sys::ReadableStream), like the one returned by
+    /// `Blob.stream()` for a file the user has selected.
+    #[cfg(feature = "browser")]
+    pub fn from_readable_stream(stream: web_sys::ReadableStream) -> Self {
+        use js_sys::Uint8Array;
+        use send_wrapper::SendWrapper;
+        use wasm_bindgen::JsCast;
+
+        let stream = wasm_streams::ReadableStream::from_raw(stream)
+            .into_stream()
+            .map(|chunk| {
+                chunk
+                    .map(|data| {
+                        Bytes::from(
+                            data.unchecked_into::<Uint8Array>().to_vec(),
+                        )
+                    })
+                    .map_err(|e| ServerFnError::Request(format!("{e:?}")))
+            });
+        Self(Box::pin(SendWrapper::new(stream)))
+    }
+
+    /// Converts the stream into the body of a request, which ends at the first
+    /// error.
+    pub(crate) fn into_body(self) -> impl Stream<Item = Bytes> + Send {
+        self.0
+            .take_while(|chunk| ready(chunk.is_ok()))
+            .filter_map(|chunk| ready(chunk.ok()))
+    }
 }
 
 impl<S, T> From<S> for ByteStream
@@ -263,3 +329,488 @@ where
         }))))
     }
 }
+
+/// An encoding that sends a stream of typed events as
+/// [server-sent events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events),
+/// which pass through proxies that do not support websockets.
+///
+/// The data of each event is encoded using `E`. Binary encodings like `Cbor`
+/// are base64-encoded, because server-sent events can only carry text.
+///
+/// A server function that uses this as its output encoding should return an
+/// [`EventStream`].
+///
+/// ```rust,ignore
+/// #[server(input = GetUrl, output = ServerSentEvents<Json>)]
+/// pub async fn ticks() -> Result<EventStream<u32>, ServerFnError> {
+///     let ticks = futures::stream::iter(0..10).map(|tick| {
+///         Ok(ServerSentEvent::new(tick).with_id(tick.to_string()))
+///     });
+///     Ok(EventStream::new(ticks))
+/// }
+/// ```
+///
+/// ## Resuming the Stream
+///
+/// In the browser, a server function with a `GET` input encoding (like
+/// `GetUrl`) receives its events through an
+/// [`EventSource`](https://developer.mozilla.org/en-US/docs/Web/API/EventSource).
+/// If the connection drops, it reconnects automatically, and sends the id of
+/// the last event it received in the [`LAST_EVENT_ID`] header, which the server
+/// function can read to resume the stream from that point.
+///
+/// Other clients, and other input encodings, read the events from the body of
+/// an ordinary response, and do not reconnect.
+pub struct ServerSentEvents<E>(PhantomData<E>);
+
+impl<E> Encoding for ServerSentEvents<E> {
+    const CONTENT_TYPE: &'static str = "text/event-stream";
+    const METHOD: Method = Method::GET;
+}
+
+/// The header in which a reconnecting client sends the id of the last
+/// server-sent event it received.
+pub const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// The event type of events that carry an error, rather than data.
+pub(crate) const ERROR_EVENT: &str = "server-fn-error";
+/// The event type of the event sent once the stream has ended, without which
+/// an `EventSource` would reconnect and run the server function again.
+pub(crate) const END_EVENT: &str = "server-fn-end";
+
+/// A single event in an [`EventStream`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSentEvent<T> {
+    /// The id of the event.
+    ///
+    /// On the client, this is the id of the most recent event that had one.
+    pub id: Option<String>,
+    /// The data carried by the event.
+    pub data: T,
+}
+
+impl<T> ServerSentEvent<T> {
+    /// Creates a new event without an id.
+    pub fn new(data: T) -> Self {
+        Self { id: None, data }
+    }
+
+    /// Sets the id of the event, which lets the client resume the stream
+    /// after this event if it reconnects.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+}
+
+/// A stream of typed events.
+///
+/// A server function can return this type if its output encoding is
+/// [`ServerSentEvents`].
+#[allow(clippy::type_complexity)]
+pub struct EventStream<T, CustErr = NoCustomError>(
+    Pin<
+        Box<
+            dyn Stream<
+                    Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>,
+                > + Send,
+        >,
+    >,
+);
+
+impl<T, CustErr> Debug for EventStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EventStream").finish()
+    }
+}
+
+impl<T, CustErr> EventStream<T, CustErr> {
+    /// Creates a new `EventStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning a stream of events.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<ServerSentEvent<T>, ServerFnError<CustErr>>> + Send
+    {
+        self.0
+    }
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for EventStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(|data| Ok(ServerSentEvent::new(data)))))
+    }
+}
+
+/// A server-sent event as received by a [`Client`], before its data has been
+/// decoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RawEvent {
+    /// The type of the event, which is `message` unless the server set another.
+    pub event: String,
+    /// The id of the most recent event that had one.
+    pub id: Option<String>,
+    /// The data carried by the event.
+    pub data: String,
+}
+
+/// Formats a single event in the `text/event-stream` format.
+fn format_event(event: &str, id: Option<&str>, data: &str) -> Bytes {
+    let mut buf = String::new();
+    if let Some(id) = id {
+        // line breaks would end the field early
+        buf.push_str("id: ");
+        buf.extend(id.chars().filter(|c| !matches!(c, '\n' | '\r' | '\0')));
+        buf.push('\n');
+    }
+    if !event.is_empty() {
+        buf.push_str("event: ");
+        buf.push_str(event);
+        buf.push('\n');
+    }
+    // data with line breaks is split across several fields, which the client
+    // joins back together
+    for line in data.split('\n') {
+        buf.push_str("data: ");
+        buf.push_str(line.trim_end_matches('\r'));
+        buf.push('\n');
+    }
+    buf.push('\n');
+    buf.into()
+}
+
+/// Encodes an event, or an error, as a single server-sent event.
+fn encode_event<E, T, CustErr>(
+    event: Result<ServerSentEvent<T>, ServerFnError<CustErr>>,
+) -> Bytes
+where
+    E: MessageEncoding<T>,
+    CustErr: FromStr + Display,
+{
+    let encoded = event.and_then(|event| {
+        let data = E::encode(&event.data)
+            .and_then(|data| {
+                if E::BINARY {
+                    Ok(BASE64.encode(data))
+                } else {
+                    String::from_utf8(data.into()).map_err(|e| e.to_string())
+                }
+            })
+            .map_err(ServerFnError::Serialization)?;
+        Ok((event.id, data))
+    });
+    match encoded {
+        Ok((id, data)) => format_event("", id.as_deref(), &data),
+        Err(err) => {
+            format_event(ERROR_EVENT, None, &err.ser().unwrap_or_default())
+        }
+    }
+}
+
+/// Reverses [`encode_event`].
+fn decode_event<E, T, CustErr>(
+    event: Result<RawEvent, ServerFnError>,
+) -> Result<ServerSentEvent<T>, ServerFnError<CustErr>>
+where
+    E: MessageEncoding<T>,
+    CustErr: FromStr + Display,
+{
+    let RawEvent { event, id, data } = event
+        .map_err(|err| ServerFnError::de(&err.ser().unwrap_or_default()))?;
+    if event == ERROR_EVENT {
+        return Err(ServerFnError::de(&data));
+    }
+    let data = if E::BINARY {
+        BASE64.decode(data).map_err(|e| e.to_string())
+    } else {
+        Ok(data.into_bytes())
+    }
+    .and_then(|data| E::decode(&data))
+    .map_err(ServerFnError::Deserialization)?;
+    Ok(ServerSentEvent { id, data })
+}
+
+/// Decodes a stream of raw events, up to the event that marks its end.
+fn decode_events<E, T, CustErr>(
+    events: impl Stream<Item = Result<RawEvent, ServerFnError>> + Send + 'static,
+) -> EventStream<T, CustErr>
+where
+    E: MessageEncoding<T> + 'static,
+    T: 'static,
+    CustErr: FromStr + Display + 'static,
+{
+    EventStream::new(
+        events
+            .take_while(|event| {
+                ready(!matches!(event, Ok(event) if event.event == END_EVENT))
+            })
+            .map(decode_event::<E, T, CustErr>),
+    )
+}
+
+/// Parses the body of a `text/event-stream` response into the events it
+/// contains.
+pub(crate) fn parse_events(
+    body: impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+) -> impl Stream<Item = Result<RawEvent, ServerFnError>> + Send + 'static {
+    let mut parser = EventParser::default();
+    body.flat_map(move |chunk| {
+        let events = match chunk {
+            Ok(chunk) => parser.feed(&chunk).into_iter().map(Ok).collect(),
+            Err(e) => vec![Err(e)],
+        };
+        stream::iter(events)
+    })
+}
+
+/// Parses the `text/event-stream` format, following the
+/// [specification](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation).
+#[derive(Default)]
+struct EventParser {
+    line: Vec<u8>,
+    /// Whether the last byte was `\r`, which may be followed by `\n` as part
+    /// of the same line break.
+    after_cr: bool,
+    event: String,
+    data: String,
+    last_id: Option<String>,
+}
+
+impl EventParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<RawEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            let after_cr = mem::replace(&mut self.after_cr, byte == b'\r');
+            match byte {
+                b'\n' if after_cr => {}
+                b'\n' | b'\r' => {
+                    let line = mem::take(&mut self.line);
+                    events.extend(self.process_line(&line));
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<RawEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = match line.split_once(':') {
+            // lines that start with a colon are comments
+            Some(("", _)) => return None,
+            Some((field, value)) => {
+                (field, value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line.as_ref(), ""),
+        };
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => {
+                self.last_id =
+                    Some(value.to_string()).filter(|id| !id.is_empty());
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<RawEvent> {
+        let event = mem::take(&mut self.event);
+        let mut data = mem::take(&mut self.data);
+        // an event without any data fields is not dispatched
+        data.pop()?;
+        Some(RawEvent {
+            event: if event.is_empty() {
+                "message".to_string()
+            } else {
+                event
+            },
+            id: self.last_id.clone(),
+            data,
+        })
+    }
+}
+
+impl<E, CustErr, T, Response> IntoRes<ServerSentEvents<E>, Response, CustErr>
+    for EventStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    E: MessageEncoding<T> + 'static,
+    T: Send + 'static,
+    CustErr: FromStr + Display + Send + 'static,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        let events = self
+            .into_inner()
+            .map(|event| Ok(encode_event::<E, T, CustErr>(event)))
+            .chain(stream::once(ready(Ok(format_event(END_EVENT, None, "")))));
+        Response::try_from_stream(ServerSentEvents::<E>::CONTENT_TYPE, events)
+    }
+}
+
+impl<E, CustErr, T, Response> FromRes<ServerSentEvents<E>, Response, CustErr>
+    for EventStream<T, CustErr>
+where
+    Response: ClientRes<CustErr> + Send,
+    E: MessageEncoding<T> + 'static,
+    T: Send + 'static,
+    CustErr: FromStr + Display + Send + 'static,
+{
+    async fn from_res(res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        let events = parse_events(res.try_into_stream()?);
+        Ok(decode_events::<E, T, CustErr>(events))
+    }
+}
+
+impl<E: 'static> ServerSentEvents<E> {
+    /// Sends the request with [`Client::open_event_stream`], which lets the
+    /// client receive the events in its own way, and decodes them.
+    #[doc(hidden)]
+    pub async fn run_client<F, T, CustErr>(
+        req: <F::Client as Client<CustErr>>::Request,
+    ) -> Result<EventStream<T, CustErr>, ServerFnError<CustErr>>
+    where
+        F: ServerFn<Output = EventStream<T, CustErr>, Error = CustErr>,
+        E: MessageEncoding<T>,
+        T: Send + 'static,
+        CustErr: FromStr + Display + Send + 'static,
+    {
+        let events = F::Client::open_event_stream(req).await?;
+        Ok(decode_events::<E, T, CustErr>(events))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&[u8]]) -> Vec<RawEvent> {
+        let mut parser = EventParser::default();
+        chunks.iter().flat_map(|chunk| parser.feed(chunk)).collect()
+    }
+
+    #[test]
+    fn formatted_events_parse_back() {
+        let body = [
+            format_event("", Some("1"), "one"),
+            format_event("tick", None, "two\nlines"),
+            format_event("", Some("bad\nid"), ""),
+        ]
+        .concat();
+        assert_eq!(
+            parse(&[&body]),
+            vec![
+                RawEvent {
+                    event: "message".into(),
+                    id: Some("1".into()),
+                    data: "one".into(),
+                },
+                RawEvent {
+                    event: "tick".into(),
+                    id: Some("1".into()),
+                    data: "two\nlines".into(),
+                },
+                RawEvent {
+                    event: "message".into(),
+                    id: Some("badid".into()),
+                    data: "".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn events_can_be_split_across_chunks() {
+        let body = format_event("", Some("7"), "a\r\nb");
+        let bytes = body.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        assert_eq!(
+            parse(&bytes),
+            vec![RawEvent {
+                event: "message".into(),
+                id: Some("7".into()),
+                data: "a\nb".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn parser_follows_the_specification() {
+        let body = b": a comment\r\n\
+            data\r\n\
+            \r\n\
+            event: ignored\n\
+            \n\
+            id: 2\rdata:no space\r\rid\ndata: x\n\n";
+        // a CRLF split across chunks is a single line break
+        let (head, tail) = body.split_at(13);
+        assert_eq!(
+            parse(&[head, tail]),
+            vec![
+                RawEvent {
+                    event: "message".into(),
+                    id: None,
+                    data: "".into(),
+                },
+                RawEvent {
+                    event: "message".into(),
+                    id: Some("2".into()),
+                    data: "no space".into(),
+                },
+                RawEvent {
+                    event: "message".into(),
+                    id: None,
+                    data: "x".into(),
+                },
+            ]
+        );
+    }
+
+    fn round_trip<E, T>(
+        event: Result<ServerSentEvent<T>, ServerFnError>,
+    ) -> Result<ServerSentEvent<T>, ServerFnError>
+    where
+        E: MessageEncoding<T>,
+    {
+        let mut events = parse(&[&encode_event::<E, T, _>(event)]);
+        assert_eq!(events.len(), 1);
+        decode_event::<E, T, _>(Ok(events.remove(0)))
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_events_round_trip() {
+        use crate::codec::Json;
+
+        let event =
+            ServerSentEvent::new("multi\nline".to_string()).with_id("3");
+        assert_eq!(round_trip::<Json, _>(Ok(event.clone())), Ok(event));
+        let err = ServerFnError::ServerError("oops".into());
+        assert_eq!(round_trip::<Json, String>(Err(err.clone())), Err(err));
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn binary_events_round_trip() {
+        use crate::codec::Cbor;
+
+        let event = ServerSentEvent::new(vec![0u8, 10, 13, 255]);
+        assert_eq!(round_trip::<Cbor, _>(Ok(event.clone())), Ok(event));
+    }
+}
@@ -0,0 +1,353 @@
+use super::{Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding};
+use crate::{
+    client::Client,
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    request::{ClientReq, Req, WebsocketReq},
+    response::{ClientRes, Res},
+    ServerFn,
+};
+use bytes::Bytes;
+use futures::{future::ready, stream, Stream, StreamExt};
+use http::Method;
+use std::{
+    fmt::{Debug, Display},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    str::FromStr,
+};
+
+/// A protocol that opens a websocket connection to the server function, over
+/// which the client sends a stream of input messages and receives a stream of
+/// output messages.
+///
+/// Each message is encoded on its own, using `E`. A server function that uses
+/// this protocol should take a single [`WebsocketStream`] argument and return a
+/// [`WebsocketStream`], and should use it as both its input and output encoding.
+///
+/// ```rust,ignore
+/// #[server(input = Websocket<Json>, output = Websocket<Json>)]
+/// pub async fn uppercase(
+///     input: WebsocketStream<String>,
+/// ) -> Result<WebsocketStream<String>, ServerFnError> {
+///     let output = input.into_inner().map(|msg| msg.map(|msg| msg.to_uppercase()));
+///     Ok(WebsocketStream::new(output))
+/// }
+///
+/// // on the client, the output can be read as soon as each input has been sent
+/// let input = futures::stream::iter(["hello".to_string(), "world".to_string()]);
+/// let mut output = uppercase(input.into()).await?.into_inner();
+/// while let Some(msg) = output.next().await {
+///     println!("{}", msg?);
+/// }
+/// ```
+///
+/// The connection stays open until the stream returned by the server function
+/// ends.
+///
+/// This requires the `websocket` feature, which also adds websocket support
+/// to the server integrations and to the `reqwest` client.
+pub struct Websocket<E>(PhantomData<E>);
+
+impl<E: Encoding> Encoding for Websocket<E> {
+    const CONTENT_TYPE: &'static str = E::CONTENT_TYPE;
+    const METHOD: Method = Method::GET;
+}
+
+/// A stream of typed messages, sent or received over a [`Websocket`].
+pub struct WebsocketStream<T, CustErr = NoCustomError>(
+    Pin<Box<dyn Stream<Item = Result<T, ServerFnError<CustErr>>> + Send>>,
+);
+
+impl<T, CustErr> Debug for WebsocketStream<T, CustErr> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("WebsocketStream").finish()
+    }
+}
+
+impl<T, CustErr> WebsocketStream<T, CustErr> {
+    /// Creates a new `WebsocketStream` from the given stream.
+    pub fn new(
+        value: impl Stream<Item = Result<T, ServerFnError<CustErr>>>
+            + Send
+            + 'static,
+    ) -> Self {
+        Self(Box::pin(value))
+    }
+
+    /// Consumes the wrapper, returning a stream of messages.
+    pub fn into_inner(
+        self,
+    ) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>> + Send {
+        self.0
+    }
+}
+
+impl<S, T: 'static, CustErr: 'static> From<S> for WebsocketStream<T, CustErr>
+where
+    S: Stream<Item = T> + Send + 'static,
+{
+    fn from(value: S) -> Self {
+        Self(Box::pin(value.map(Ok)))
+    }
+}
+
+const MESSAGE: u8 = 0;
+const ERROR: u8 = 1;
+/// Sent by the client once its input has ended, because the connection needs
+/// to stay open for the rest of the output.
+const END: u8 = 2;
+
+/// Frames a message, or an error, as a single binary websocket message.
+fn encode_message<E, T, CustErr>(
+    value: Result<T, ServerFnError<CustErr>>,
+) -> Bytes
+where
+    E: MessageEncoding<T>,
+    CustErr: FromStr + Display,
+{
+    let err = match value.and_then(|value| {
+        E::encode(&value).map_err(ServerFnError::Serialization)
+    }) {
+        Ok(data) => return [&[MESSAGE], data.as_ref()].concat().into(),
+        Err(err) => err,
+    };
+    let err = err.ser().unwrap_or_default();
+    [&[ERROR], err.as_bytes()].concat().into()
+}
+
+/// Reverses [`encode_message`].
+fn decode_message<E, T, CustErr>(
+    data: Result<Bytes, ServerFnError>,
+) -> Result<T, ServerFnError<CustErr>>
+where
+    E: MessageEncoding<T>,
+    CustErr: FromStr + Display,
+{
+    let data =
+        data.map_err(|err| ServerFnError::de(&err.ser().unwrap_or_default()))?;
+    match data.split_first() {
+        Some((&MESSAGE, data)) => {
+            E::decode(data).map_err(ServerFnError::Deserialization)
+        }
+        Some((&ERROR, data)) => {
+            Err(ServerFnError::de(&String::from_utf8_lossy(data)))
+        }
+        _ => Err(ServerFnError::Deserialization(
+            "Invalid websocket message.".to_string(),
+        )),
+    }
+}
+
+/// Reads the messages received from the client, until it says its input has
+/// ended, or the connection fails.
+fn read_input<E, T, CustErr>(
+    incoming: impl Stream<Item = Result<Bytes, ServerFnError>>,
+) -> impl Stream<Item = Result<T, ServerFnError<CustErr>>>
+where
+    E: MessageEncoding<T>,
+    CustErr: FromStr + Display,
+{
+    let mut failed = false;
+    incoming
+        .take_while(move |data| {
+            let is_end = matches!(data, Ok(data) if data.as_ref() == [END]);
+            let keep = !failed && !is_end;
+            failed |= data.is_err();
+            ready(keep)
+        })
+        .map(decode_message::<E, T, CustErr>)
+}
+
+impl<E: 'static> Websocket<E> {
+    /// Upgrades the request to a websocket connection, and runs the server
+    /// function on the stream of messages received over it.
+    #[doc(hidden)]
+    pub async fn run_server<F, I, O, CustErr>(
+        req: F::ServerRequest,
+    ) -> Result<F::ServerResponse, ServerFnError<CustErr>>
+    where
+        F: ServerFn<Output = WebsocketStream<O, CustErr>, Error = CustErr>
+            + From<WebsocketStream<I, CustErr>>,
+        F::ServerRequest:
+            WebsocketReq<CustErr, WebsocketResponse = F::ServerResponse>,
+        CustErr: FromStr + Display + 'static,
+        E: MessageEncoding<I> + MessageEncoding<O>,
+        I: Send + 'static,
+        O: Send + 'static,
+    {
+        let (incoming, outgoing, res) = req.try_into_websocket().await?;
+        let input = read_input::<E, I, CustErr>(incoming);
+        let output = F::from(WebsocketStream::new(input)).run_body().await?;
+        let output = output.into_inner().map(encode_message::<E, O, CustErr>);
+        // if the connection is never established, there is nothing to send
+        // the output to
+        _ = outgoing.send(Box::pin(output));
+        Ok(res)
+    }
+
+    /// Opens a websocket connection to the server function, and sends it the
+    /// stream of input messages.
+    #[doc(hidden)]
+    pub fn run_client<F, I, O, CustErr>(
+        input: F,
+    ) -> impl Future<
+        Output = Result<WebsocketStream<O, CustErr>, ServerFnError<CustErr>>,
+    > + Send
+    where
+        F: ServerFn<Output = WebsocketStream<O, CustErr>, Error = CustErr>
+            + Into<WebsocketStream<I, CustErr>>,
+        CustErr: FromStr + Display + 'static,
+        E: MessageEncoding<I> + MessageEncoding<O>,
+        I: 'static,
+        O: 'static,
+    {
+        let input: WebsocketStream<I, CustErr> = input.into();
+        let input = input
+            .into_inner()
+            .map(encode_message::<E, I, CustErr>)
+            .chain(stream::once(ready(Bytes::from_static(&[END]))));
+        async move {
+            let incoming = F::Client::open_websocket(F::PATH, input).await?;
+            let output = incoming.map(decode_message::<E, O, CustErr>);
+            Ok(WebsocketStream::new(output))
+        }
+    }
+}
+
+// Server functions that use `Websocket` are run by `Websocket::run_server` and
+// `Websocket::run_client`, which replace the usual request/response cycle.
+// These implementations only exist to satisfy the bounds on `ServerFn`.
+
+fn not_a_websocket<CustErr>() -> ServerFnError<CustErr> {
+    ServerFnError::Request(
+        "Websocket server functions can only be called over a websocket \
+         connection."
+            .to_string(),
+    )
+}
+
+impl<E, CustErr, T, Request> IntoReq<Websocket<E>, Request, CustErr> for T
+where
+    Request: ClientReq<CustErr>,
+{
+    fn into_req(
+        self,
+        _path: &str,
+        _accepts: &str,
+    ) -> Result<Request, ServerFnError<CustErr>> {
+        Err(not_a_websocket())
+    }
+}
+
+impl<E, CustErr, T, Request> FromReq<Websocket<E>, Request, CustErr> for T
+where
+    Request: Req<CustErr> + Send + 'static,
+{
+    async fn from_req(_req: Request) -> Result<Self, ServerFnError<CustErr>> {
+        Err(not_a_websocket())
+    }
+}
+
+impl<E, CustErr, T, Response> IntoRes<Websocket<E>, Response, CustErr>
+    for WebsocketStream<T, CustErr>
+where
+    Response: Res<CustErr>,
+    T: Send,
+    CustErr: Send,
+{
+    async fn into_res(self) -> Result<Response, ServerFnError<CustErr>> {
+        Err(not_a_websocket())
+    }
+}
+
+impl<E, CustErr, T, Response> FromRes<Websocket<E>, Response, CustErr>
+    for WebsocketStream<T, CustErr>
+where
+    Response: ClientRes<CustErr> + Send,
+    T: Send,
+    CustErr: Send,
+{
+    async fn from_res(_res: Response) -> Result<Self, ServerFnError<CustErr>> {
+        Err(not_a_websocket())
+    }
+}
+
+#[cfg(all(test, feature = "json"))]
+mod tests {
+    use super::*;
+    use crate::codec::Json;
+    use futures::executor::block_on;
+
+    type Result<T> = std::result::Result<T, ServerFnError>;
+
+    fn decode(data: Bytes) -> Result<String> {
+        decode_message::<Json, String, NoCustomError>(Ok(data))
+    }
+
+    fn frame(msg: &str) -> Result<Bytes> {
+        Ok(encode_message::<Json, _, NoCustomError>(
+            Ok(msg.to_string()),
+        ))
+    }
+
+    fn read(incoming: Vec<Result<Bytes>>) -> Vec<Result<String>> {
+        let input =
+            read_input::<Json, String, NoCustomError>(stream::iter(incoming));
+        block_on(input.collect())
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let data = frame("hello").unwrap();
+        assert_eq!(data[0], MESSAGE);
+        assert_eq!(decode(data).unwrap(), "hello");
+    }
+
+    #[test]
+    fn errors_round_trip() {
+        let data = encode_message::<Json, String, NoCustomError>(Err(
+            ServerFnError::ServerError("oops".to_string()),
+        ));
+        assert_eq!(data[0], ERROR);
+        assert_eq!(
+            decode(data).unwrap_err(),
+            ServerFnError::ServerError("oops".to_string())
+        );
+
+        // an error from the connection itself is passed on
+        let err = decode_message::<Json, String, NoCustomError>(Err(
+            ServerFnError::Request("closed".to_string()),
+        ))
+        .unwrap_err();
+        assert_eq!(err, ServerFnError::Request("closed".to_string()));
+    }
+
+    #[test]
+    fn invalid_frames_are_rejected() {
+        for data in [&[][..], &[END], &[7, b'1']] {
+            let err = decode(Bytes::copy_from_slice(data)).unwrap_err();
+            assert!(matches!(err, ServerFnError::Deserialization(_)), "{err}");
+        }
+        let err = decode(Bytes::from_static(&[MESSAGE, b'{'])).unwrap_err();
+        assert!(matches!(err, ServerFnError::Deserialization(_)), "{err}");
+    }
+
+    #[test]
+    fn input_ends_at_the_end_frame() {
+        let input = read(vec![
+            frame("a"),
+            frame("b"),
+            Ok(Bytes::from_static(&[END])),
+            frame("c"),
+        ]);
+        assert_eq!(input, vec![Ok("a".to_string()), Ok("b".to_string())]);
+    }
+
+    #[test]
+    fn input_ends_after_an_error() {
+        let closed = ServerFnError::Request("closed".to_string());
+        let input = read(vec![frame("a"), Err(closed.clone()), frame("b")]);
+        assert_eq!(input, vec![Ok("a".to_string()), Err(closed)]);
+    }
+}
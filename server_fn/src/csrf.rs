@@ -0,0 +1,526 @@
+//! Protection against cross-site request forgery (CSRF) for server functions
+//! that can be called by submitting an HTML form.
+//!
+//! With the `form-redirects` feature, a server function accepts a plain HTML
+//! form that is submitted without JavaScript. Without any other protection, a
+//! form on another site could submit to it too, with the cookies of a user who
+//! visits that site. The `csrf` feature guards against this:
+//!
+//! 1. A request with a body that an HTML form could have sent is rejected if
+//!    its `Origin` header, or its `Referer` if there is no `Origin`, is not the
+//!    origin the request was sent to, or one allowed with [`trust_origin`].
+//!    Other requests cannot be sent from another site unless the server
+//!    allows it with CORS, so they are not checked.
+//! 2. A form that is submitted without JavaScript must also include a valid
+//!    token in the hidden [`CSRF_FIELD`], which `<ActionForm/>` and
+//!    `<MultiActionForm/>` add when they are rendered on the server. Each
+//!    token is tied to a random [`ClientSecret`], which the server integration
+//!    gives to the browser in the [`CSRF_COOKIE`], so a token taken from one
+//!    browser is rejected when it is sent by another. Tokens are signed with
+//!    a secret that only the server knows, and expire after a day.
+//!
+//! A request that fails either check is answered with `403 Forbidden`, without
+//! running the server function.
+//!
+//! The token is read from forms that are URL-encoded, the default for
+//! `<form>`, and from `multipart/form-data` forms if the `multipart` feature
+//! is enabled. Other forms, like those with `enctype="text/plain"`, can only
+//! be submitted with JavaScript.
+
+/// The name of the hidden form field that carries the CSRF token.
+pub const CSRF_FIELD: &str = "csrf_token";
+
+/// The name of the cookie that carries the [`ClientSecret`].
+pub const CSRF_COOKIE: &str = "csrf_secret";
+
+/// Whether the `csrf` feature is enabled, so that forms should include a
+/// [`CSRF_FIELD`].
+pub const ENABLED: bool = cfg!(feature = "csrf");
+
+/// A random secret that belongs to one client, which the CSRF tokens of the
+/// forms rendered for it are tied to.
+///
+/// The server integrations take it from the [`CSRF_COOKIE`] of each request
+/// for a page, and provide it as context for the forms on that page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSecret {
+    secret: String,
+    is_new: bool,
+}
+
+impl ClientSecret {
+    /// Takes the secret from the [`CSRF_COOKIE`] in the given `Cookie`
+    /// header, or creates a new one if there is none.
+    ///
+    /// Returns `None` in the browser, or if the `csrf` feature is disabled.
+    pub fn from_cookies(cookies: &str) -> Option<Self> {
+        #[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+        {
+            Some(match server::cookie(cookies, CSRF_COOKIE) {
+                Some(secret) => Self {
+                    secret: secret.to_string(),
+                    is_new: false,
+                },
+                None => Self {
+                    secret: server::new_secret(),
+                    is_new: true,
+                },
+            })
+        }
+        #[cfg(not(all(feature = "csrf", not(target_arch = "wasm32"))))]
+        {
+            _ = cookies;
+            None
+        }
+    }
+
+    /// The value of a `Set-Cookie` header that gives the secret to the
+    /// client, if it did not have one yet.
+    pub fn set_cookie(&self) -> Option<String> {
+        self.is_new.then(|| {
+            format!(
+                "{CSRF_COOKIE}={}; Path=/; HttpOnly; SameSite=Lax",
+                self.secret
+            )
+        })
+    }
+
+    /// Creates a new token for the hidden [`CSRF_FIELD`] of a form.
+    ///
+    /// Returns `None` in the browser, or if the `csrf` feature is disabled.
+    pub fn form_token(&self) -> Option<String> {
+        #[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+        {
+            Some(server::new_token(&self.secret))
+        }
+        #[cfg(not(all(feature = "csrf", not(target_arch = "wasm32"))))]
+        {
+            None
+        }
+    }
+}
+
+#[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+pub(crate) use server::check;
+// batches are only handled by the Axum integration
+#[cfg(all(
+    feature = "csrf",
+    feature = "axum-no-default",
+    not(target_arch = "wasm32")
+))]
+pub(crate) use server::is_allowed_origin;
+#[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+pub use server::{set_secret, trust_origin};
+
+#[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+mod server {
+    use super::{CSRF_COOKIE, CSRF_FIELD};
+    use crate::{error::ServerFnError, request::Req};
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use once_cell::sync::Lazy;
+    use ring::{
+        hmac,
+        rand::{SecureRandom, SystemRandom},
+    };
+    use std::{
+        sync::{PoisonError, RwLock},
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+    use url::Url;
+
+    /// How long a token stays valid after it is created.
+    const TOKEN_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+    static KEY: Lazy<RwLock<hmac::Key>> = Lazy::new(|| {
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+            .expect("could not generate a key for CSRF tokens");
+        RwLock::new(key)
+    });
+
+    static TRUSTED_ORIGINS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+    /// Sets the secret that CSRF tokens are signed with.
+    ///
+    /// By default, each server process signs tokens with a random secret of
+    /// its own, so a form rendered by one process is rejected by another. An
+    /// app that is served by more than one process should set the same secret
+    /// in each of them before it serves any requests.
+    pub fn set_secret(secret: &[u8]) {
+        *KEY.write().unwrap_or_else(PoisonError::into_inner) =
+            hmac::Key::new(hmac::HMAC_SHA256, secret);
+    }
+
+    /// Allows requests from the given origin, like `https://example.com`.
+    ///
+    /// Requests from the origin they are sent to, as given by their `Host`
+    /// header, are always allowed. This is only needed for other origins, or
+    /// if a proxy changes the `Host` header.
+    pub fn trust_origin(origin: &str) -> Result<(), url::ParseError> {
+        let origin = Url::parse(origin)?.origin().ascii_serialization();
+        TRUSTED_ORIGINS
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(origin);
+        Ok(())
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn random() -> String {
+        let mut bytes = [0; 16];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .expect("could not generate a CSRF token");
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub(super) fn new_secret() -> String {
+        random()
+    }
+
+    /// Returns the value of the cookie called `name` in a `Cookie` header.
+    pub(super) fn cookie<'a>(cookies: &'a str, name: &str) -> Option<&'a str> {
+        cookies.split(';').find_map(|cookie| {
+            let (key, value) = cookie.split_once('=')?;
+            let value = value.trim();
+            (key.trim() == name && !value.is_empty()).then_some(value)
+        })
+    }
+
+    /// The message that is signed for a token, which ties it to the secret of
+    /// the client it was created for.
+    fn signed_message(payload: &str, secret: &str) -> String {
+        format!("{payload}.{secret}")
+    }
+
+    pub(super) fn new_token(secret: &str) -> String {
+        let expires = now() + TOKEN_MAX_AGE.as_secs();
+        let payload = format!("{expires}.{}", random());
+        let key = KEY.read().unwrap_or_else(PoisonError::into_inner);
+        let tag = hmac::sign(&key, signed_message(&payload, secret).as_bytes());
+        format!("{payload}.{}", URL_SAFE_NO_PAD.encode(tag))
+    }
+
+    fn is_valid_token(token: &str, secret: &str) -> bool {
+        let Some((payload, tag)) = token.rsplit_once('.') else {
+            return false;
+        };
+        let Ok(tag) = URL_SAFE_NO_PAD.decode(tag) else {
+            return false;
+        };
+        let key = KEY.read().unwrap_or_else(PoisonError::into_inner);
+        let message = signed_message(payload, secret);
+        if hmac::verify(&key, message.as_bytes(), &tag).is_err() {
+            return false;
+        }
+        payload
+            .split_once('.')
+            .and_then(|(expires, _)| expires.parse::<u64>().ok())
+            .is_some_and(|expires| now() <= expires)
+    }
+
+    /// Whether the request was sent from an origin that is allowed to call
+    /// server functions. Requests that do not say where they were sent from,
+    /// like those from clients other than browsers, are allowed.
+    pub(crate) fn is_allowed_origin<CustErr>(req: &impl Req<CustErr>) -> bool {
+        let Some(origin) = req.header("Origin").or_else(|| req.referer())
+        else {
+            return true;
+        };
+        // an opaque origin, like `null`, is never allowed
+        let Ok(url) = Url::parse(&origin) else {
+            return false;
+        };
+        let serialized = url.origin().ascii_serialization();
+        let trusted = TRUSTED_ORIGINS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&serialized);
+        if trusted {
+            return true;
+        }
+        let authority = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return false,
+        };
+        req.header("Host")
+            .is_some_and(|host| host.eq_ignore_ascii_case(&authority))
+    }
+
+    /// Reads the token from the body of a multipart form.
+    #[cfg(feature = "multipart")]
+    async fn multipart_token(
+        content_type: &str,
+        body: bytes::Bytes,
+    ) -> Option<String> {
+        let boundary = multer::parse_boundary(content_type).ok()?;
+        let body = futures::stream::once(async move {
+            Ok::<_, std::convert::Infallible>(body)
+        });
+        let mut multipart = multer::Multipart::new(body, boundary);
+        while let Ok(Some(field)) = multipart.next_field().await {
+            if field.name() == Some(CSRF_FIELD) {
+                return field.text().await.ok();
+            }
+        }
+        None
+    }
+
+    /// Rejects a request that might have been forged by another site, with a
+    /// message that says why.
+    pub(crate) async fn check<CustErr, Request>(
+        req: Request,
+    ) -> Result<Request, String>
+    where
+        Request: Req<CustErr>,
+    {
+        let content_type = req
+            .to_content_type()
+            .map(|ty| ty.into_owned())
+            .unwrap_or_default();
+        let lowercase = content_type.to_ascii_lowercase();
+        let urlencoded =
+            lowercase.starts_with("application/x-www-form-urlencoded");
+        let multipart = lowercase.starts_with("multipart/form-data");
+        let from_form =
+            urlencoded || multipart || lowercase.starts_with("text/plain");
+        if !from_form {
+            return Ok(req);
+        }
+
+        if !is_allowed_origin(&req) {
+            return Err("The request was sent from another site.".to_string());
+        }
+
+        // a form that is submitted without JavaScript must carry a token too
+        let accepts_html = req
+            .accepts()
+            .is_some_and(|accepts| accepts.contains("text/html"));
+        if !accepts_html {
+            return Ok(req);
+        }
+        let missing_token = || {
+            format!(
+                "The form is missing a valid `{CSRF_FIELD}`. Reload the page \
+                 and try again."
+            )
+        };
+        let readable = urlencoded || (multipart && cfg!(feature = "multipart"));
+        if !readable {
+            return Err(format!(
+                "Forms sent as `{}` can only be submitted with \
+                 JavaScript.",
+                lowercase.split(';').next().unwrap_or_default().trim()
+            ));
+        }
+        let Some(secret) = req.header("Cookie").and_then(|cookies| {
+            cookie(&cookies, CSRF_COOKIE).map(str::to_string)
+        }) else {
+            return Err(missing_token());
+        };
+        let (body, req) = req.try_buffer_body().await.map_err(
+            |_: ServerFnError<CustErr>| {
+                "The request body could not be read.".to_string()
+            },
+        )?;
+        let token = if urlencoded {
+            url::form_urlencoded::parse(&body)
+                .find(|(name, _)| name == CSRF_FIELD)
+                .map(|(_, token)| token.into_owned())
+        } else {
+            #[cfg(feature = "multipart")]
+            {
+                multipart_token(&content_type, body).await
+            }
+            #[cfg(not(feature = "multipart"))]
+            {
+                None
+            }
+        };
+        if token.is_some_and(|token| is_valid_token(&token, &secret)) {
+            Ok(req)
+        } else {
+            Err(missing_token())
+        }
+    }
+
+    #[cfg(all(test, feature = "axum-no-default"))]
+    mod tests {
+        use super::*;
+        use crate::{csrf::ClientSecret, error::NoCustomError};
+        use axum::body::Body;
+        use futures::executor::block_on;
+        use http::{request::Builder, Request};
+        use http_body_util::BodyExt;
+
+        const SECRET: &str = "client-secret";
+
+        fn request(content_type: &str, origin: Option<&str>) -> Builder {
+            let req = Request::post("/api/add_todo")
+                .header("Host", "example.com")
+                .header("Content-Type", content_type);
+            match origin {
+                Some(origin) => req.header("Origin", origin),
+                None => req,
+            }
+        }
+
+        fn run(
+            req: Builder,
+            body: impl Into<Body>,
+        ) -> Result<Request<Body>, String> {
+            let req = req.body(body.into()).unwrap();
+            block_on(check::<NoCustomError, _>(req))
+        }
+
+        fn urlencoded(origin: Option<&str>) -> Result<Request<Body>, String> {
+            let req = request("application/x-www-form-urlencoded", origin);
+            run(req, "title=Buy+milk")
+        }
+
+        /// Submits a form without JavaScript, with the given token, and the
+        /// given secret in its cookies.
+        fn html_form(
+            token: &str,
+            secret: Option<&str>,
+        ) -> Result<Request<Body>, String> {
+            let mut req = request(
+                "application/x-www-form-urlencoded",
+                Some("https://example.com"),
+            )
+            .header("Accept", "text/html");
+            if let Some(secret) = secret {
+                let cookies = format!("theme=dark; {CSRF_COOKIE}={secret}");
+                req = req.header("Cookie", cookies);
+            }
+            run(req, format!("title=Buy+milk&{CSRF_FIELD}={token}"))
+        }
+
+        #[test]
+        fn forms_from_the_same_origin_are_allowed() {
+            assert!(urlencoded(Some("https://example.com")).is_ok());
+            // clients other than browsers do not say where they are from
+            assert!(urlencoded(None).is_ok());
+            let req = request("application/x-www-form-urlencoded", None)
+                .header("Referer", "https://example.com/todos?page=2");
+            assert!(run(req, "title=Buy+milk").is_ok());
+        }
+
+        #[test]
+        fn forms_from_other_origins_are_rejected() {
+            for origin in [
+                "https://evil.example",
+                "https://example.com:8443",
+                "http://example.com.evil.example",
+                "null",
+            ] {
+                assert!(urlencoded(Some(origin)).is_err(), "{origin}");
+            }
+            let req = request("application/x-www-form-urlencoded", None)
+                .header("Referer", "https://evil.example/");
+            assert!(run(req, "title=Buy+milk").is_err());
+        }
+
+        #[test]
+        fn trusted_origins_are_allowed() {
+            let origin = "https://trusted.example";
+            assert!(urlencoded(Some(origin)).is_err());
+            trust_origin("https://trusted.example/some/path").unwrap();
+            assert!(urlencoded(Some(origin)).is_ok());
+        }
+
+        #[test]
+        fn bodies_that_forms_cannot_send_are_not_checked() {
+            let req = request("application/json", Some("https://evil.example"));
+            assert!(run(req, "{}").is_ok());
+        }
+
+        #[test]
+        fn forms_without_javascript_need_a_token() {
+            let req = html_form(&new_token(SECRET), Some(SECRET)).unwrap();
+            // the body can still be read by the server function
+            let body = block_on(req.into_body().collect()).unwrap().to_bytes();
+            assert!(body.starts_with(b"title=Buy+milk&"));
+
+            assert!(html_form("", Some(SECRET)).is_err());
+            assert!(html_form("1.2.3", Some(SECRET)).is_err());
+        }
+
+        #[test]
+        fn tokens_are_tied_to_the_client_secret() {
+            let token = new_token(SECRET);
+            assert!(html_form(&token, None).is_err());
+            assert!(html_form(&token, Some("another-secret")).is_err());
+
+            // the expiry time is signed too
+            let (expires, rest) = token.split_once('.').unwrap();
+            let later = expires.parse::<u64>().unwrap() + 1;
+            assert!(
+                html_form(&format!("{later}.{rest}"), Some(SECRET)).is_err()
+            );
+        }
+
+        #[test]
+        fn client_secrets_are_kept_in_a_cookie() {
+            let secret = ClientSecret::from_cookies("").unwrap();
+            let set_cookie = secret.set_cookie().unwrap();
+            let (cookie, attrs) = set_cookie.split_once(';').unwrap();
+            assert!(attrs.contains("HttpOnly"));
+
+            let sent =
+                ClientSecret::from_cookies(&format!("a=b; {cookie}")).unwrap();
+            assert_eq!(
+                sent,
+                ClientSecret {
+                    is_new: false,
+                    ..secret
+                }
+            );
+            assert_eq!(sent.set_cookie(), None);
+
+            let (_, value) = cookie.split_once('=').unwrap();
+            assert!(html_form(&sent.form_token().unwrap(), Some(value)).is_ok());
+        }
+
+        #[test]
+        fn other_forms_need_javascript() {
+            let req = request("text/plain", Some("https://example.com"))
+                .header("Accept", "text/html")
+                .header("Cookie", format!("{CSRF_COOKIE}={SECRET}"));
+            let body = format!("{CSRF_FIELD}={}", new_token(SECRET));
+            let err = run(req, body).unwrap_err();
+            assert!(err.contains("text/plain"), "{err}");
+        }
+
+        #[cfg(feature = "multipart")]
+        #[test]
+        fn tokens_are_read_from_multipart_forms() {
+            let multipart = |token: &str| {
+                let req = request(
+                    "multipart/form-data; boundary=XYZ",
+                    Some("https://example.com"),
+                )
+                .header("Accept", "text/html")
+                .header("Cookie", format!("{CSRF_COOKIE}={SECRET}"));
+                let body = format!(
+                    "--XYZ\r\n\
+                     Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+                     Buy milk\r\n\
+                     --XYZ\r\n\
+                     Content-Disposition: form-data; name=\"{CSRF_FIELD}\"\r\n\r\n\
+                     {token}\r\n\
+                     --XYZ--\r\n"
+                );
+                run(req, body)
+            };
+            assert!(multipart(&new_token(SECRET)).is_ok());
+            assert!(multipart(&new_token("another-secret")).is_err());
+        }
+    }
+}
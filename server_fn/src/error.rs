@@ -1,3 +1,8 @@
+use crate::{
+    codec::{Encoding, MessageEncoding},
+    response::{ClientRes, Res},
+};
+use http::StatusCode;
 use serde::{Deserialize, Serialize};
 use std::{
     error, fmt,
@@ -75,7 +80,8 @@ impl From<ServerFnError> for Error {
 )]
 #[cfg_attr(
     feature = "rkyv",
-    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
 )]
 pub struct NoCustomError;
 
@@ -94,6 +100,8 @@ impl FromStr for NoCustomError {
     }
 }
 
+impl ErrorStatus for NoCustomError {}
+
 /// Wraps some error type, which may implement any of [`Error`](trait@std::error::Error), [`Clone`], or
 /// [`Display`].
 #[derive(Debug)]
@@ -181,7 +189,8 @@ impl<E> ViaError<E> for WrapError<E> {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(
     feature = "rkyv",
-    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
 )]
 pub enum ServerFnError<E = NoCustomError> {
     /// A user-defined custom error type, which defaults to [`NoCustomError`].
@@ -340,6 +349,96 @@ where
     }
 }
 
+/// Maps a custom error type to the HTTP status code of the response that
+/// carries it, for server functions that send [typed errors](typed_error_response).
+///
+/// ```rust,ignore
+/// #[derive(Debug, Clone, Serialize, Deserialize)]
+/// pub enum TodoError {
+///     NotFound(u32),
+///     Forbidden,
+/// }
+///
+/// impl ErrorStatus for TodoError {
+///     fn status(&self) -> StatusCode {
+///         match self {
+///             TodoError::NotFound(_) => StatusCode::NOT_FOUND,
+///             TodoError::Forbidden => StatusCode::FORBIDDEN,
+///         }
+///     }
+/// }
+/// ```
+pub trait ErrorStatus {
+    /// The status code of the response. Defaults to `500 Internal Server Error`.
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl<E: ErrorStatus> ErrorStatus for ServerFnError<E> {
+    fn status(&self) -> StatusCode {
+        match self {
+            ServerFnError::WrappedServerError(e) => e.status(),
+            ServerFnError::Args(_) | ServerFnError::MissingArg(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Converts an error into a response whose body is the whole [`ServerFnError`],
+/// encoded with `Enc`, and whose status code comes from [`ErrorStatus`].
+///
+/// Unlike [`ServerFnErrorSerde`], this keeps the structure of the custom error
+/// type, as long as it can be encoded with `Enc`. This is used by server
+/// functions with `#[server(typed_errors = true)]`, which encode their errors
+/// with their output encoding.
+pub fn typed_error_response<Enc, CustErr, Response>(
+    path: &str,
+    err: &ServerFnError<CustErr>,
+) -> Response
+where
+    Enc: Encoding + MessageEncoding<ServerFnError<CustErr>>,
+    CustErr: ErrorStatus,
+    Response: Res<CustErr>,
+{
+    match Enc::encode(err) {
+        Ok(data) => Response::encoded_error_response(
+            path,
+            err.status(),
+            Enc::CONTENT_TYPE,
+            data,
+        ),
+        Err(e) => Response::error_response(
+            path,
+            &ServerFnError::Serialization(format!(
+                "Could not serialize error: {e}"
+            )),
+        ),
+    }
+}
+
+/// Reads an error sent by [`typed_error_response`] from the response.
+///
+/// Errors that were not sent by the server function itself, such as those
+/// returned by middleware, are read with [`ServerFnErrorSerde`] instead.
+pub async fn typed_error_from_res<Enc, CustErr, Response>(
+    res: Response,
+) -> ServerFnError<CustErr>
+where
+    Enc: MessageEncoding<ServerFnError<CustErr>>,
+    CustErr: FromStr + Display,
+    Response: ClientRes<CustErr>,
+{
+    match res.try_into_bytes().await {
+        Ok(data) => Enc::decode(&data).unwrap_or_else(|_| {
+            ServerFnError::de(&String::from_utf8_lossy(&data))
+        }),
+        Err(err) => err,
+    }
+}
+
 impl<E> std::error::Error for ServerFnError<E>
 where
     E: std::error::Error + 'static,
@@ -96,17 +96,23 @@
 //! [`serde_qs`]: <https://docs.rs/serde_qs/latest/serde_qs/>
 //! [`cbor`]: <https://docs.rs/cbor/latest/cbor/>
 
+pub mod batch;
+pub mod cache;
 /// Implementations of the client side of the server function call.
 pub mod client;
 
 /// Encodings for arguments and results.
 pub mod codec;
 
+pub mod csrf;
+
 #[macro_use]
 /// Error types and utilities.
 pub mod error;
 /// Types to add server middleware to a server function.
 pub mod middleware;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 /// Utilities to allow client-side redirects.
 pub mod redirect;
 /// Types and traits for  for HTTP requests.
@@ -133,7 +139,7 @@ use http::Method;
 use middleware::{Layer, Service};
 use once_cell::sync::Lazy;
 use redirect::RedirectHook;
-use request::Req;
+use request::{ClientReq, Req};
 use response::{ClientRes, Res};
 #[cfg(feature = "rkyv")]
 pub use rkyv;
@@ -142,9 +148,15 @@ pub use serde;
 #[doc(hidden)]
 #[cfg(feature = "serde-lite")]
 pub use serde_lite;
+/// Derives [`Schema`](openapi::Schema), which describes a type in the OpenAPI
+/// document. This does nothing unless the `openapi` feature is enabled.
+pub use server_fn_macro_default::Schema;
 use std::{fmt::Display, future::Future, pin::Pin, str::FromStr, sync::Arc};
 #[doc(hidden)]
 pub use xxhash_rust;
+#[doc(hidden)]
+#[cfg(feature = "protobuf")]
+pub use prost;
 
 /// Defines a function that runs only on the server, but can be called from the server or the client.
 ///
@@ -188,6 +200,13 @@ where
     /// A unique path for the server function’s API endpoint, relative to the host, including its prefix.
     const PATH: &'static str;
 
+    /// Whether calls to this server function can be sent as part of a
+    /// [batch](batch), once batching has been turned on.
+    ///
+    /// The `#[server]` macro sets this unless the server function streams its
+    /// arguments or its output.
+    const BATCHABLE: bool = false;
+
     /// The type of the HTTP client that will send the request from the client side.
     ///
     /// For example, this might be `gloo-net` in the browser, or `reqwest` for a desktop app.
@@ -225,6 +244,51 @@ where
         Self::PATH
     }
 
+    /// The timeout and retry policy the client follows when it calls this
+    /// server function.
+    ///
+    /// Defaults to the policy set with [`client::set_client_policy`].
+    fn client_policy() -> client::ClientPolicy {
+        client::get_client_policy()
+    }
+
+    /// [`Interceptor`](client::Interceptor)s that the client should apply to
+    /// calls to this server function.
+    fn client_interceptors(
+    ) -> Vec<client::ClientInterceptor<Self::Client, Self::Error>> {
+        Vec::new()
+    }
+
+    /// Converts an error returned by the server function into a response.
+    ///
+    /// By default, this serializes the error with [`ServerFnErrorSerde`].
+    fn error_response(
+        err: &ServerFnError<Self::Error>,
+    ) -> Self::ServerResponse {
+        Self::ServerResponse::error_response(Self::PATH, err)
+    }
+
+    /// Reads an error from a response with an error status code, on the
+    /// client.
+    ///
+    /// By default, this deserializes the error with [`ServerFnErrorSerde`].
+    fn error_from_res(
+        res: <Self::Client as Client<Self::Error>>::Response,
+    ) -> impl Future<Output = ServerFnError<Self::Error>> + Send {
+        async move {
+            match res.try_into_string().await {
+                Ok(text) => ServerFnError::de(&text),
+                Err(err) => err,
+            }
+        }
+    }
+
+    /// How the responses of this server function may be cached. This only
+    /// applies if its input encoding uses `GET`.
+    fn cache_policy() -> Option<cache::CachePolicy> {
+        None
+    }
+
     /// Middleware that should be applied to this server function.
     fn middlewares(
     ) -> Vec<Arc<dyn Layer<Self::ServerRequest, Self::ServerResponse>>> {
@@ -252,17 +316,30 @@ where
         let mut referer = req.referer().as_deref().map(ToOwned::to_owned);
 
         async move {
+            // reject requests that might have been forged by another site
+            #[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+            let req = csrf::check(req).await;
+            #[cfg(not(all(feature = "csrf", not(target_arch = "wasm32"))))]
+            let req = Ok::<_, String>(req);
+
             #[allow(unused_variables, unused_mut)]
             // used in form redirects feature
-            let (mut res, err) = Self::execute_on_server(req)
-                .await
-                .map(|res| (res, None))
-                .unwrap_or_else(|e| {
-                    (
-                        Self::ServerResponse::error_response(Self::PATH, &e),
-                        Some(e),
-                    )
-                });
+            let (mut res, err) = match req {
+                Ok(req) => Self::execute_on_server(req)
+                    .await
+                    .map(|res| (res, None))
+                    .unwrap_or_else(|e| (Self::error_response(&e), Some(e))),
+                Err(msg) => {
+                    let e = ServerFnError::ServerError(msg);
+                    let res = Self::ServerResponse::encoded_error_response(
+                        Self::PATH,
+                        http::StatusCode::FORBIDDEN,
+                        "text/plain",
+                        e.ser().unwrap_or_default().into(),
+                    );
+                    (res, Some(e))
+                }
+            };
 
             // if it accepts HTML, we'll redirect to the Referer
             #[cfg(feature = "form-redirects")]
@@ -303,6 +380,23 @@ where
         }
     }
 
+    /// Like `run_on_client`, but sets an additional header on the request.
+    #[doc(hidden)]
+    fn run_on_client_with_header(
+        self,
+        name: &'static str,
+        value: String,
+    ) -> impl Future<Output = Result<Self::Output, ServerFnError<Self::Error>>> + Send
+    {
+        async move {
+            let req = self
+                .into_req(Self::PATH, Self::OutputEncoding::CONTENT_TYPE)?
+                .try_with_header(name, &value)?;
+            Self::run_on_client_with_req(req, redirect::REDIRECT_HOOK.get())
+                .await
+        }
+    }
+
     #[doc(hidden)]
     fn run_on_client_with_req(
         req: <Self::Client as Client<Self::Error>>::Request,
@@ -310,16 +404,21 @@ where
     ) -> impl Future<Output = Result<Self::Output, ServerFnError<Self::Error>>> + Send
     {
         async move {
-            let res = Self::Client::send(req).await?;
+            let res = client::send_intercepted::<Self::Client, _>(
+                req,
+                Self::client_policy(),
+                Self::client_interceptors(),
+                Self::BATCHABLE && batch::is_batching(),
+            )
+            .await?;
 
             let status = res.status();
             let location = res.location();
             let has_redirect_header = res.has_redirect();
 
-            // if it returns an error status, deserialize the error using FromStr
+            // if it returns an error status, deserialize the error
             let res = if (400..=599).contains(&status) {
-                let text = res.try_into_string().await?;
-                Err(ServerFnError::<Self::Error>::de(&text))
+                Err(Self::error_from_res(res).await)
             } else {
                 // otherwise, deserialize the body as is
                 Ok(Self::Output::from_res(res).await)
@@ -343,10 +442,24 @@ where
         Output = Result<Self::ServerResponse, ServerFnError<Self::Error>>,
     > + Send {
         async {
+            // only the responses to `GET` requests can be cached
+            let cache = Self::cache_policy()
+                .filter(|_| Self::InputEncoding::METHOD == Method::GET);
+            let if_none_match = cache
+                .as_ref()
+                .and_then(|_| req.header("If-None-Match"))
+                .map(|value| value.into_owned());
+
             let this = Self::from_req(req).await?;
             let output = this.run_body().await?;
             let res = output.into_res().await?;
-            Ok(res)
+            match cache {
+                Some(cache) => {
+                    res.cache(&cache.header_value(), if_none_match.as_deref())
+                        .await
+                }
+                None => Ok(res),
+            }
         }
     }
 }
@@ -368,6 +481,43 @@ macro_rules! initialize_server_fn_map {
     };
 }
 
+/// Registers a server function for the OpenAPI document.
+#[cfg(feature = "openapi")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_openapi {
+    ($spec:expr) => {
+        $crate::inventory::submit! { $spec }
+    };
+}
+
+/// Does nothing, because the `openapi` feature is disabled.
+#[cfg(not(feature = "openapi"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_openapi {
+    ($spec:expr) => {};
+}
+
+/// Implements [`Schema`](openapi::Schema), for `#[derive(Schema)]`.
+#[cfg(feature = "openapi")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_schema {
+    ($($impl:tt)*) => {
+        $($impl)*
+    };
+}
+
+/// Does nothing, because the `openapi` feature is disabled, so that types can
+/// derive `Schema` in code that is shared with the client.
+#[cfg(not(feature = "openapi"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_schema {
+    ($($impl:tt)*) => {};
+}
+
 /// A list of middlewares that can be applied to a server function.
 pub type MiddlewareSet<Req, Res> = Vec<Arc<dyn Layer<Req, Res>>>;
 
@@ -444,6 +594,35 @@ impl<Req, Res> Clone for ServerFnTraitObj<Req, Res> {
 type LazyServerFnMap<Req, Res> =
     Lazy<DashMap<&'static str, ServerFnTraitObj<Req, Res>>>;
 
+#[allow(unused)] // used by server integrations
+type LazyMiddlewareMap<Req, Res> =
+    Lazy<DashMap<&'static str, MiddlewareSet<Req, Res>>>;
+
+/// Wraps a server function in its middleware.
+///
+/// The middleware of each server function is created the first time it is
+/// called, and kept for later calls, so that middleware with state, like a
+/// rate limit, keeps it from one request to the next.
+#[allow(unused)] // used by server integrations
+fn layered_service<Req, Res>(
+    server_fn: &ServerFnTraitObj<Req, Res>,
+    middlewares: &LazyMiddlewareMap<Req, Res>,
+) -> middleware::BoxedService<Req, Res>
+where
+    Req: Send + 'static,
+    Res: 'static,
+{
+    let middleware = middlewares
+        .entry(server_fn.path())
+        .or_insert_with(|| server_fn.middleware())
+        .clone();
+    let mut service = middleware::BoxedService::new(server_fn.clone());
+    for middleware in middleware {
+        service = middleware.layer(service);
+    }
+    service
+}
+
 #[cfg(feature = "ssr")]
 impl<Req: 'static, Res: 'static> inventory::Collect
     for ServerFnTraitObj<Req, Res>
@@ -459,17 +638,36 @@ impl<Req: 'static, Res: 'static> inventory::Collect
 #[cfg(feature = "axum-no-default")]
 pub mod axum {
     use crate::{
+        batch::{
+            BatchCall, BatchResponse, BATCH_CALL_HEADERS, BATCH_PATH,
+            MAX_BATCH_CALLS, MAX_BATCH_SIZE,
+        },
+        error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+        layered_service,
         middleware::{BoxedService, Service},
-        Encoding, LazyServerFnMap, ServerFn, ServerFnTraitObj,
+        Encoding, LazyMiddlewareMap, LazyServerFnMap, ServerFn,
+        ServerFnTraitObj,
     };
     use axum::body::Body;
-    use http::{Method, Request, Response, StatusCode};
+    use futures::{
+        future::{join_all, ready},
+        Future,
+    };
+    use http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE, SET_COOKIE},
+        HeaderName, HeaderValue, Method, Request, Response, StatusCode,
+    };
+    use http_body_util::{BodyExt, LengthLimitError, Limited};
+    use std::pin::Pin;
 
     static REGISTERED_SERVER_FUNCTIONS: LazyServerFnMap<
         Request<Body>,
         Response<Body>,
     > = initialize_server_fn_map!(Request<Body>, Response<Body>);
 
+    static MIDDLEWARES: LazyMiddlewareMap<Request<Body>, Response<Body>> =
+        once_cell::sync::Lazy::new(Default::default);
+
     /// Explicitly register a server function. This is only necessary if you are
     /// running the server in a WASM environment (or a rare environment that the
     /// `inventory` crate won't work in.).
@@ -480,6 +678,7 @@ pub mod axum {
                 ServerResponse = Response<Body>,
             > + 'static,
     {
+        MIDDLEWARES.remove(T::PATH);
         REGISTERED_SERVER_FUNCTIONS.insert(
             T::PATH,
             ServerFnTraitObj::new(
@@ -500,6 +699,14 @@ pub mod axum {
 
     /// An Axum handler that responds to a server function request.
     pub async fn handle_server_fn(req: Request<Body>) -> Response<Body> {
+        if req.uri().path() == BATCH_PATH {
+            return handle_batch(req, |req| Box::pin(handle_single(req))).await;
+        }
+        handle_single(req).await
+    }
+
+    /// Responds to a request for a single server function.
+    async fn handle_single(req: Request<Body>) -> Response<Body> {
         let path = req.uri().path();
 
         if let Some(mut service) = get_server_fn_service(path) {
@@ -521,18 +728,199 @@ pub mod axum {
         }
     }
 
+    /// Responds to a [batch](crate::batch) of server function calls, running
+    /// each of them with `handler` as if it had been sent on its own.
+    ///
+    /// Each call is sent with the headers and extensions of the batch, along
+    /// with those of its own headers that are in [`BATCH_CALL_HEADERS`].
+    /// Cookies set by any of the calls are set on the response to the batch.
+    ///
+    /// Batches that are not sent as `application/json` are rejected with
+    /// `415 Unsupported Media Type`, and, with the `csrf` feature, those sent
+    /// from another site are rejected with `403 Forbidden`. Batches with more
+    /// than [`MAX_BATCH_CALLS`] calls, or whose body is longer than
+    /// [`MAX_BATCH_SIZE`] bytes, are rejected with `413 Payload Too Large`.
+    pub async fn handle_batch<F>(
+        req: Request<Body>,
+        handler: F,
+    ) -> Response<Body>
+    where
+        F: Fn(
+            Request<Body>,
+        ) -> Pin<Box<dyn Future<Output = Response<Body>> + Send>>,
+    {
+        let error_response = |status, err: ServerFnError| {
+            Response::builder()
+                .status(status)
+                .body(Body::from(err.ser().unwrap_or_default()))
+                .unwrap()
+        };
+        let too_large = || {
+            error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ServerFnError::Args(format!(
+                    "A batch can hold at most {MAX_BATCH_CALLS} calls, and \
+                     {MAX_BATCH_SIZE} bytes."
+                )),
+            )
+        };
+
+        // a form cannot be sent as JSON, so this also keeps other sites from
+        // sending a batch without the browser asking the server first
+        let is_json = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ty| ty.to_str().ok())
+            .and_then(|ty| ty.split(';').next())
+            .is_some_and(|ty| {
+                ty.trim().eq_ignore_ascii_case("application/json")
+            });
+        if !is_json {
+            return error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                ServerFnError::Request(
+                    "A batch must be sent as `application/json`.".to_string(),
+                ),
+            );
+        }
+        #[cfg(all(feature = "csrf", not(target_arch = "wasm32")))]
+        if !crate::csrf::is_allowed_origin::<NoCustomError>(&req) {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                ServerFnError::ServerError(
+                    "The request was sent from another site.".to_string(),
+                ),
+            );
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = match Limited::new(body, MAX_BATCH_SIZE).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(e) if e.is::<LengthLimitError>() => return too_large(),
+            Err(e) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    ServerFnError::Deserialization(e.to_string()),
+                )
+            }
+        };
+        let calls = match serde_json::from_slice::<Vec<BatchCall>>(&body) {
+            Ok(calls) if calls.len() > MAX_BATCH_CALLS => return too_large(),
+            Ok(calls) => calls,
+            Err(e) => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    ServerFnError::Args(e.to_string()),
+                )
+            }
+        };
+
+        // the handler is not borrowed across the await, so it doesn't need to
+        // be `Sync`
+        let responses = join_all(calls.into_iter().map(|call| {
+            match batched_request(&parts, call) {
+                Ok(req) => handler(req),
+                Err(err) => Box::pin(ready(
+                    Response::builder()
+                        .status(StatusCode::BAD_REQUEST)
+                        .body(Body::from(err.ser().unwrap_or_default()))
+                        .unwrap(),
+                )),
+            }
+        }))
+        .await;
+
+        let mut res =
+            Response::builder().header(CONTENT_TYPE, "application/json");
+        let mut batch = Vec::with_capacity(responses.len());
+        for response in responses {
+            let (mut parts, body) = response.into_parts();
+            for cookie in parts.headers.get_all(SET_COOKIE) {
+                res = res.header(SET_COOKIE, cookie);
+            }
+            parts.headers.remove(SET_COOKIE);
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(e) => {
+                    parts.status = StatusCode::INTERNAL_SERVER_ERROR;
+                    ServerFnError::<NoCustomError>::Response(e.to_string())
+                        .ser()
+                        .unwrap_or_default()
+                        .into()
+                }
+            };
+            batch.push(BatchResponse {
+                status: parts.status.as_u16(),
+                headers: parts
+                    .headers
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        Some((
+                            name.to_string(),
+                            value.to_str().ok()?.to_string(),
+                        ))
+                    })
+                    .collect(),
+                body,
+            });
+        }
+        match serde_json::to_string(&batch) {
+            Ok(body) => res.body(Body::from(body)).unwrap(),
+            Err(e) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from(
+                    ServerFnError::<NoCustomError>::Serialization(
+                        e.to_string(),
+                    )
+                    .ser()
+                    .unwrap_or_default(),
+                ))
+                .unwrap(),
+        }
+    }
+
+    /// Builds the request for a single call in a batch.
+    fn batched_request(
+        parts: &http::request::Parts,
+        call: BatchCall,
+    ) -> Result<Request<Body>, ServerFnError> {
+        let invalid = |e: &dyn std::fmt::Display| {
+            ServerFnError::Args(format!("Invalid call in batch: {e}"))
+        };
+        let method = Method::from_bytes(call.method.as_bytes())
+            .map_err(|e| invalid(&e))?;
+        if call.path.split('?').next() == Some(BATCH_PATH) {
+            return Err(invalid(&"batches cannot be nested"));
+        }
+        let mut headers = parts.headers.clone();
+        headers.remove(CONTENT_LENGTH);
+        for (name, value) in call.headers {
+            let name = HeaderName::try_from(name).map_err(|e| invalid(&e))?;
+            if !BATCH_CALL_HEADERS.contains(&name.as_str()) {
+                continue;
+            }
+            let value =
+                HeaderValue::try_from(value).map_err(|e| invalid(&e))?;
+            headers.insert(name, value);
+        }
+        let mut req = Request::builder()
+            .method(method)
+            .uri(call.path)
+            .version(parts.version)
+            .body(Body::from(call.body))
+            .map_err(|e| invalid(&e))?;
+        *req.headers_mut() = headers;
+        *req.extensions_mut() = parts.extensions.clone();
+        Ok(req)
+    }
+
     /// Returns the server function at the given path as a service that can be modified.
     pub fn get_server_fn_service(
         path: &str,
     ) -> Option<BoxedService<Request<Body>, Response<Body>>> {
-        REGISTERED_SERVER_FUNCTIONS.get(path).map(|server_fn| {
-            let middleware = (server_fn.middleware)();
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
-            }
-            service
-        })
+        REGISTERED_SERVER_FUNCTIONS
+            .get(path)
+            .map(|server_fn| layered_service(&server_fn, &MIDDLEWARES))
     }
 }
 
@@ -540,8 +928,13 @@ pub mod axum {
 #[cfg(feature = "actix")]
 pub mod actix {
     use crate::{
-        middleware::BoxedService, request::actix::ActixRequest,
-        response::actix::ActixResponse, Encoding, LazyServerFnMap, ServerFn,
+        batch::BATCH_PATH,
+        error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+        layered_service,
+        middleware::BoxedService,
+        request::actix::ActixRequest,
+        response::actix::ActixResponse,
+        Encoding, LazyMiddlewareMap, LazyServerFnMap, ServerFn,
         ServerFnTraitObj,
     };
     use actix_web::{web::Payload, HttpRequest, HttpResponse};
@@ -554,6 +947,9 @@ pub mod actix {
         ActixResponse,
     > = initialize_server_fn_map!(ActixRequest, ActixResponse);
 
+    static MIDDLEWARES: LazyMiddlewareMap<ActixRequest, ActixResponse> =
+        once_cell::sync::Lazy::new(Default::default);
+
     /// Explicitly register a server function. This is only necessary if you are
     /// running the server in a WASM environment (or a rare environment that the
     /// `inventory` crate won't work in.).
@@ -564,6 +960,7 @@ pub mod actix {
                 ServerResponse = ActixResponse,
             > + 'static,
     {
+        MIDDLEWARES.remove(T::PATH);
         REGISTERED_SERVER_FUNCTIONS.insert(
             T::PATH,
             ServerFnTraitObj::new(
@@ -588,13 +985,10 @@ pub mod actix {
         payload: Payload,
     ) -> HttpResponse {
         let path = req.uri().path();
-        if let Some(server_fn) = REGISTERED_SERVER_FUNCTIONS.get(path) {
-            let middleware = (server_fn.middleware)();
-            // http::Method is the only non-Copy type here
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
-            }
+        if path == BATCH_PATH {
+            return batch_not_supported();
+        }
+        if let Some(mut service) = get_server_fn_service(path) {
             service
                 .0
                 .run(ActixRequest::from((req, payload)))
@@ -615,17 +1009,29 @@ pub mod actix {
         }
     }
 
+    /// Responds to a [batch](crate::batch) of server function calls with
+    /// `501 Not Implemented`. Batching is only supported by the Axum
+    /// integration, because a request for each call in the batch cannot be
+    /// built with the app data of the Actix request. The client then sends
+    /// each call on its own.
+    pub fn batch_not_supported() -> HttpResponse {
+        HttpResponse::NotImplemented().body(
+            ServerFnError::<NoCustomError>::ServerError(
+                "Batches of server function calls are not supported by the \
+                 Actix integration."
+                    .to_string(),
+            )
+            .ser()
+            .unwrap_or_default(),
+        )
+    }
+
     /// Returns the server function at the given path as a service that can be modified.
     pub fn get_server_fn_service(
         path: &str,
     ) -> Option<BoxedService<ActixRequest, ActixResponse>> {
-        REGISTERED_SERVER_FUNCTIONS.get(path).map(|server_fn| {
-            let middleware = (server_fn.middleware)();
-            let mut service = BoxedService::new(server_fn.clone());
-            for middleware in middleware {
-                service = middleware.layer(service);
-            }
-            service
-        })
+        REGISTERED_SERVER_FUNCTIONS
+            .get(path)
+            .map(|server_fn| layered_service(&server_fn, &MIDDLEWARES))
     }
 }
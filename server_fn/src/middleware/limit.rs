@@ -0,0 +1,409 @@
+//! Limits on the requests a server function accepts, which work with any
+//! server integration.
+//!
+//! Each limit is a [`Layer`], added to a server function with the
+//! `#[middleware]` attribute. The middleware of a server function is created
+//! the first time it is called and kept for later calls, so a [`RateLimit`]
+//! or a [`ConcurrencyLimit`] counts all the calls to the server function it is
+//! added to:
+//!
+//! ```rust,ignore
+//! #[server]
+//! #[middleware(RateLimit::per_ip(10, Duration::from_secs(60)))]
+//! #[middleware(BodyLimit::new(64 * 1024))]
+//! pub async fn sign_up(email: String) -> Result<(), ServerFnError> {
+//!     // ...
+//! }
+//! ```
+//!
+//! To share a limit between several server functions, create it once and add
+//! clones of it to each of them, as its state is shared by its clones.
+//!
+//! A request that goes over a limit is answered with an error, without
+//! running the server function.
+
+use super::{BoxedService, Layer, Service};
+use crate::{
+    error::{NoCustomError, ServerFnError, ServerFnErrorSerde},
+    request::Req,
+    response::Res,
+};
+use futures::{future::ready, Future};
+use http::StatusCode;
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Builds the response to a request that went over a limit.
+fn limit_response<Response>(
+    path: &str,
+    status: StatusCode,
+    msg: &str,
+) -> Response
+where
+    Response: Res<NoCustomError>,
+{
+    let err = ServerFnError::<NoCustomError>::ServerError(msg.to_string());
+    Response::encoded_error_response(
+        path,
+        status,
+        "text/plain",
+        err.ser().unwrap_or_default().into(),
+    )
+}
+
+type KeyFn<Request> = dyn Fn(&Request) -> Option<String> + Send + Sync;
+
+/// Limits how often a server function can be called, using a token bucket for
+/// each client.
+///
+/// Each client can make up to `requests` calls at once, after which it can
+/// make them at the rate of `requests` per `per`. Calls beyond that are
+/// answered with `429 Too Many Requests`.
+///
+/// Clients are told apart by their IP address, a header, or a key taken from
+/// the request by a function of your own. A request that no key can be taken
+/// from is answered with `400 Bad Request`, rather than being counted against
+/// a bucket shared with other clients, so a limit never silently stops
+/// telling clients apart.
+pub struct RateLimit<Request> {
+    requests: u32,
+    per: Duration,
+    key: Arc<KeyFn<Request>>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl<Request> Clone for RateLimit<Request> {
+    fn clone(&self) -> Self {
+        Self {
+            requests: self.requests,
+            per: self.per,
+            key: Arc::clone(&self.key),
+            buckets: Arc::clone(&self.buckets),
+        }
+    }
+}
+
+impl<Request> fmt::Debug for RateLimit<Request> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimit")
+            .field("requests", &self.requests)
+            .field("per", &self.per)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Request> RateLimit<Request>
+where
+    Request: Req<NoCustomError>,
+{
+    /// Limits each client by the IP address it connects from.
+    ///
+    /// With Axum, this requires the `axum` feature of `server_fn`, and serving
+    /// the app with `into_make_service_with_connect_info::<SocketAddr>()`, or
+    /// every request is rejected. Behind a proxy, use [`RateLimit::per_header`] with the
+    /// header that the proxy sets.
+    pub fn per_ip(requests: u32, per: Duration) -> Self {
+        Self::per_key(requests, per, |req: &Request| {
+            req.remote_addr().map(|addr| addr.to_string())
+        })
+    }
+
+    /// Limits each client by the value of the given header, like
+    /// `X-Forwarded-For` or an API key. Requests without the header are
+    /// rejected.
+    pub fn per_header(
+        requests: u32,
+        per: Duration,
+        header: &'static str,
+    ) -> Self {
+        Self::per_key(requests, per, move |req: &Request| {
+            req.header(header).map(|value| value.into_owned())
+        })
+    }
+
+    /// Limits all clients together.
+    pub fn global(requests: u32, per: Duration) -> Self {
+        Self::per_key(requests, per, |_: &Request| Some(String::new()))
+    }
+}
+
+impl<Request> RateLimit<Request> {
+    /// Limits each client by the key that `key` returns for its requests.
+    /// Requests for which it returns `None` are rejected.
+    pub fn per_key(
+        requests: u32,
+        per: Duration,
+        key: impl Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            requests: requests.max(1),
+            per,
+            key: Arc::new(key),
+            buckets: Default::default(),
+        }
+    }
+
+    /// Takes a token from the bucket of the given key, if there is one left.
+    fn try_acquire(&self, key: String) -> bool {
+        let capacity = f64::from(self.requests);
+        let rate = capacity / self.per.as_secs_f64().max(f64::EPSILON);
+        let now = Instant::now();
+        let mut buckets =
+            self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        // forget the clients whose buckets have filled up again, once there
+        // are enough of them to be worth it
+        if buckets.map.len() >= buckets.prune_at {
+            buckets.map.retain(|_, bucket| {
+                bucket.tokens_at(now, rate, capacity) < capacity
+            });
+            buckets.prune_at = (buckets.map.len() * 2).max(MIN_PRUNE_AT);
+        }
+
+        let bucket = buckets.map.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens = bucket.tokens_at(now, rate, capacity);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+const MIN_PRUNE_AT: usize = 1024;
+
+struct Buckets {
+    map: HashMap<String, Bucket>,
+    prune_at: usize,
+}
+
+impl Default for Buckets {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+            prune_at: MIN_PRUNE_AT,
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// The number of tokens in the bucket at the given time.
+    fn tokens_at(&self, now: Instant, rate: f64, capacity: f64) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated);
+        (self.tokens + elapsed.as_secs_f64() * rate).min(capacity)
+    }
+}
+
+impl<Request, Response> Layer<Request, Response> for RateLimit<Request>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(RateLimitService {
+            limit: self.clone(),
+            inner,
+        })
+    }
+}
+
+struct RateLimitService<Request, Response> {
+    limit: RateLimit<Request>,
+    inner: BoxedService<Request, Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for RateLimitService<Request, Response>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let Some(key) = (self.limit.key)(&req) else {
+            return Box::pin(ready(limit_response(
+                req.path(),
+                StatusCode::BAD_REQUEST,
+                "The client could not be identified, so the rate limit of \
+                 this server function cannot be applied.",
+            )));
+        };
+        if self.limit.try_acquire(key) {
+            self.inner.0.run(req)
+        } else {
+            Box::pin(ready(limit_response(
+                req.path(),
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests. Try again later.",
+            )))
+        }
+    }
+}
+
+/// Limits the size of the body of a request to a server function.
+///
+/// A request whose `Content-Length` is over the limit is answered with
+/// `413 Payload Too Large`. Otherwise, reading more than the limit from the
+/// body fails, which the server function reports as an error in its
+/// arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit {
+    max: usize,
+}
+
+impl BodyLimit {
+    /// Limits the body to `max` bytes.
+    pub fn new(max: usize) -> Self {
+        Self { max }
+    }
+}
+
+impl<Request, Response> Layer<Request, Response> for BodyLimit
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(BodyLimitService {
+            max: self.max,
+            inner,
+        })
+    }
+}
+
+struct BodyLimitService<Request, Response> {
+    max: usize,
+    inner: BoxedService<Request, Response>,
+}
+
+impl<Request, Response> Service<Request, Response>
+    for BodyLimitService<Request, Response>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let too_large = req
+            .header("Content-Length")
+            .and_then(|len| len.parse::<usize>().ok())
+            .is_some_and(|len| len > self.max);
+        if too_large {
+            Box::pin(ready(limit_response(
+                req.path(),
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "The request body is too large.",
+            )))
+        } else {
+            self.inner.0.run(req.limit_body(self.max))
+        }
+    }
+}
+
+/// Limits how many calls to a server function can run at the same time.
+///
+/// Calls beyond the limit are answered with `503 Service Unavailable` at once,
+/// rather than waiting, which the client retries by default if it has been
+/// given a [`ClientPolicy`](crate::client::ClientPolicy) with retries.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    max: usize,
+    running: Arc<AtomicUsize>,
+}
+
+impl ConcurrencyLimit {
+    /// Allows up to `max` calls at the same time.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            running: Default::default(),
+        }
+    }
+}
+
+impl<Request, Response> Layer<Request, Response> for ConcurrencyLimit
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn layer(
+        &self,
+        inner: BoxedService<Request, Response>,
+    ) -> BoxedService<Request, Response> {
+        BoxedService::new(ConcurrencyLimitService {
+            limit: self.clone(),
+            inner,
+        })
+    }
+}
+
+struct ConcurrencyLimitService<Request, Response> {
+    limit: ConcurrencyLimit,
+    inner: BoxedService<Request, Response>,
+}
+
+/// Counts a running call until it is dropped.
+struct Running(Arc<AtomicUsize>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<Request, Response> Service<Request, Response>
+    for ConcurrencyLimitService<Request, Response>
+where
+    Request: Req<NoCustomError> + Send + 'static,
+    Response: Res<NoCustomError> + Send + 'static,
+{
+    fn run(
+        &mut self,
+        req: Request,
+    ) -> Pin<Box<dyn Future<Output = Response> + Send>> {
+        let running = &self.limit.running;
+        if running.fetch_add(1, Ordering::AcqRel) >= self.limit.max {
+            running.fetch_sub(1, Ordering::AcqRel);
+            return Box::pin(ready(limit_response(
+                req.path(),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many requests are running. Try again later.",
+            )));
+        }
+        let guard = Running(Arc::clone(running));
+        let res = self.inner.0.run(req);
+        Box::pin(async move {
+            let res = res.await;
+            drop(guard);
+            res
+        })
+    }
+}
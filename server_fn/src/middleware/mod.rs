@@ -1,5 +1,8 @@
 use std::{future::Future, pin::Pin};
 
+#[cfg(feature = "ssr")]
+pub mod limit;
+
 /// An abstraction over a middleware layer, which can be used to add additional
 /// middleware layer to a [`Service`].
 pub trait Layer<Req, Res>: Send + Sync + 'static {
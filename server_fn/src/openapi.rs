@@ -0,0 +1,461 @@
+//! Generates an [OpenAPI 3](https://spec.openapis.org/oas/v3.0.3.html) document
+//! that describes every registered server function, so that clients in other
+//! languages can be generated from it.
+//!
+//! ```rust,ignore
+//! let spec = server_fn::openapi::openapi_spec("My App", "1.0.0");
+//! std::fs::write("openapi.json", spec.to_string())?;
+//! ```
+//!
+//! The `#[server]` macro registers the path, method, encodings and doc comments
+//! of each server function, along with the schemas of its arguments and
+//! output. Schemas are built from the [`Schema`] trait, which is implemented for
+//! common standard library types, and can be derived for your own types.
+//! Types that do not implement it are described by an empty schema, which
+//! allows any value.
+
+use crate::error::{NoCustomError, ServerFnError};
+use http::Method;
+pub use serde_json::Value;
+use serde_json::{json, Map};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Describes a type as a JSON schema, for the OpenAPI document.
+///
+/// This can be derived with [`derive(Schema)`](crate::Schema) for structs and
+/// enums, whose schema then follows the
+/// way they are serialized by `serde`, including its `rename`, `rename_all`,
+/// `default` and `skip` attributes.
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize, Schema)]
+/// #[serde(rename_all = "camelCase")]
+/// pub struct Todo {
+///     id: u32,
+///     title: String,
+///     #[serde(default)]
+///     due_date: Option<String>,
+/// }
+/// ```
+///
+/// It can also be implemented by hand, e.g., for types with a custom
+/// serialization.
+///
+/// ```rust,ignore
+/// impl Schema for Todo {
+///     fn schema() -> Value {
+///         json!({
+///             "type": "object",
+///             "properties": {
+///                 "id": u32::schema(),
+///                 "title": String::schema(),
+///             },
+///             "required": ["id", "title"],
+///         })
+///     }
+/// }
+/// ```
+pub trait Schema {
+    /// Returns the JSON schema that describes the type.
+    fn schema() -> Value;
+}
+
+pub use crate::Schema;
+
+macro_rules! impl_schema {
+    ($($ty:ty => $schema:tt),* $(,)?) => {
+        $(
+            impl Schema for $ty {
+                fn schema() -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_schema! {
+    () => { "type": "null" },
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer", "format": "int32" },
+    i16 => { "type": "integer", "format": "int32" },
+    i32 => { "type": "integer", "format": "int32" },
+    i64 => { "type": "integer", "format": "int64" },
+    isize => { "type": "integer", "format": "int64" },
+    u8 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u16 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u32 => { "type": "integer", "format": "int64", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    usize => { "type": "integer", "format": "int64", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    f64 => { "type": "number", "format": "double" },
+    char => { "type": "string", "minLength": 1, "maxLength": 1 },
+    String => { "type": "string" },
+    str => { "type": "string" },
+    Value => {},
+    NoCustomError => { "type": "null" },
+}
+
+impl<T: Schema + ?Sized> Schema for &T {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Box<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Rc<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Arc<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        let mut schema = T::schema();
+        if let Some(schema) = schema.as_object_mut() {
+            schema.insert("nullable".to_string(), Value::Bool(true));
+        }
+        schema
+    }
+}
+
+impl<T: Schema> Schema for [T] {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn schema() -> Value {
+        json!({
+            "type": "array",
+            "items": T::schema(),
+            "minItems": N,
+            "maxItems": N,
+        })
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        <[T]>::schema()
+    }
+}
+
+impl<T: Schema> Schema for VecDeque<T> {
+    fn schema() -> Value {
+        <[T]>::schema()
+    }
+}
+
+impl<T: Schema, S> Schema for HashSet<T, S> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema(), "uniqueItems": true })
+    }
+}
+
+impl<T: Schema> Schema for BTreeSet<T> {
+    fn schema() -> Value {
+        HashSet::<T>::schema()
+    }
+}
+
+impl<K, V: Schema, S> Schema for HashMap<K, V, S> {
+    fn schema() -> Value {
+        // serde serializes map keys as strings
+        json!({ "type": "object", "additionalProperties": V::schema() })
+    }
+}
+
+impl<K, V: Schema> Schema for BTreeMap<K, V> {
+    fn schema() -> Value {
+        HashMap::<K, V>::schema()
+    }
+}
+
+/// Describes a server function, for the OpenAPI document.
+///
+/// This is registered by the `#[server]` macro.
+#[derive(Debug)]
+pub struct ServerFnSpec {
+    /// The name of the server function.
+    pub name: &'static str,
+    /// The path of the server function.
+    pub path: &'static str,
+    /// The HTTP method the server function expects.
+    pub method: Method,
+    /// The content type of the request body.
+    pub input_content_type: &'static str,
+    /// The content type of the response body.
+    pub output_content_type: &'static str,
+    /// The doc comments on the server function.
+    pub docs: &'static str,
+    /// Returns the schema of the arguments.
+    pub input: fn() -> Value,
+    /// Returns the schema of the output.
+    pub output: fn() -> Value,
+    /// Returns the schema of the errors, for server functions with
+    /// `typed_errors`, which encode their errors with their output encoding.
+    pub typed_error: Option<fn() -> Value>,
+}
+
+inventory::collect!(ServerFnSpec);
+
+/// Returns an OpenAPI document that describes every registered server
+/// function.
+pub fn openapi_spec(title: &str, version: &str) -> Value {
+    let mut specs = inventory::iter::<ServerFnSpec>
+        .into_iter()
+        .collect::<Vec<_>>();
+    specs.sort_by_key(|spec| spec.path);
+
+    let mut paths = Map::new();
+    for spec in specs {
+        let method = spec.method.as_str().to_ascii_lowercase();
+        let path = paths
+            .entry(spec.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        path[method] = operation(spec);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": title, "version": version },
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "ServerFnError": {
+                    "type": "string",
+                    "description": "A `ServerFnError`, serialized as the name \
+                        of its variant and its message, separated by `|`.",
+                    "pattern": "^(WrappedServerFn|Registration|Request|\
+                        Response|ServerError|Deserialization|Serialization|\
+                        Args|MissingArg)\\|",
+                    "example": "ServerError|Something went wrong.",
+                }
+            }
+        }
+    })
+}
+
+/// Describes a single server function as an OpenAPI operation.
+fn operation(spec: &ServerFnSpec) -> Value {
+    let mut operation = Map::new();
+    operation.insert("operationId".to_string(), json!(spec.name));
+    let docs = spec.docs.trim();
+    if let Some(summary) = docs.lines().next().filter(|line| !line.is_empty()) {
+        operation.insert("summary".to_string(), json!(summary));
+        operation.insert("description".to_string(), json!(docs));
+    }
+
+    let input = body_schema(spec.input_content_type, (spec.input)());
+    if spec.method == Method::GET {
+        // the arguments are sent in the query string
+        let required =
+            input["required"].as_array().cloned().unwrap_or_default();
+        let parameters = input["properties"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(name, schema)| {
+                let mut parameter = json!({
+                    "name": name,
+                    "in": "query",
+                    "required": required.contains(&json!(name)),
+                    "schema": schema,
+                });
+                if schema["type"] == "object" {
+                    parameter["style"] = json!("deepObject");
+                }
+                parameter
+            })
+            .collect::<Vec<_>>();
+        operation.insert("parameters".to_string(), json!(parameters));
+    } else {
+        operation.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": { spec.input_content_type: { "schema": input } },
+            }),
+        );
+    }
+
+    let output = body_schema(spec.output_content_type, (spec.output)());
+    let mut responses = json!({
+        "200": {
+            "description": "The output of the server function.",
+            "content": { spec.output_content_type: { "schema": output } },
+        },
+    });
+    match spec.typed_error {
+        // the status code of a typed error comes from its `ErrorStatus`
+        Some(error) => {
+            let error = body_schema(spec.output_content_type, error());
+            responses["default"] = json!({
+                "description": "An error returned by the server function.",
+                "content": { spec.output_content_type: { "schema": error } },
+            });
+        }
+        None => {
+            responses["500"] = json!({
+                "description": "An error returned by the server function.",
+                "content": {
+                    "text/plain": {
+                        "schema": {
+                            "$ref": "#/components/schemas/ServerFnError"
+                        }
+                    }
+                },
+            });
+        }
+    }
+    operation.insert("responses".to_string(), responses);
+    Value::Object(operation)
+}
+
+/// Returns the schema of a body with the given content type, which is only
+/// described by the schema of its data if the encoding preserves its
+/// structure.
+fn body_schema(content_type: &str, schema: Value) -> Value {
+    match content_type {
+        "application/json"
+        | "application/cbor"
+        | "application/msgpack"
+        | "application/x-www-form-urlencoded" => schema,
+        "multipart/form-data" => json!({ "type": "object" }),
+        "text/plain" | "text/event-stream" => json!({ "type": "string" }),
+        _ => json!({ "type": "string", "format": "binary" }),
+    }
+}
+
+/// Builds the schema of an object, like the arguments of a server function,
+/// from the name and schema of each field, and whether it has a default.
+#[doc(hidden)]
+pub fn object_schema(fields: Vec<(&str, Value, bool)>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for (name, schema, has_default) in fields {
+        if !has_default && schema["nullable"] != true {
+            required.push(json!(name));
+        }
+        properties.insert(name.to_string(), schema);
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Builds the schema of a tuple, which is serialized as an array.
+#[doc(hidden)]
+pub fn tuple_schema(items: Vec<Value>) -> Value {
+    json!({
+        "type": "array",
+        "items": { "anyOf": items },
+        "minItems": items.len(),
+        "maxItems": items.len(),
+    })
+}
+
+/// Builds the schema of an enum from the name of each variant, and the schema
+/// of its fields if it has any, following `serde`’s default, externally
+/// tagged representation.
+#[doc(hidden)]
+pub fn enum_schema(variants: Vec<(&str, Option<Value>)>) -> Value {
+    let (unit, other): (Vec<_>, Vec<_>) = variants
+        .into_iter()
+        .partition(|(_, fields)| fields.is_none());
+    // unit variants are serialized as their names
+    let unit = (!unit.is_empty()).then(|| {
+        let names = unit.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        json!({ "type": "string", "enum": names })
+    });
+    let mut schemas = unit
+        .into_iter()
+        .chain(other.into_iter().map(|(name, fields)| {
+            json!({
+                "type": "object",
+                "properties": { name: fields },
+                "required": [name],
+                "additionalProperties": false,
+            })
+        }))
+        .collect::<Vec<_>>();
+    if schemas.len() == 1 {
+        schemas.remove(0)
+    } else {
+        json!({ "oneOf": schemas })
+    }
+}
+
+impl<E: Schema> Schema for ServerFnError<E> {
+    fn schema() -> Value {
+        enum_schema(vec![
+            ("WrappedServerError", Some(E::schema())),
+            ("Registration", Some(String::schema())),
+            ("Request", Some(String::schema())),
+            ("Response", Some(String::schema())),
+            ("ServerError", Some(String::schema())),
+            ("Deserialization", Some(String::schema())),
+            ("Serialization", Some(String::schema())),
+            ("Args", Some(String::schema())),
+            ("MissingArg", Some(String::schema())),
+        ])
+    }
+}
+
+/// Returns the schema of `T`, if it implements [`Schema`].
+///
+/// The `#[server]` macro calls `(&SchemaOf::<T>::new()).schema()`, which uses
+/// [`ViaSchema`] if `T` implements [`Schema`], and falls back to [`ViaAny`]
+/// otherwise.
+#[doc(hidden)]
+pub struct SchemaOf<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> SchemaOf<T> {
+    #[doc(hidden)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaSchema {
+    fn schema(&self) -> Value;
+}
+
+impl<T: Schema + ?Sized> ViaSchema for SchemaOf<T> {
+    fn schema(&self) -> Value {
+        T::schema()
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaAny {
+    fn schema(&self) -> Value;
+}
+
+impl<T: ?Sized> ViaAny for &SchemaOf<T> {
+    fn schema(&self) -> Value {
+        json!({})
+    }
+}
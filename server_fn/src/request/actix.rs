@@ -1,9 +1,16 @@
 use crate::{error::ServerFnError, request::Req};
-use actix_web::{web::Payload, HttpRequest};
+#[cfg(feature = "websocket")]
+use crate::{
+    request::{WebsocketReq, WebsocketSender},
+    response::actix::ActixResponse,
+};
+use actix_web::{
+    dev, error::PayloadError, web::Payload, FromRequest, HttpRequest,
+};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{FutureExt, Stream, StreamExt};
 use send_wrapper::SendWrapper;
-use std::{borrow::Cow, future::Future};
+use std::{borrow::Cow, future::Future, net::IpAddr, pin::Pin};
 
 /// A wrapped Actix request.
 ///
@@ -53,6 +60,42 @@ where
         self.header("Referer")
     }
 
+    fn path(&self) -> &str {
+        self.0 .0.path()
+    }
+
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        ActixRequest::header(self, name)
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        self.0 .0.peer_addr().map(|addr| addr.ip())
+    }
+
+    fn limit_body(self, max: usize) -> Self {
+        let (req, payload) = self.take();
+        let mut read = 0;
+        let limited = payload.map(move |chunk| {
+            let chunk = chunk?;
+            read += chunk.len();
+            if read > max {
+                Err(PayloadError::Overflow)
+            } else {
+                Ok(chunk)
+            }
+        });
+        let mut limited = dev::Payload::Stream {
+            payload: Box::pin(limited)
+                as Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>,
+        };
+        // extracting the payload is always ready at once
+        let payload = Payload::from_request(&req, &mut limited)
+            .now_or_never()
+            .and_then(Result::ok)
+            .expect("extracting the payload is infallible");
+        ActixRequest::from((req, payload))
+    }
+
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send
@@ -68,6 +111,28 @@ where
         })
     }
 
+    fn try_buffer_body(
+        self,
+    ) -> impl Future<Output = Result<(Bytes, Self), ServerFnError<CustErr>>> + Send
+    {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            let (req, payload) = self.0.take();
+            let bytes = payload
+                .to_bytes()
+                .await
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+            let mut buffered = dev::Payload::from(bytes.clone());
+            // extracting the payload is always ready at once
+            let payload = Payload::from_request(&req, &mut buffered)
+                .now_or_never()
+                .and_then(Result::ok)
+                .expect("extracting the payload is infallible");
+            Ok((bytes, ActixRequest::from((req, payload))))
+        })
+    }
+
     fn try_into_string(
         self,
     ) -> impl Future<Output = Result<String, ServerFnError<CustErr>>> + Send
@@ -91,6 +156,86 @@ where
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send,
         ServerFnError<CustErr>,
     > {
-        Ok(futures::stream::once(async { todo!() }))
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        let payload = self.0.take().1;
+        Ok(SendWrapper::new(payload.map(|chunk| {
+            chunk.map_err(|e| ServerFnError::Deserialization(e.to_string()))
+        })))
+    }
+}
+
+#[cfg(feature = "websocket")]
+impl<CustErr> WebsocketReq<CustErr> for ActixRequest
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = ActixResponse;
+
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (
+                impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+                WebsocketSender,
+                Self::WebsocketResponse,
+            ),
+            ServerFnError<CustErr>,
+        >,
+    > + Send {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            use actix_ws::AggregatedMessage;
+            use futures::channel::{mpsc, oneshot};
+
+            let (req, payload) = self.0.take();
+            let (res, mut session, stream) = actix_ws::handle(&req, payload)
+                .map_err(|e| ServerFnError::Request(e.to_string()))?;
+            let (incoming_tx, incoming_rx) = mpsc::unbounded();
+            let (outgoing_tx, outgoing_rx): (WebsocketSender, _) =
+                oneshot::channel();
+
+            // the session and message stream are tied to this thread, so
+            // they are driven by a local task that forwards messages to and
+            // from the channels
+            actix_web::rt::spawn(async move {
+                let mut pong_session = session.clone();
+                let mut stream = stream.aggregate_continuations();
+                let receive = async move {
+                    while let Some(msg) = stream.next().await {
+                        let msg = match msg {
+                            Ok(AggregatedMessage::Binary(data)) => Ok(data),
+                            Ok(AggregatedMessage::Ping(data)) => {
+                                _ = pong_session.pong(&data).await;
+                                continue;
+                            }
+                            Ok(AggregatedMessage::Close(_)) => break,
+                            Ok(_) => continue,
+                            Err(e) => {
+                                Err(ServerFnError::Request(e.to_string()))
+                            }
+                        };
+                        if incoming_tx.unbounded_send(msg).is_err() {
+                            break;
+                        }
+                    }
+                };
+                let send = async move {
+                    if let Ok(mut outgoing) = outgoing_rx.await {
+                        while let Some(data) = outgoing.next().await {
+                            if session.binary(data).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    _ = session.close(None).await;
+                };
+                futures::join!(receive, send);
+            });
+
+            Ok((incoming_rx, outgoing_tx, ActixResponse::from(res)))
+        })
     }
 }
@@ -1,12 +1,16 @@
+#[cfg(feature = "websocket")]
+use crate::request::{WebsocketReq, WebsocketSender};
 use crate::{error::ServerFnError, request::Req};
 use axum::body::{Body, Bytes};
 use futures::{Stream, StreamExt};
+#[cfg(feature = "websocket")]
+use http::Response;
 use http::{
     header::{ACCEPT, CONTENT_TYPE, REFERER},
     Request,
 };
-use http_body_util::BodyExt;
-use std::borrow::Cow;
+use http_body_util::{BodyExt, Limited};
+use std::{borrow::Cow, net::IpAddr};
 
 impl<CustErr> Req<CustErr> for Request<Body>
 where
@@ -34,6 +38,38 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn path(&self) -> &str {
+        self.uri().path()
+    }
+
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(name)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        // only known if the app is served with
+        // `into_make_service_with_connect_info::<SocketAddr>()`
+        #[cfg(feature = "axum")]
+        {
+            use axum::extract::ConnectInfo;
+            use std::net::SocketAddr;
+
+            self.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| info.0.ip())
+        }
+        #[cfg(not(feature = "axum"))]
+        {
+            None
+        }
+    }
+
+    fn limit_body(self, max: usize) -> Self {
+        self.map(|body| Body::new(Limited::new(body, max)))
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         let (_parts, body) = self.into_parts();
 
@@ -43,6 +79,18 @@ where
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 
+    async fn try_buffer_body(
+        self,
+    ) -> Result<(Bytes, Self), ServerFnError<CustErr>> {
+        let (parts, body) = self.into_parts();
+        let bytes = body
+            .collect()
+            .await
+            .map(|c| c.to_bytes())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        Ok((bytes.clone(), Request::from_parts(parts, Body::from(bytes))))
+    }
+
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
         let bytes = self.try_into_bytes().await?;
         String::from_utf8(bytes.to_vec())
@@ -60,3 +108,68 @@ where
         }))
     }
 }
+
+#[cfg(feature = "websocket")]
+impl<CustErr> WebsocketReq<CustErr> for Request<Body>
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = Response<Body>;
+
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+            WebsocketSender,
+            Self::WebsocketResponse,
+        ),
+        ServerFnError<CustErr>,
+    > {
+        use axum::extract::{
+            ws::{Message, WebSocketUpgrade},
+            FromRequestParts,
+        };
+        use futures::{
+            channel::{mpsc, oneshot},
+            SinkExt,
+        };
+
+        let (mut parts, _body) = self.into_parts();
+        let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &())
+            .await
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        let (incoming_tx, incoming_rx) = mpsc::unbounded();
+        let (outgoing_tx, outgoing_rx): (WebsocketSender, _) =
+            oneshot::channel();
+        let res = upgrade.on_upgrade(|socket| async move {
+            let (mut sink, mut stream) = socket.split();
+            let receive = async move {
+                while let Some(msg) = stream.next().await {
+                    let msg = match msg {
+                        Ok(Message::Binary(data)) => Ok(Bytes::from(data)),
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => continue,
+                        Err(e) => Err(ServerFnError::Request(e.to_string())),
+                    };
+                    if incoming_tx.unbounded_send(msg).is_err() {
+                        break;
+                    }
+                }
+            };
+            let send = async move {
+                if let Ok(mut outgoing) = outgoing_rx.await {
+                    while let Some(data) = outgoing.next().await {
+                        let msg = Message::Binary(data.into());
+                        if sink.send(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+                _ = sink.close().await;
+            };
+            futures::join!(receive, send);
+        });
+        Ok((incoming_rx, outgoing_tx, res))
+    }
+}
@@ -8,27 +8,101 @@ use send_wrapper::SendWrapper;
 use std::ops::{Deref, DerefMut};
 use wasm_bindgen::JsValue;
 use wasm_streams::ReadableStream;
-use web_sys::{FormData, Headers, RequestInit, UrlSearchParams};
+use web_sys::{
+    AbortController, AbortSignal, FormData, Headers, RequestInit,
+    UrlSearchParams,
+};
 
 /// A `fetch` request made in the browser.
 #[derive(Debug)]
-pub struct BrowserRequest(pub(crate) SendWrapper<Request>);
+pub struct BrowserRequest(pub(crate) SendWrapper<RequestInner>);
+
+#[derive(Debug)]
+pub(crate) struct RequestInner {
+    pub(crate) request: Request,
+    pub(crate) abort_ctrl: Option<AbortOnDrop>,
+}
+
+/// Aborts the `fetch` request it is attached to when it is dropped, unless
+/// [`AbortOnDrop::run`] has been called first. This means that dropping the
+/// `Future` of a server function call (for example, because a newer call has
+/// superseded it) cancels the underlying HTTP request.
+#[derive(Debug)]
+pub(crate) struct AbortOnDrop(Option<AbortController>);
+
+impl AbortOnDrop {
+    /// Disarms the guard, because the request has completed.
+    pub fn run(mut self) {
+        self.0.take();
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        if let Some(ctrl) = self.0.take() {
+            ctrl.abort();
+        }
+    }
+}
+
+fn abort_signal() -> (Option<AbortOnDrop>, Option<AbortSignal>) {
+    let ctrl = AbortController::new().ok();
+    let signal = ctrl.as_ref().map(|ctrl| ctrl.signal());
+    (ctrl.map(|ctrl| AbortOnDrop(Some(ctrl))), signal)
+}
+
+impl BrowserRequest {
+    /// Returns the request, along with a copy of it that has its own abort
+    /// signal, so that it can be sent again once the original has been.
+    ///
+    /// There is no copy if the request cannot be copied, for example because
+    /// its body has already been read.
+    pub fn try_clone(self) -> (Self, Option<Self>) {
+        let RequestInner {
+            request,
+            abort_ctrl,
+        } = self.0.take();
+        let request = web_sys::Request::from(request);
+        // this is `Request.clone()`, which copies the body, rather than
+        // `Clone::clone`, which would share it
+        let copy = web_sys::Request::clone(&request).ok().and_then(|copy| {
+            let (abort_ctrl, abort_signal) = abort_signal();
+            let init = RequestInit::new();
+            init.set_signal(abort_signal.as_ref());
+            let copy =
+                web_sys::Request::new_with_request_and_init(&copy, &init)
+                    .ok()?;
+            Some(Self(SendWrapper::new(RequestInner {
+                request: copy.into(),
+                abort_ctrl,
+            })))
+        });
+        let request = Self(SendWrapper::new(RequestInner {
+            request: request.into(),
+            abort_ctrl,
+        }));
+        (request, copy)
+    }
+}
 
 impl From<Request> for BrowserRequest {
     fn from(value: Request) -> Self {
-        Self(SendWrapper::new(value))
+        Self(SendWrapper::new(RequestInner {
+            request: value,
+            abort_ctrl: None,
+        }))
     }
 }
 
 impl From<BrowserRequest> for Request {
     fn from(value: BrowserRequest) -> Self {
-        value.0.take()
+        value.0.take().request
     }
 }
 
 impl From<BrowserRequest> for web_sys::Request {
     fn from(value: BrowserRequest) -> Self {
-        value.0.take().into()
+        value.0.take().request.into()
     }
 }
 
@@ -36,13 +110,13 @@ impl Deref for BrowserRequest {
     type Target = Request;
 
     fn deref(&self) -> &Self::Target {
-        self.0.deref()
+        &self.0.deref().request
     }
 }
 
 impl DerefMut for BrowserRequest {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        self.0.deref_mut()
+        &mut self.0.deref_mut().request
     }
 }
 
@@ -73,13 +147,16 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         url.push_str(path);
         url.push('?');
         url.push_str(query);
-        Ok(Self(SendWrapper::new(
-            Request::get(&url)
+        let (abort_ctrl, abort_signal) = abort_signal();
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: Request::get(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
                 .build()
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
-        )))
+            abort_ctrl,
+        })))
     }
 
     fn try_new_post(
@@ -92,13 +169,16 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         let mut url = String::with_capacity(server_url.len() + path.len());
         url.push_str(server_url);
         url.push_str(path);
-        Ok(Self(SendWrapper::new(
-            Request::post(&url)
+        let (abort_ctrl, abort_signal) = abort_signal();
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: Request::post(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
-        )))
+            abort_ctrl,
+        })))
     }
 
     fn try_new_post_bytes(
@@ -113,13 +193,16 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         url.push_str(path);
         let body: &[u8] = &body;
         let body = Uint8Array::from(body).buffer();
-        Ok(Self(SendWrapper::new(
-            Request::post(&url)
+        let (abort_ctrl, abort_signal) = abort_signal();
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: Request::post(&url)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
                 .body(body)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
-        )))
+            abort_ctrl,
+        })))
     }
 
     fn try_new_multipart(
@@ -131,12 +214,15 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         let mut url = String::with_capacity(server_url.len() + path.len());
         url.push_str(server_url);
         url.push_str(path);
-        Ok(Self(SendWrapper::new(
-            Request::post(&url)
+        let (abort_ctrl, abort_signal) = abort_signal();
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: Request::post(&url)
                 .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
                 .body(body.0.take())
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
-        )))
+            abort_ctrl,
+        })))
     }
 
     fn try_new_post_form_data(
@@ -156,13 +242,16 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
                         },
                     ))
                 })?;
-        Ok(Self(SendWrapper::new(
-            Request::post(path)
+        let (abort_ctrl, abort_signal) = abort_signal();
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: Request::post(path)
                 .header("Content-Type", content_type)
                 .header("Accept", accepts)
+                .abort_signal(abort_signal.as_ref())
                 .body(url_params)
                 .map_err(|e| ServerFnError::Request(e.to_string()))?,
-        )))
+            abort_ctrl,
+        })))
     }
 
     fn try_new_streaming(
@@ -171,9 +260,42 @@ impl<CustErr> ClientReq<CustErr> for BrowserRequest {
         content_type: &str,
         body: impl Stream<Item = Bytes> + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        let req = streaming_request(path, accepts, content_type, body)
-            .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
-        Ok(Self(SendWrapper::new(req)))
+        let url = format!("{}{}", get_server_url(), path);
+        let (abort_ctrl, abort_signal) = abort_signal();
+        let request = streaming_request(
+            &url,
+            accepts,
+            content_type,
+            abort_signal.as_ref(),
+            body,
+        )
+        .map_err(|e| ServerFnError::Request(format!("{e:?}")))?;
+        Ok(Self(SendWrapper::new(RequestInner {
+            request,
+            abort_ctrl,
+        })))
+    }
+
+    fn try_with_header(
+        self,
+        name: &str,
+        value: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let RequestInner {
+            request,
+            abort_ctrl,
+        } = self.0.take();
+        let request = web_sys::Request::from(request);
+        request.headers().set(name, value).map_err(|e| {
+            ServerFnError::Request(
+                e.as_string()
+                    .unwrap_or_else(|| format!("invalid header {name:?}")),
+            )
+        })?;
+        Ok(Self(SendWrapper::new(RequestInner {
+            request: request.into(),
+            abort_ctrl,
+        })))
     }
 }
 
@@ -181,6 +303,7 @@ fn streaming_request(
     path: &str,
     accepts: &str,
     content_type: &str,
+    signal: Option<&AbortSignal>,
     body: impl Stream<Item = Bytes> + 'static,
 ) -> Result<Request, JsValue> {
     let stream = ReadableStream::from_stream(body.map(|bytes| {
@@ -193,7 +316,10 @@ fn streaming_request(
     headers.append("Content-Type", content_type)?;
     headers.append("Accept", accepts)?;
     let mut init = RequestInit::new();
-    init.headers(&headers).method("POST").body(Some(&stream));
+    init.headers(&headers)
+        .method("POST")
+        .signal(signal)
+        .body(Some(&stream));
 
     // Chrome requires setting `duplex: "half"` on streaming requests
     Reflect::set(
@@ -1,7 +1,11 @@
 use crate::error::ServerFnError;
 use bytes::Bytes;
+#[cfg(feature = "websocket")]
+use futures::channel::oneshot;
 use futures::Stream;
-use std::{borrow::Cow, future::Future};
+#[cfg(feature = "websocket")]
+use std::pin::Pin;
+use std::{borrow::Cow, future::Future, net::IpAddr};
 
 /// Request types for Actix.
 #[cfg(feature = "actix")]
@@ -70,8 +74,25 @@ where
         content_type: &str,
         body: impl Stream<Item = Bytes> + Send + 'static,
     ) -> Result<Self, ServerFnError<CustErr>>;
+
+    /// Sets a header on the request, replacing any existing value.
+    ///
+    /// This is used to send the `Idempotency-Key` of a queued call, which the
+    /// server relies on to run it only once, so a client that cannot set the
+    /// header should return an error rather than send the request without it.
+    fn try_with_header(
+        self,
+        name: &str,
+        value: &str,
+    ) -> Result<Self, ServerFnError<CustErr>>;
 }
 
+/// Hands the binary messages that should be sent to the client over to a
+/// websocket connection opened by [`WebsocketReq::try_into_websocket`].
+#[cfg(feature = "websocket")]
+pub type WebsocketSender =
+    oneshot::Sender<Pin<Box<dyn Stream<Item = Bytes> + Send>>>;
+
 /// Represents the request as received by the server.
 pub trait Req<CustErr>
 where
@@ -89,11 +110,30 @@ where
     /// Returns the `Referer` header, if any.
     fn referer(&self) -> Option<Cow<'_, str>>;
 
+    /// Returns the path of the request’s URL.
+    fn path(&self) -> &str;
+
+    /// Returns the first value of the given header, if any.
+    fn header(&self, name: &str) -> Option<Cow<'_, str>>;
+
+    /// Returns the IP address of the client that sent the request, if known.
+    fn remote_addr(&self) -> Option<IpAddr>;
+
+    /// Limits the body of the request to `max` bytes. Reading any more than
+    /// that fails.
+    fn limit_body(self, max: usize) -> Self;
+
     /// Attempts to extract the body of the request into [`Bytes`].
     fn try_into_bytes(
         self,
     ) -> impl Future<Output = Result<Bytes, ServerFnError<CustErr>>> + Send;
 
+    /// Reads the whole body of the request into [`Bytes`], returning it along
+    /// with a request whose body can still be read in full.
+    fn try_buffer_body(
+        self,
+    ) -> impl Future<Output = Result<(Bytes, Self), ServerFnError<CustErr>>> + Send;
+
     /// Attempts to convert the body of the request into a string.
     fn try_into_string(
         self,
@@ -108,6 +148,34 @@ where
     >;
 }
 
+/// A request that can be upgraded to a websocket connection, as needed by
+/// server functions that use [`Websocket`](crate::codec::Websocket).
+#[cfg(feature = "websocket")]
+pub trait WebsocketReq<CustErr>: Req<CustErr> {
+    /// The type of the response that completes a websocket upgrade.
+    type WebsocketResponse: Send;
+
+    /// Attempts to upgrade the request to a websocket connection.
+    ///
+    /// Returns the stream of binary messages received from the client, a
+    /// [`WebsocketSender`] for the stream of binary messages to send back, and
+    /// the response that completes the upgrade. The connection is closed once
+    /// the stream of outgoing messages ends.
+    #[allow(clippy::type_complexity)]
+    fn try_into_websocket(
+        self,
+    ) -> impl Future<
+        Output = Result<
+            (
+                impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+                WebsocketSender,
+                Self::WebsocketResponse,
+            ),
+            ServerFnError<CustErr>,
+        >,
+    > + Send;
+}
+
 /// A mocked request type that can be used in place of the actual server request,
 /// when compiling for the browser.
 pub struct BrowserMockReq;
@@ -131,10 +199,33 @@ where
     fn referer(&self) -> Option<Cow<'_, str>> {
         unreachable!()
     }
+
+    fn path(&self) -> &str {
+        unreachable!()
+    }
+
+    fn header(&self, _name: &str) -> Option<Cow<'_, str>> {
+        unreachable!()
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        unreachable!()
+    }
+
+    fn limit_body(self, _max: usize) -> Self {
+        unreachable!()
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         unreachable!()
     }
 
+    async fn try_buffer_body(
+        self,
+    ) -> Result<(Bytes, Self), ServerFnError<CustErr>> {
+        unreachable!()
+    }
+
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
         unreachable!()
     }
@@ -148,3 +239,28 @@ where
         Ok(futures::stream::once(async { unreachable!() }))
     }
 }
+
+#[cfg(feature = "websocket")]
+impl<CustErr> WebsocketReq<CustErr> for BrowserMockReq
+where
+    CustErr: 'static,
+{
+    type WebsocketResponse = crate::response::BrowserMockRes;
+
+    async fn try_into_websocket(
+        self,
+    ) -> Result<
+        (
+            impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
+            WebsocketSender,
+            Self::WebsocketResponse,
+        ),
+        ServerFnError<CustErr>,
+    > {
+        Ok((
+            futures::stream::once(async { unreachable!() }),
+            oneshot::channel().0,
+            crate::response::BrowserMockRes,
+        ))
+    }
+}
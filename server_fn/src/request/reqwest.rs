@@ -1,10 +1,15 @@
 use super::ClientReq;
 use crate::{client::get_server_url, error::ServerFnError};
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use once_cell::sync::Lazy;
-use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{
+    header::{HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE},
+    Body,
+};
 pub use reqwest::{multipart::Form, Client, Method, Request, Url};
+use std::convert::Infallible;
+use sync_wrapper::SyncStream;
 
 pub(crate) static CLIENT: Lazy<Client> = Lazy::new(Client::new);
 
@@ -91,28 +96,35 @@ impl<CustErr> ClientReq<CustErr> for Request {
     }
 
     fn try_new_streaming(
-        _path: &str,
-        _accepts: &str,
-        _content_type: &str,
-        _body: impl Stream<Item = Bytes> + 'static,
+        path: &str,
+        accepts: &str,
+        content_type: &str,
+        body: impl Stream<Item = Bytes> + Send + 'static,
     ) -> Result<Self, ServerFnError<CustErr>> {
-        todo!("Streaming requests are not yet implemented for reqwest.")
-        // We run into a fundamental issue here.
-        // To be a reqwest body, the type must be Sync
-        // That means the streaming types need to be wrappers over Sync streams
-        // However, Axum BodyDataStream is !Sync, so we can't use the same wrapper type there
+        let url = format!("{}{}", get_server_url(), path);
+        // a reqwest body must be `Sync`, which most streams are not, but it
+        // is only ever polled through a mutable reference
+        let body =
+            Body::wrap_stream(SyncStream::new(body.map(Ok::<_, Infallible>)));
+        CLIENT
+            .post(url)
+            .header(CONTENT_TYPE, content_type)
+            .header(ACCEPT, accepts)
+            .body(body)
+            .build()
+            .map_err(|e| ServerFnError::Request(e.to_string()))
+    }
 
-        /*        let url = format!("{}{}", get_server_url(), path);
-            let body = Body::wrap_stream(
-                body.map(|chunk| Ok(chunk) as Result<Bytes, ServerFnErrorErr>),
-            );
-            CLIENT
-                .post(url)
-                .header(CONTENT_TYPE, content_type)
-                .header(ACCEPT, accepts)
-                .body(body)
-                .build()
-                .map_err(|e| ServerFnError::Request(e.to_string()))
-        }*/
+    fn try_with_header(
+        mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let name = HeaderName::try_from(name)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        let value = HeaderValue::try_from(value)
+            .map_err(|e| ServerFnError::Request(e.to_string()))?;
+        self.headers_mut().insert(name, value);
+        Ok(self)
     }
 }
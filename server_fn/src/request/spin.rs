@@ -5,8 +5,8 @@ use http::{
     header::{ACCEPT, CONTENT_TYPE, REFERER},
     Request,
 };
-use http_body_util::BodyExt;
-use std::borrow::Cow;
+use http_body_util::{BodyExt, Limited};
+use std::{borrow::Cow, net::IpAddr};
 
 impl<CustErr> Req<CustErr> for IncomingRequest
 where
@@ -34,6 +34,30 @@ where
             .map(|h| String::from_utf8_lossy(h.as_bytes()))
     }
 
+    fn path(&self) -> &str {
+        self.uri().path()
+    }
+
+    fn header(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.headers()
+            .get(name)
+            .map(|h| String::from_utf8_lossy(h.as_bytes()))
+    }
+
+    fn remote_addr(&self) -> Option<IpAddr> {
+        // Spin passes the address of the client on in a header, along with its
+        // port
+        let addr = self.headers().get("spin-client-addr")?.to_str().ok()?;
+        addr.parse::<std::net::SocketAddr>()
+            .map(|addr| addr.ip())
+            .or_else(|_| addr.parse::<IpAddr>())
+            .ok()
+    }
+
+    fn limit_body(self, max: usize) -> Self {
+        self.map(|body| Body::new(Limited::new(body, max)))
+    }
+
     async fn try_into_bytes(self) -> Result<Bytes, ServerFnError<CustErr>> {
         let (_parts, body) = self.into_parts();
 
@@ -43,6 +67,18 @@ where
             .map_err(|e| ServerFnError::Deserialization(e.to_string()))
     }
 
+    async fn try_buffer_body(
+        self,
+    ) -> Result<(Bytes, Self), ServerFnError<CustErr>> {
+        let (parts, body) = self.into_parts();
+        let bytes = body
+            .collect()
+            .await
+            .map(|c| c.to_bytes())
+            .map_err(|e| ServerFnError::Deserialization(e.to_string()))?;
+        Ok((bytes.clone(), Request::from_parts(parts, Body::from(bytes))))
+    }
+
     async fn try_into_string(self) -> Result<String, ServerFnError<CustErr>> {
         let bytes = self.try_into_bytes().await?;
         String::from_utf8(bytes.to_vec())
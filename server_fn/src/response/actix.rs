@@ -1,8 +1,13 @@
 use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+use crate::{
+    cache,
+    error::{
+        ServerFnError, ServerFnErrorErr, ServerFnErrorSerde,
+        SERVER_FN_ERROR_HEADER,
+    },
 };
 use actix_web::{
+    body::{self, BoxBody},
     http::{
         header,
         header::{HeaderValue, LOCATION},
@@ -15,6 +20,7 @@ use futures::{Stream, StreamExt};
 use send_wrapper::SendWrapper;
 use std::{
     fmt::{Debug, Display},
+    future::Future,
     str::FromStr,
 };
 
@@ -88,10 +94,64 @@ where
         ))
     }
 
+    fn encoded_error_response(
+        path: &str,
+        status: http::StatusCode,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        let status = StatusCode::from_u16(status.as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        ActixResponse(SendWrapper::new(
+            HttpResponse::build(status)
+                .insert_header((header::CONTENT_TYPE, content_type))
+                .append_header((SERVER_FN_ERROR_HEADER, path))
+                .body(data),
+        ))
+    }
+
     fn redirect(&mut self, path: &str) {
         if let Ok(path) = HeaderValue::from_str(path) {
             *self.0.status_mut() = StatusCode::FOUND;
             self.0.headers_mut().insert(LOCATION, path);
         }
     }
+
+    fn cache(
+        self,
+        cache_control: &str,
+        if_none_match: Option<&str>,
+    ) -> impl Future<Output = Result<Self, ServerFnError<CustErr>>> + Send {
+        // Actix is going to keep this on a single thread anyway so it's fine to wrap it
+        // with SendWrapper, which makes it `Send` but will panic if it moves to another thread
+        SendWrapper::new(async move {
+            let (mut res, body) = self.take().into_parts();
+            let body = body::to_bytes(body)
+                .await
+                .map_err(|e| ServerFnError::Response(e.to_string()))?;
+            let etag = cache::etag(&body);
+            let not_modified = if_none_match.is_some_and(|if_none_match| {
+                cache::etag_matches(if_none_match, &etag)
+            });
+            let headers = res.headers_mut();
+            headers.insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(cache_control)
+                    .map_err(|e| ServerFnError::Response(e.to_string()))?,
+            );
+            headers.insert(
+                header::ETAG,
+                HeaderValue::from_str(&etag)
+                    .map_err(|e| ServerFnError::Response(e.to_string()))?,
+            );
+            let res = if not_modified {
+                *res.status_mut() = StatusCode::NOT_MODIFIED;
+                res.headers_mut().remove(header::CONTENT_TYPE);
+                res.set_body(BoxBody::new(()))
+            } else {
+                res.set_body(BoxBody::new(body))
+            };
+            Ok(ActixResponse::from(res))
+        })
+    }
 }
@@ -1,5 +1,8 @@
 use super::ClientRes;
-use crate::{error::ServerFnError, redirect::REDIRECT_HEADER};
+use crate::{
+    error::ServerFnError, redirect::REDIRECT_HEADER,
+    request::browser::AbortOnDrop,
+};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 pub use gloo_net::http::Response;
@@ -10,7 +13,32 @@ use wasm_bindgen::JsCast;
 use wasm_streams::ReadableStream;
 
 /// The response to a `fetch` request made in the browser.
-pub struct BrowserResponse(pub(crate) SendWrapper<Response>);
+///
+/// The request is still aborted if the response is dropped before its body
+/// has been read.
+pub struct BrowserResponse(
+    pub(crate) SendWrapper<Response>,
+    pub(crate) SendWrapper<Option<AbortOnDrop>>,
+);
+
+impl BrowserResponse {
+    pub(crate) fn new(res: Response, abort_ctrl: Option<AbortOnDrop>) -> Self {
+        Self(SendWrapper::new(res), SendWrapper::new(abort_ctrl))
+    }
+
+    /// Disarms the abort guard, because the body has been read.
+    fn finish(self) {
+        if let Some(ctrl) = self.1.take() {
+            ctrl.run();
+        }
+    }
+}
+
+impl From<Response> for BrowserResponse {
+    fn from(value: Response) -> Self {
+        Self::new(value, None)
+    }
+}
 
 impl<CustErr> ClientRes<CustErr> for BrowserResponse {
     fn try_into_string(
@@ -20,10 +48,13 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
+            let text = self
+                .0
                 .text()
                 .await
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()));
+            self.finish();
+            text
         })
     }
 
@@ -34,11 +65,14 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         // the browser won't send this async work between threads (because it's single-threaded)
         // so we can safely wrap this
         SendWrapper::new(async move {
-            self.0
+            let bytes = self
+                .0
                 .binary()
                 .await
                 .map(Bytes::from)
-                .map_err(|e| ServerFnError::Deserialization(e.to_string()))
+                .map_err(|e| ServerFnError::Deserialization(e.to_string()));
+            self.finish();
+            bytes
         })
     }
 
@@ -48,9 +82,13 @@ impl<CustErr> ClientRes<CustErr> for BrowserResponse {
         impl Stream<Item = Result<Bytes, ServerFnError>> + Send + 'static,
         ServerFnError<CustErr>,
     > {
+        // the guard moves into the stream, so that dropping the stream before
+        // it has finished aborts the request
+        let abort_ctrl = self.1.take();
         let stream = ReadableStream::from_raw(self.0.body().unwrap())
             .into_stream()
-            .map(|data| {
+            .map(move |data| {
+                let _ = &abort_ctrl;
                 let data = data.unwrap().unchecked_into::<Uint8Array>();
                 let mut buf = Vec::new();
                 let length = data.length();
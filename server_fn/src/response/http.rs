@@ -1,11 +1,16 @@
 use super::Res;
-use crate::error::{
-    ServerFnError, ServerFnErrorErr, ServerFnErrorSerde, SERVER_FN_ERROR_HEADER,
+use crate::{
+    cache,
+    error::{
+        ServerFnError, ServerFnErrorErr, ServerFnErrorSerde,
+        SERVER_FN_ERROR_HEADER,
+    },
 };
 use axum::body::Body;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 use http::{header, HeaderValue, Response, StatusCode};
+use http_body_util::BodyExt;
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
@@ -63,10 +68,60 @@ where
             .unwrap()
     }
 
+    fn encoded_error_response(
+        path: &str,
+        status: StatusCode,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        Response::builder()
+            .status(status)
+            .header(http::header::CONTENT_TYPE, content_type)
+            .header(SERVER_FN_ERROR_HEADER, path)
+            .body(Body::from(data))
+            .unwrap()
+    }
+
     fn redirect(&mut self, path: &str) {
         if let Ok(path) = HeaderValue::from_str(path) {
             self.headers_mut().insert(header::LOCATION, path);
             *self.status_mut() = StatusCode::FOUND;
         }
     }
+
+    async fn cache(
+        self,
+        cache_control: &str,
+        if_none_match: Option<&str>,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        let (mut parts, body) = self.into_parts();
+        let body = body
+            .collect()
+            .await
+            .map_err(|e| ServerFnError::Response(e.to_string()))?
+            .to_bytes();
+        let etag = cache::etag(&body);
+        let not_modified = if_none_match.is_some_and(|if_none_match| {
+            cache::etag_matches(if_none_match, &etag)
+        });
+        let headers = &mut parts.headers;
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_str(cache_control)
+                .map_err(|e| ServerFnError::Response(e.to_string()))?,
+        );
+        headers.insert(
+            header::ETAG,
+            HeaderValue::from_str(&etag)
+                .map_err(|e| ServerFnError::Response(e.to_string()))?,
+        );
+        if not_modified {
+            parts.status = StatusCode::NOT_MODIFIED;
+            headers.remove(header::CONTENT_TYPE);
+            headers.remove(header::CONTENT_LENGTH);
+            Ok(Response::from_parts(parts, Body::empty()))
+        } else {
+            Ok(Response::from_parts(parts, Body::from(body)))
+        }
+    }
 }
@@ -12,6 +12,7 @@ pub mod http;
 pub mod reqwest;
 
 use crate::error::ServerFnError;
+use ::http::StatusCode;
 use bytes::Bytes;
 use futures::Stream;
 use std::future::Future;
@@ -44,8 +45,45 @@ where
     /// Converts an error into a response, with a `500` status code and the error text as its body.
     fn error_response(path: &str, err: &ServerFnError<CustErr>) -> Self;
 
+    /// Converts an error that has already been encoded into a response, with
+    /// the given status code.
+    ///
+    /// By default, this ignores the status code and content type, and sends
+    /// the error as text with [`error_response`](Res::error_response).
+    fn encoded_error_response(
+        path: &str,
+        status: StatusCode,
+        content_type: &str,
+        data: Bytes,
+    ) -> Self {
+        _ = (status, content_type);
+        Self::error_response(
+            path,
+            &ServerFnError::ServerError(
+                String::from_utf8_lossy(&data).into_owned(),
+            ),
+        )
+    }
+
     /// Redirect the response by setting a 302 code and Location header.
     fn redirect(&mut self, path: &str);
+
+    /// Sets the `Cache-Control` header of a successful response, and an
+    /// `ETag` computed from its body. If the `ETag` matches `if_none_match`,
+    /// the body is left out and the status is set to `304 Not Modified`.
+    ///
+    /// By default, the response is returned unchanged, so it is not cached.
+    fn cache(
+        self,
+        cache_control: &str,
+        if_none_match: Option<&str>,
+    ) -> impl Future<Output = Result<Self, ServerFnError<CustErr>>> + Send
+    where
+        Self: Send,
+    {
+        _ = (cache_control, if_none_match);
+        async { Ok(self) }
+    }
 }
 
 /// Represents the response as received by the client.
@@ -108,6 +146,15 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
         unreachable!()
     }
 
+    fn encoded_error_response(
+        _path: &str,
+        _status: StatusCode,
+        _content_type: &str,
+        _data: Bytes,
+    ) -> Self {
+        unreachable!()
+    }
+
     fn try_from_stream(
         _content_type: &str,
         _data: impl Stream<Item = Result<Bytes, ServerFnError<CustErr>>>,
@@ -118,4 +165,12 @@ impl<CustErr> Res<CustErr> for BrowserMockRes {
     fn redirect(&mut self, _path: &str) {
         unreachable!()
     }
+
+    async fn cache(
+        self,
+        _cache_control: &str,
+        _if_none_match: Option<&str>,
+    ) -> Result<Self, ServerFnError<CustErr>> {
+        unreachable!()
+    }
 }
@@ -0,0 +1,74 @@
+#![cfg(feature = "actix")]
+
+use actix_web::{dev, test::TestRequest, web::Payload, FromRequest};
+use bytes::Bytes;
+use futures::{executor::block_on, stream, FutureExt, Stream, StreamExt};
+use server_fn::{
+    error::NoCustomError,
+    request::{actix::ActixRequest, Req},
+    ServerFnError,
+};
+use std::pin::Pin;
+
+/// Builds a request whose body arrives in the given chunks.
+fn request(
+    chunks: Vec<Result<Bytes, actix_web::error::PayloadError>>,
+) -> ActixRequest {
+    let req = TestRequest::post().uri("/api/upload").to_http_request();
+    let mut payload = dev::Payload::Stream {
+        payload: Box::pin(stream::iter(chunks))
+            as Pin<Box<dyn Stream<Item = _>>>,
+    };
+    let payload = Payload::from_request(&req, &mut payload)
+        .now_or_never()
+        .unwrap()
+        .unwrap();
+    ActixRequest::from((req, payload))
+}
+
+fn read(req: ActixRequest) -> Vec<Result<Bytes, ServerFnError>> {
+    let stream = Req::<NoCustomError>::try_into_stream(req).unwrap();
+    block_on(stream.collect())
+}
+
+#[test]
+fn bodies_are_streamed_chunk_by_chunk() {
+    let chunks = read(request(vec![
+        Ok(Bytes::from_static(b"hello ")),
+        Ok(Bytes::from_static(b"world")),
+    ]));
+    assert_eq!(
+        chunks,
+        vec![
+            Ok(Bytes::from_static(b"hello ")),
+            Ok(Bytes::from_static(b"world"))
+        ]
+    );
+}
+
+#[test]
+fn errors_in_the_body_are_streamed() {
+    let chunks = read(request(vec![
+        Ok(Bytes::from_static(b"hello")),
+        Err(actix_web::error::PayloadError::Incomplete(None)),
+    ]));
+    assert_eq!(chunks[0], Ok(Bytes::from_static(b"hello")));
+    assert!(
+        matches!(chunks[1], Err(ServerFnError::Deserialization(_))),
+        "{chunks:?}"
+    );
+}
+
+#[test]
+fn streamed_bodies_are_limited() {
+    let req = request(vec![
+        Ok(Bytes::from_static(b"hello ")),
+        Ok(Bytes::from_static(b"world")),
+    ]);
+    let chunks = read(Req::<NoCustomError>::limit_body(req, 8));
+    assert_eq!(chunks[0], Ok(Bytes::from_static(b"hello ")));
+    assert!(
+        matches!(chunks[1], Err(ServerFnError::Deserialization(_))),
+        "{chunks:?}"
+    );
+}
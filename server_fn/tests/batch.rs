@@ -0,0 +1,188 @@
+#![cfg(feature = "axum-no-default")]
+
+use axum::body::Body;
+use futures::executor::block_on;
+use http::{Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use server_fn::{
+    axum::handle_batch,
+    batch::{
+        BatchCall, BatchResponse, BATCH_PATH, MAX_BATCH_CALLS, MAX_BATCH_SIZE,
+    },
+};
+
+fn call(path: &str, body: &'static str) -> BatchCall {
+    BatchCall {
+        method: "POST".to_string(),
+        path: path.to_string(),
+        headers: vec![("accept".to_string(), path.to_string())],
+        body: body.into(),
+    }
+}
+
+/// Sends the batch to a handler that echoes the path, headers and body of
+/// each call.
+fn send(body: impl Into<Body>) -> Response<Body> {
+    send_req(
+        Request::post(BATCH_PATH)
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost:3000")
+            .header("Cookie", "session=mine")
+            .header("x-batch", "1")
+            .body(body.into())
+            .unwrap(),
+    )
+}
+
+fn send_req(req: Request<Body>) -> Response<Body> {
+    block_on(handle_batch(req, |req| {
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = body.collect().await.unwrap().to_bytes();
+            let mut res = Response::builder()
+                .header("x-path", parts.uri.path())
+                .header("set-cookie", "seen=1");
+            for (name, value) in &parts.headers {
+                res = res.header(format!("x-echo-{name}"), value);
+            }
+            res.body(Body::from(body)).unwrap()
+        })
+    }))
+}
+
+fn responses(res: Response<Body>) -> Vec<BatchResponse> {
+    let body = block_on(res.into_body().collect()).unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn header<'a>(res: &'a BatchResponse, name: &str) -> Option<&'a str> {
+    res.headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[test]
+fn each_call_is_handled_on_its_own() {
+    let calls = vec![call("/api/a", "first"), call("/api/b", "second")];
+    let res = send(serde_json::to_string(&calls).unwrap());
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers().get_all("set-cookie").iter().count(), 2);
+
+    let responses = responses(res);
+    assert_eq!(responses.len(), 2);
+    for (res, path, body) in [
+        (&responses[0], "/api/a", "first"),
+        (&responses[1], "/api/b", "second"),
+    ] {
+        assert_eq!(res.status, 200);
+        assert_eq!(res.body, body);
+        assert_eq!(header(res, "x-path"), Some(path));
+        assert_eq!(header(res, "x-echo-accept"), Some(path));
+        assert_eq!(header(res, "x-echo-x-batch"), Some("1"));
+        // cookies are moved to the response to the batch
+        assert_eq!(header(res, "set-cookie"), None);
+    }
+}
+
+#[test]
+fn batches_cannot_be_nested() {
+    let calls = vec![call(BATCH_PATH, "[]"), call("/api/a", "")];
+    let responses = responses(send(serde_json::to_string(&calls).unwrap()));
+    assert_eq!(responses[0].status, 400);
+    assert_eq!(responses[1].status, 200);
+}
+
+#[test]
+fn batches_with_too_many_calls_are_rejected() {
+    let calls = vec![call("/api/a", ""); MAX_BATCH_CALLS + 1];
+    let res = send(serde_json::to_string(&calls).unwrap());
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let calls = vec![call("/api/a", ""); MAX_BATCH_CALLS];
+    let res = send(serde_json::to_string(&calls).unwrap());
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test]
+fn batches_that_are_too_large_are_rejected() {
+    let res = send(vec![b' '; MAX_BATCH_SIZE + 1]);
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let res = send("not a batch");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn batches_must_be_sent_as_json() {
+    let calls = serde_json::to_string(&[call("/api/a", "")]).unwrap();
+    for content_type in [
+        None,
+        Some("text/plain"),
+        Some("application/x-www-form-urlencoded"),
+    ] {
+        let mut req = Request::post(BATCH_PATH);
+        if let Some(content_type) = content_type {
+            req = req.header("Content-Type", content_type);
+        }
+        let res = send_req(req.body(Body::from(calls.clone())).unwrap());
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    let req = Request::post(BATCH_PATH)
+        .header("Content-Type", "application/json; charset=utf-8")
+        .body(Body::from(calls))
+        .unwrap();
+    assert_eq!(send_req(req).status(), StatusCode::OK);
+}
+
+#[test]
+fn calls_only_set_allowed_headers() {
+    let mut call = call("/api/a", "");
+    call.headers.extend(
+        [
+            ("Content-Type", "application/cbor"),
+            ("Idempotency-Key", "key"),
+            ("Origin", "https://evil.example"),
+            ("Referer", "https://evil.example/"),
+            ("Host", "evil.example"),
+            ("Cookie", "session=stolen"),
+            ("X-Forwarded-For", "10.0.0.1"),
+            ("Forwarded", "for=10.0.0.1"),
+            ("x-batch", "2"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string())),
+    );
+    let responses = responses(send(serde_json::to_string(&[call]).unwrap()));
+    let res = &responses[0];
+    assert_eq!(res.status, 200);
+    assert_eq!(header(res, "x-echo-content-type"), Some("application/cbor"));
+    assert_eq!(header(res, "x-echo-accept"), Some("/api/a"));
+    assert_eq!(header(res, "x-echo-idempotency-key"), Some("key"));
+    // everything else comes from the batch
+    assert_eq!(header(res, "x-echo-host"), Some("localhost:3000"));
+    assert_eq!(header(res, "x-echo-cookie"), Some("session=mine"));
+    assert_eq!(header(res, "x-echo-x-batch"), Some("1"));
+    for name in ["origin", "referer", "x-forwarded-for", "forwarded"] {
+        assert_eq!(header(res, &format!("x-echo-{name}")), None, "{name}");
+    }
+}
+
+#[cfg(feature = "csrf")]
+#[test]
+fn batches_from_other_sites_are_rejected() {
+    let calls = serde_json::to_string(&[call("/api/a", "")]).unwrap();
+    let batch = |origin: &str| {
+        Request::post(BATCH_PATH)
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost:3000")
+            .header("Origin", origin)
+            .body(Body::from(calls.clone()))
+            .unwrap()
+    };
+    let res = send_req(batch("https://evil.example"));
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = send_req(batch("http://localhost:3000"));
+    assert_eq!(res.status(), StatusCode::OK);
+}
@@ -0,0 +1,164 @@
+#![cfg(all(feature = "axum-no-default", feature = "reqwest"))]
+
+use axum::body::Body;
+use futures::{channel::oneshot, executor::block_on, FutureExt};
+use http::{header::CONTENT_LENGTH, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use server_fn::{
+    axum::{handle_server_fn, register_explicit},
+    client::reqwest::ReqwestClient,
+    codec::Json,
+    error::NoCustomError,
+    middleware::{
+        limit::{BodyLimit, ConcurrencyLimit, RateLimit},
+        Layer,
+    },
+    ServerFn, ServerFnError,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Declares a server function that echoes its text, with the given middleware,
+/// the way the `#[server]` macro would.
+macro_rules! server_fn {
+    ($name:ident, $path:literal, $middleware:expr, $body:expr) => {
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        struct $name {
+            text: String,
+        }
+
+        impl ServerFn for $name {
+            const PATH: &'static str = $path;
+
+            type Client = ReqwestClient;
+            type ServerRequest = Request<Body>;
+            type ServerResponse = Response<Body>;
+            type Output = String;
+            type InputEncoding = Json;
+            type OutputEncoding = Json;
+            type Error = NoCustomError;
+
+            fn middlewares(
+            ) -> Vec<Arc<dyn Layer<Request<Body>, Response<Body>>>> {
+                vec![Arc::new($middleware)]
+            }
+
+            async fn run_body(self) -> Result<String, ServerFnError> {
+                $body.await;
+                Ok(self.text)
+            }
+        }
+    };
+}
+
+server_fn!(
+    Rated,
+    "/api/rated",
+    RateLimit::global(2, Duration::from_secs(3600)),
+    async {}
+);
+
+server_fn!(
+    PerKey,
+    "/api/per_key",
+    RateLimit::per_header(1, Duration::from_secs(3600), "x-api-key"),
+    async {}
+);
+
+server_fn!(Small, "/api/small", BodyLimit::new(32), async {});
+
+static RELEASE: Mutex<Option<oneshot::Receiver<()>>> = Mutex::new(None);
+
+server_fn!(Slow, "/api/slow", ConcurrencyLimit::new(1), async {
+    let release = RELEASE.lock().unwrap().take();
+    if let Some(release) = release {
+        _ = release.await;
+    }
+});
+
+fn request(path: &str, text: &str) -> Request<Body> {
+    let body =
+        serde_json::to_string(&serde_json::json!({ "text": text })).unwrap();
+    Request::post(path)
+        .header("Content-Type", "application/json")
+        .header(CONTENT_LENGTH, body.len())
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[test]
+fn rate_limits_count_every_request() {
+    register_explicit::<Rated>();
+
+    for _ in 0..2 {
+        let res = block_on(handle_server_fn(request("/api/rated", "hi")));
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    let res = block_on(handle_server_fn(request("/api/rated", "hi")));
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[test]
+fn rate_limits_count_each_client_on_its_own() {
+    register_explicit::<PerKey>();
+    let with_key = |key: &str| {
+        let mut req = request("/api/per_key", "hi");
+        req.headers_mut().insert("x-api-key", key.parse().unwrap());
+        req
+    };
+
+    let res = block_on(handle_server_fn(with_key("a")));
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = block_on(handle_server_fn(with_key("b")));
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = block_on(handle_server_fn(with_key("a")));
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // a request without a key is not counted against a shared bucket
+    for _ in 0..2 {
+        let res = block_on(handle_server_fn(request("/api/per_key", "hi")));
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[test]
+fn large_bodies_are_rejected() {
+    register_explicit::<Small>();
+
+    let res = block_on(handle_server_fn(request("/api/small", "hi")));
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res =
+        block_on(handle_server_fn(request("/api/small", &"a".repeat(64))));
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // without a Content-Length, reading the body fails once it is too large
+    let req = Request::post("/api/small")
+        .header("Content-Type", "application/json")
+        .body(Body::from(format!(r#"{{"text":"{}"}}"#, "a".repeat(64))))
+        .unwrap();
+    let res = block_on(handle_server_fn(req));
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn concurrent_calls_over_the_limit_are_rejected() {
+    register_explicit::<Slow>();
+    let (release, rx) = oneshot::channel();
+    *RELEASE.lock().unwrap() = Some(rx);
+
+    // the first call waits until it is released
+    let mut first = Box::pin(handle_server_fn(request("/api/slow", "first")));
+    assert!((&mut first).now_or_never().is_none());
+
+    let res = block_on(handle_server_fn(request("/api/slow", "second")));
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    release.send(()).unwrap();
+    assert_eq!(block_on(first).status(), StatusCode::OK);
+
+    let res = block_on(handle_server_fn(request("/api/slow", "third")));
+    assert_eq!(res.status(), StatusCode::OK);
+}
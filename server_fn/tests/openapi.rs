@@ -0,0 +1,143 @@
+#![cfg(feature = "openapi")]
+
+// the path that the default macros refer to
+extern crate server_fn as server_fns;
+
+use http::Method;
+use serde_json::json;
+use server_fn::{
+    error::NoCustomError,
+    openapi::{openapi_spec, Schema, ServerFnSpec},
+    ServerFnError,
+};
+
+#[allow(dead_code)]
+#[derive(Schema)]
+#[serde(rename_all = "camelCase")]
+struct Todo {
+    id: u32,
+    due_date: Option<String>,
+    #[serde(rename = "text")]
+    title: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(skip)]
+    cached: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Schema)]
+struct Wrapper<T>(T);
+
+#[allow(dead_code)]
+#[derive(Schema)]
+enum TodoError {
+    Forbidden,
+    #[serde(rename = "gone")]
+    Deleted,
+    NotFound(u32),
+    Invalid {
+        field: String,
+    },
+}
+
+#[test]
+fn structs_follow_serde_attributes() {
+    assert_eq!(
+        Todo::schema(),
+        json!({
+            "type": "object",
+            "properties": {
+                "id": u32::schema(),
+                "dueDate": Option::<String>::schema(),
+                "text": String::schema(),
+                "tags": Vec::<String>::schema(),
+            },
+            "required": ["id", "text"],
+        })
+    );
+    assert_eq!(Wrapper::<bool>::schema(), bool::schema());
+}
+
+#[test]
+fn enums_are_externally_tagged() {
+    assert_eq!(
+        TodoError::schema(),
+        json!({
+            "oneOf": [
+                { "type": "string", "enum": ["Forbidden", "gone"] },
+                {
+                    "type": "object",
+                    "properties": { "NotFound": u32::schema() },
+                    "required": ["NotFound"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Invalid": {
+                            "type": "object",
+                            "properties": { "field": String::schema() },
+                            "required": ["field"],
+                        }
+                    },
+                    "required": ["Invalid"],
+                    "additionalProperties": false,
+                },
+            ]
+        })
+    );
+}
+
+server_fn::inventory::submit! {
+    ServerFnSpec {
+        name: "get_todo",
+        path: "/api/get_todo",
+        method: Method::POST,
+        input_content_type: "application/json",
+        output_content_type: "application/json",
+        docs: "",
+        input: || json!({}),
+        output: Todo::schema,
+        typed_error: None,
+    }
+}
+
+server_fn::inventory::submit! {
+    ServerFnSpec {
+        name: "delete_todo",
+        path: "/api/delete_todo",
+        method: Method::POST,
+        input_content_type: "application/json",
+        output_content_type: "application/json",
+        docs: "",
+        input: || json!({}),
+        output: <()>::schema,
+        typed_error: Some(ServerFnError::<TodoError>::schema),
+    }
+}
+
+#[test]
+fn typed_errors_use_the_output_encoding() {
+    let spec = openapi_spec("Todos", "1.0.0");
+
+    let responses = &spec["paths"]["/api/get_todo"]["post"]["responses"];
+    assert_eq!(
+        responses["500"]["content"]["text/plain"]["schema"]["$ref"],
+        "#/components/schemas/ServerFnError"
+    );
+
+    let responses = &spec["paths"]["/api/delete_todo"]["post"]["responses"];
+    assert!(responses.get("500").is_none());
+    let error = &responses["default"]["content"]["application/json"]["schema"];
+    assert_eq!(*error, ServerFnError::<TodoError>::schema());
+    assert_eq!(
+        error["oneOf"][0]["properties"]["WrappedServerError"],
+        TodoError::schema()
+    );
+    assert_eq!(
+        ServerFnError::<NoCustomError>::schema()["oneOf"][0]["properties"]
+            ["WrappedServerError"],
+        json!({ "type": "null" })
+    );
+}
@@ -0,0 +1,153 @@
+#![cfg(all(
+    feature = "protobuf",
+    feature = "axum-no-default",
+    feature = "reqwest"
+))]
+
+use axum::body::Body;
+use futures::executor::block_on;
+use http::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Request, Response,
+};
+use http_body_util::BodyExt;
+use server_fn::{
+    client::set_server_url,
+    codec::{
+        Encoding, FromReq, FromRes, IntoReq, IntoRes, MessageEncoding, Protobuf,
+    },
+    error::NoCustomError,
+    prost::Message,
+    ServerFnError,
+};
+
+#[derive(Clone, PartialEq, Message)]
+struct Todo {
+    #[prost(uint32, tag = "1")]
+    id: u32,
+    #[prost(string, tag = "2")]
+    title: String,
+    #[prost(string, repeated, tag = "3")]
+    tags: Vec<String>,
+    #[prost(message, optional, tag = "4")]
+    owner: Option<User>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct User {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+/// A message whose first field says it is longer than the message.
+const INVALID: &[u8] = &[0x0a, 0x10, 0x01];
+
+fn todo() -> Todo {
+    Todo {
+        id: 7,
+        title: "Buy milk".to_string(),
+        tags: vec!["home".to_string(), "urgent".to_string()],
+        owner: Some(User {
+            name: "Ada".to_string(),
+        }),
+    }
+}
+
+/// Hands the request to the server, as if it had been sent over the network.
+fn receive_req(req: reqwest::Request) -> Request<Body> {
+    let body = req
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap()
+        .to_vec();
+    let mut received = Request::post(req.url().path())
+        .body(Body::from(body))
+        .unwrap();
+    *received.headers_mut() = req.headers().clone();
+    received
+}
+
+/// Hands the response to the client, as if it had been sent over the network.
+fn receive_res(res: Response<Body>) -> reqwest::Response {
+    let (parts, body) = res.into_parts();
+    let body = block_on(body.collect()).unwrap().to_bytes();
+    Response::from_parts(parts, body).into()
+}
+
+#[test]
+fn messages_are_sent_to_the_server() {
+    set_server_url("http://localhost:3000");
+    let req: reqwest::Request =
+        IntoReq::<Protobuf, _, NoCustomError>::into_req(
+            todo(),
+            "/api/add_todo",
+            Protobuf::CONTENT_TYPE,
+        )
+        .unwrap();
+    assert_eq!(req.method(), Protobuf::METHOD);
+    assert_eq!(req.headers()[CONTENT_TYPE], Protobuf::CONTENT_TYPE);
+    assert_eq!(req.headers()[ACCEPT], Protobuf::CONTENT_TYPE);
+
+    let received =
+        block_on(<Todo as FromReq<Protobuf, _, NoCustomError>>::from_req(
+            receive_req(req),
+        ))
+        .unwrap();
+    assert_eq!(received, todo());
+}
+
+#[test]
+fn messages_are_sent_back_to_the_client() {
+    let res: Response<Body> =
+        block_on(IntoRes::<Protobuf, _, NoCustomError>::into_res(todo()))
+            .unwrap();
+    assert_eq!(res.headers()[CONTENT_TYPE], Protobuf::CONTENT_TYPE);
+
+    let received =
+        block_on(<Todo as FromRes<Protobuf, _, NoCustomError>>::from_res(
+            receive_res(res),
+        ))
+        .unwrap();
+    assert_eq!(received, todo());
+}
+
+#[test]
+fn invalid_messages_are_rejected() {
+    let req = Request::post("/api/add_todo")
+        .header(CONTENT_TYPE, Protobuf::CONTENT_TYPE)
+        .body(Body::from(INVALID))
+        .unwrap();
+    let err =
+        block_on(<Todo as FromReq<Protobuf, _, NoCustomError>>::from_req(req))
+            .unwrap_err();
+    assert!(matches!(err, ServerFnError::Args(_)), "{err:?}");
+
+    let res = Response::builder()
+        .header(CONTENT_TYPE, Protobuf::CONTENT_TYPE)
+        .body(Body::from(INVALID))
+        .unwrap();
+    let err =
+        block_on(<Todo as FromRes<Protobuf, _, NoCustomError>>::from_res(
+            receive_res(res),
+        ))
+        .unwrap_err();
+    assert!(matches!(err, ServerFnError::Deserialization(_)), "{err:?}");
+}
+
+// encoded messages are arbitrary bytes, rather than UTF-8 text
+const _: () = assert!(<Protobuf as MessageEncoding<Todo>>::BINARY);
+
+#[test]
+fn stream_messages_round_trip() {
+    let bytes = <Protobuf as MessageEncoding<Todo>>::encode(&todo()).unwrap();
+    assert_eq!(bytes, todo().encode_to_vec());
+    let decoded: Todo = Protobuf::decode(&bytes).unwrap();
+    assert_eq!(decoded, todo());
+
+    // fields that are left out take their default values
+    let empty: Todo = Protobuf::decode(&[]).unwrap();
+    assert_eq!(empty, Todo::default());
+
+    let invalid: Result<Todo, _> = Protobuf::decode(INVALID);
+    assert!(invalid.is_err());
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "reqwest")]
+
+use server_fn::{
+    client::set_server_url,
+    error::NoCustomError,
+    request::{reqwest::Request, ClientReq},
+};
+use std::sync::Once;
+
+fn post(path: &str) -> Request {
+    static SERVER_URL: Once = Once::new();
+    SERVER_URL.call_once(|| set_server_url("http://localhost:3000"));
+    <Request as ClientReq<NoCustomError>>::try_new_post(
+        path,
+        "application/json",
+        "application/json",
+        "{}".to_string(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn reqwest_requests_can_set_headers() {
+    let req = ClientReq::<NoCustomError>::try_with_header(
+        post("/api/save_note"),
+        "Idempotency-Key",
+        "0123abcd",
+    )
+    .unwrap();
+    assert_eq!(req.headers()["idempotency-key"], "0123abcd");
+
+    // a header that is set again is replaced
+    let req = ClientReq::<NoCustomError>::try_with_header(
+        req,
+        "Idempotency-Key",
+        "x",
+    )
+    .unwrap();
+    assert_eq!(req.headers().get_all("idempotency-key").iter().count(), 1);
+}
+
+#[test]
+fn invalid_headers_are_rejected() {
+    let res = ClientReq::<NoCustomError>::try_with_header(
+        post("/api/save_note"),
+        "Idempotency-Key",
+        "line\nbreak",
+    );
+    assert!(res.is_err());
+}
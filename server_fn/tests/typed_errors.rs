@@ -0,0 +1,107 @@
+#![cfg(all(feature = "axum-no-default", feature = "reqwest"))]
+
+use axum::body::Body;
+use futures::executor::block_on;
+use http::{header::CONTENT_TYPE, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use server_fn::{
+    codec::{Cbor, Json},
+    error::{
+        typed_error_from_res, typed_error_response, ErrorStatus,
+        SERVER_FN_ERROR_HEADER,
+    },
+    response::Res,
+    ServerFnError,
+};
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum TodoError {
+    NotFound { id: u32 },
+    Forbidden,
+}
+
+impl ErrorStatus for TodoError {
+    fn status(&self) -> StatusCode {
+        match self {
+            TodoError::NotFound { .. } => StatusCode::NOT_FOUND,
+            TodoError::Forbidden => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl fmt::Display for TodoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl FromStr for TodoError {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Forbidden" => Ok(TodoError::Forbidden),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Hands the response to the client, as if it had been sent over the network.
+fn receive(res: Response<Body>) -> reqwest::Response {
+    let (parts, body) = res.into_parts();
+    let body = block_on(http_body_util::BodyExt::collect(body))
+        .unwrap()
+        .to_bytes();
+    Response::from_parts(parts, body).into()
+}
+
+#[test]
+fn custom_errors_keep_their_structure() {
+    let err = ServerFnError::WrappedServerError(TodoError::NotFound { id: 7 });
+    let res: Response<Body> =
+        typed_error_response::<Json, _, _>("/api/get_todo", &err);
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(res.headers()[SERVER_FN_ERROR_HEADER], "/api/get_todo");
+
+    let received =
+        block_on(typed_error_from_res::<Json, TodoError, _>(receive(res)));
+    assert_eq!(received, err);
+}
+
+#[test]
+fn other_errors_are_sent_with_their_status() {
+    for (err, status) in [
+        (
+            ServerFnError::<TodoError>::Args("missing id".into()),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            ServerFnError::ServerError("database is down".into()),
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ),
+        (
+            ServerFnError::WrappedServerError(TodoError::Forbidden),
+            StatusCode::FORBIDDEN,
+        ),
+    ] {
+        let res: Response<Body> =
+            typed_error_response::<Cbor, _, _>("/api/get_todo", &err);
+        assert_eq!(res.status(), status);
+        let received =
+            block_on(typed_error_from_res::<Cbor, TodoError, _>(receive(res)));
+        assert_eq!(received, err);
+    }
+}
+
+#[test]
+fn errors_sent_as_text_are_still_read() {
+    // e.g., by a middleware that does not know the output encoding
+    let err =
+        ServerFnError::<TodoError>::WrappedServerError(TodoError::Forbidden);
+    let res: Response<Body> = Res::error_response("/api/get_todo", &err);
+    let received =
+        block_on(typed_error_from_res::<Json, TodoError, _>(receive(res)));
+    assert_eq!(received, err);
+}
@@ -0,0 +1,80 @@
+#![cfg(all(
+    feature = "websocket",
+    feature = "axum-no-default",
+    feature = "reqwest"
+))]
+
+use axum::body::Body;
+use futures::{executor::block_on, Future, StreamExt};
+use http::{Request, Response, StatusCode};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use server_fn::{
+    axum::{handle_server_fn, register_explicit},
+    client::reqwest::ReqwestClient,
+    codec::{Json, Websocket, WebsocketStream},
+    error::{NoCustomError, ServerFnErrorSerde},
+    ServerFn, ServerFnError,
+};
+
+/// Echoes each message it receives in upper case, the way the `#[server]`
+/// macro would declare it.
+#[derive(Debug, Serialize, Deserialize)]
+struct Uppercase {
+    #[serde(skip)]
+    input: Option<WebsocketStream<String>>,
+}
+
+impl From<WebsocketStream<String>> for Uppercase {
+    fn from(input: WebsocketStream<String>) -> Self {
+        Self { input: Some(input) }
+    }
+}
+
+impl From<Uppercase> for WebsocketStream<String> {
+    fn from(value: Uppercase) -> Self {
+        value
+            .input
+            .unwrap_or_else(|| futures::stream::empty().into())
+    }
+}
+
+impl ServerFn for Uppercase {
+    const PATH: &'static str = "/api/uppercase";
+
+    type Client = ReqwestClient;
+    type ServerRequest = Request<Body>;
+    type ServerResponse = Response<Body>;
+    type Output = WebsocketStream<String>;
+    type InputEncoding = Websocket<Json>;
+    type OutputEncoding = Websocket<Json>;
+    type Error = NoCustomError;
+
+    fn execute_on_server(
+        req: Request<Body>,
+    ) -> impl Future<Output = Result<Response<Body>, ServerFnError>> + Send
+    {
+        Websocket::<Json>::run_server::<Self, _, _, _>(req)
+    }
+
+    async fn run_body(self) -> Result<WebsocketStream<String>, ServerFnError> {
+        let input = WebsocketStream::from(self).into_inner();
+        Ok(WebsocketStream::new(
+            input.map(|msg| msg.map(|msg| msg.to_uppercase())),
+        ))
+    }
+}
+
+#[test]
+fn requests_that_are_not_websockets_are_rejected() {
+    register_explicit::<Uppercase>();
+
+    let req = Request::get("/api/uppercase").body(Body::empty()).unwrap();
+    let res = block_on(handle_server_fn(req));
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let body = block_on(res.into_body().collect()).unwrap().to_bytes();
+    let err =
+        ServerFnError::<NoCustomError>::de(&String::from_utf8_lossy(&body));
+    assert!(matches!(err, ServerFnError::Request(_)), "{err:?}");
+}
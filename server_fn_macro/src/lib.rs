@@ -10,6 +10,7 @@ use convert_case::{Case, Converter};
 use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use quote::{quote, quote_spanned, ToTokens};
 use syn::{
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
@@ -55,6 +56,24 @@ pub fn server_macro_impl(
         }
     });
 
+    // extract all #[interceptor] attributes in the same way
+    let mut interceptors: Vec<Middleware> = vec![];
+    body.attrs.retain(|attr| {
+        if attr.meta.path().is_ident("interceptor") {
+            if let Ok(interceptor) = attr.parse_args() {
+                interceptors.push(interceptor);
+                false
+            } else {
+                true
+            }
+        } else {
+            true
+        }
+    });
+
+    // the name, type and whether each argument has a default, for the
+    // OpenAPI document
+    let mut arg_specs = Vec::new();
     let fields = body
         .inputs
         .iter_mut()
@@ -95,6 +114,11 @@ pub fn server_macro_impl(
                 })?;
             }
             typed_arg.attrs = other_attrs;
+            let name = match &*typed_arg.pat {
+                Pat::Ident(ident) => ident.ident.to_string(),
+                pat => pat.to_token_stream().to_string(),
+            };
+            arg_specs.push((name, typed_arg.ty.clone(), default));
             if default {
                 Ok(quote! { #[serde(default)] pub #typed_arg })
             } else {
@@ -121,6 +145,11 @@ pub fn server_macro_impl(
         client,
         custom_wrapper,
         impl_from,
+        typed_errors,
+        retries,
+        timeout_ms,
+        client_policy,
+        cache,
     } = args;
     let prefix = prefix.unwrap_or_else(|| Literal::string(default_path));
     let fn_path = fn_path.unwrap_or_else(|| Literal::string(""));
@@ -144,6 +173,70 @@ pub fn server_macro_impl(
                 #server_fn_path::codec::PostUrl
             }
         });
+    let output_ident = match &output {
+        Some(Type::Path(path)) => {
+            path.path.segments.last().map(|seg| seg.ident.to_string())
+        }
+        _ => None,
+    };
+    // websocket server functions use the same protocol in both directions
+    let is_websocket = input_ident.as_deref() == Some("Websocket");
+    let is_event_stream = output_ident.as_deref() == Some("ServerSentEvents");
+    // only the responses to `GET` requests can be cached, and the whole body
+    // is read to compute its `ETag`, so streamed output can't be
+    if let Some(cache) = &cache {
+        let post_input = matches!(
+            input_ident.as_deref(),
+            Some(
+                "PostUrl"
+                    | "Json"
+                    | "Cbor"
+                    | "Rkyv"
+                    | "MsgPack"
+                    | "Protobuf"
+                    | "SerdeLite"
+                    | "MultipartFormData"
+                    | "Streaming"
+                    | "StreamingText"
+                    | "StreamingMultipart"
+                    | "Websocket"
+            )
+        );
+        if post_input {
+            return Err(syn::Error::new(
+                cache.span(),
+                "`cache` can only be used with an input encoding that uses \
+                 `GET`, like `GetUrl`",
+            ));
+        }
+        let streamed_output = matches!(
+            output_ident.as_deref(),
+            Some("Streaming" | "StreamingText" | "ServerSentEvents")
+        );
+        if streamed_output {
+            return Err(syn::Error::new(
+                cache.span(),
+                "`cache` can't be used with a streamed output encoding",
+            ));
+        }
+    }
+    // streamed arguments and output can't be sent as part of a batch
+    let batchable = !matches!(
+        input_ident.as_deref(),
+        Some(
+            "MultipartFormData"
+                | "Streaming"
+                | "StreamingText"
+                | "StreamingMultipart"
+                | "Websocket"
+        )
+    ) && !matches!(
+        output_ident.as_deref(),
+        Some("Streaming" | "StreamingText" | "ServerSentEvents" | "Websocket")
+    );
+    // nor can cached responses, which caches on the way to the server would
+    // not see in a batch
+    let batchable = batchable && cache.is_none();
     let output = output
         .map(|n| {
             if builtin_encoding {
@@ -153,8 +246,12 @@ pub fn server_macro_impl(
             }
         })
         .unwrap_or_else(|| {
-            quote! {
-                #server_fn_path::codec::Json
+            if is_websocket {
+                input.clone()
+            } else {
+                quote! {
+                    #server_fn_path::codec::Json
+                }
             }
         });
     // default to PascalCase version of function name if no struct name given
@@ -231,6 +328,53 @@ pub fn server_macro_impl(
             }
         });
 
+    // a protobuf request body is the message that is the only argument, so
+    // that it can be shared with other services that use the same messages
+    let protobuf_impl = if input_ident.as_deref() == Some("Protobuf") {
+        let Some((name, _)) = first_field.filter(|_| body.inputs.len() == 1)
+        else {
+            return Err(syn::Error::new(
+                body.inputs.span(),
+                "the `Protobuf` input encoding requires a single argument \
+                 that implements `prost::Message`",
+            ));
+        };
+        let prost = quote! { #server_fn_path::prost };
+        Some(quote! {
+            impl #prost::Message for #struct_name {
+                fn encode_raw(&self, buf: &mut impl #prost::bytes::BufMut) {
+                    #prost::Message::encode_raw(&self.#name, buf)
+                }
+
+                fn merge_field(
+                    &mut self,
+                    tag: u32,
+                    wire_type: #prost::encoding::WireType,
+                    buf: &mut impl #prost::bytes::Buf,
+                    ctx: #prost::encoding::DecodeContext,
+                ) -> Result<(), #prost::DecodeError> {
+                    #prost::Message::merge_field(
+                        &mut self.#name,
+                        tag,
+                        wire_type,
+                        buf,
+                        ctx,
+                    )
+                }
+
+                fn encoded_len(&self) -> usize {
+                    #prost::Message::encoded_len(&self.#name)
+                }
+
+                fn clear(&mut self) {
+                    #prost::Message::clear(&mut self.#name)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
     // check output type
     let output_arrow = body.output_arrow;
     let return_ty = body.return_ty;
@@ -297,6 +441,56 @@ pub fn server_macro_impl(
         quote! {}
     };
 
+    // registration for the OpenAPI document, which does nothing unless
+    // server_fn's `openapi` feature is enabled
+    let openapi = if cfg!(feature = "ssr") {
+        let args = arg_specs.iter().map(|(name, ty, default)| {
+            quote! {
+                (#name, (&SchemaOf::<#ty>::new()).schema(), #default)
+            }
+        });
+        let typed_error = if typed_errors.as_ref().is_some_and(|v| v.value) {
+            quote! {
+                Some(|| {
+                    use #server_fn_path::openapi::{SchemaOf, ViaAny as _, ViaSchema as _};
+                    (&SchemaOf::<#server_fn_path::ServerFnError<#error_ty>>::new()).schema()
+                })
+            }
+        } else {
+            quote! { None }
+        };
+        let fn_docs = body
+            .docs
+            .iter()
+            .map(|(doc, _)| doc.strip_prefix(' ').unwrap_or(doc))
+            .collect::<Vec<_>>()
+            .join("\n");
+        quote! {
+            #server_fn_path::__register_openapi! {{
+                use #server_fn_path::{ServerFn, codec::Encoding};
+                #server_fn_path::openapi::ServerFnSpec {
+                    name: #fn_name_as_str,
+                    path: #wrapped_struct_name_turbofish::PATH,
+                    method: <#wrapped_struct_name as ServerFn>::InputEncoding::METHOD,
+                    input_content_type: <<#wrapped_struct_name as ServerFn>::InputEncoding as Encoding>::CONTENT_TYPE,
+                    output_content_type: <<#wrapped_struct_name as ServerFn>::OutputEncoding as Encoding>::CONTENT_TYPE,
+                    docs: #fn_docs,
+                    input: || {
+                        use #server_fn_path::openapi::{SchemaOf, ViaAny as _, ViaSchema as _};
+                        #server_fn_path::openapi::object_schema(vec![#(#args),*])
+                    },
+                    output: || {
+                        use #server_fn_path::openapi::{SchemaOf, ViaAny as _, ViaSchema as _};
+                        (&SchemaOf::<#output_ty>::new()).schema()
+                    },
+                    typed_error: #typed_error,
+                }
+            }}
+        }
+    } else {
+        quote! {}
+    };
+
     // run_body in the trait implementation
     let run_body = if cfg!(feature = "ssr") {
         let destructure = if let Some(wrapper) = custom_wrapper.as_ref() {
@@ -349,6 +543,43 @@ pub fn server_macro_impl(
         }
     };
 
+    // websocket server functions replace the request/response cycle with a
+    // websocket connection, on both the client and the server
+    let websocket = is_websocket.then(|| {
+        quote! {
+            fn execute_on_server(
+                req: Self::ServerRequest,
+            ) -> impl std::future::Future<
+                Output = Result<Self::ServerResponse, #server_fn_path::ServerFnError<Self::Error>>,
+            > + Send {
+                <#input>::run_server::<Self, _, _, _>(req)
+            }
+
+            fn run_on_client(
+                self,
+            ) -> impl std::future::Future<
+                Output = Result<Self::Output, #server_fn_path::ServerFnError<Self::Error>>,
+            > + Send {
+                <#input>::run_client::<Self, _, _, _>(self)
+            }
+        }
+    });
+
+    // server-sent events are received in whichever way the client supports
+    // best, which might not be an ordinary response
+    let event_stream = is_event_stream.then(|| {
+        quote! {
+            fn run_on_client_with_req(
+                req: <Self::Client as #server_fn_path::client::Client<Self::Error>>::Request,
+                _redirect_hook: Option<&#server_fn_path::redirect::RedirectHook>,
+            ) -> impl std::future::Future<
+                Output = Result<Self::Output, #server_fn_path::ServerFnError<Self::Error>>,
+            > + Send {
+                <#output>::run_client::<Self, _, _>(req)
+            }
+        }
+    });
+
     // the actual function definition
     let func = if cfg!(feature = "ssr") {
         quote! {
@@ -395,8 +626,12 @@ pub fn server_macro_impl(
             },
         ),
         Some("MultipartFormData")
+        | Some("StreamingMultipart")
         | Some("Streaming")
-        | Some("StreamingText") => (PathInfo::None, quote! {}),
+        | Some("StreamingText")
+        | Some("Websocket") => (PathInfo::None, quote! {}),
+        // the single argument is the message, see `protobuf_impl` above
+        Some("Protobuf") => (PathInfo::None, quote! { Clone, Default }),
         Some("SerdeLite") => (
             PathInfo::Serde,
             quote! {
@@ -539,6 +774,79 @@ pub fn server_macro_impl(
     } else {
         quote! { vec![] }
     };
+    // interceptors only apply when the server function is called over HTTP,
+    // which only happens in the client build
+    let interceptors = if cfg!(feature = "ssr") {
+        quote! { vec![] }
+    } else {
+        quote! {
+            vec![
+                #(
+                    std::sync::Arc::new(#interceptors),
+                ),*
+            ]
+        }
+    };
+
+    // the client policy starts from the one given, or the global one, and
+    // overrides its retries and timeout if they are given
+    let client_policy = (retries.is_some()
+        || timeout_ms.is_some()
+        || client_policy.is_some())
+    .then(|| {
+        let policy = client_policy
+            .map(|policy| policy.to_token_stream())
+            .unwrap_or_else(|| {
+                quote! { #server_fn_path::client::get_client_policy() }
+            });
+        let retries = retries.map(|retries| {
+            quote! { policy.retries = #retries; }
+        });
+        let timeout = timeout_ms.map(|timeout_ms| {
+            quote! {
+                policy.timeout = Some(std::time::Duration::from_millis(#timeout_ms));
+            }
+        });
+        quote! {
+            fn client_policy() -> #server_fn_path::client::ClientPolicy {
+                #[allow(unused_mut)]
+                let mut policy: #server_fn_path::client::ClientPolicy = #policy;
+                #retries
+                #timeout
+                policy
+            }
+        }
+    });
+
+    let cache = cache.map(|cache| {
+        quote! {
+            fn cache_policy() -> Option<#server_fn_path::cache::CachePolicy> {
+                Some(#cache)
+            }
+        }
+    });
+
+    // typed errors are encoded with the output encoding, rather than as text
+    let typed_errors = typed_errors.is_some_and(|v| v.value).then(|| {
+        quote! {
+            fn error_response(
+                err: &#server_fn_path::ServerFnError<#error_ty>,
+            ) -> #res {
+                #server_fn_path::error::typed_error_response::<#output, _, _>(
+                    Self::PATH,
+                    err,
+                )
+            }
+
+            fn error_from_res(
+                res: <#client as #server_fn_path::client::Client<#error_ty>>::Response,
+            ) -> impl std::future::Future<
+                Output = #server_fn_path::ServerFnError<#error_ty>,
+            > + Send {
+                #server_fn_path::error::typed_error_from_res::<#output, _, _>(res)
+            }
+        }
+    });
 
     Ok(quote::quote! {
         #args_docs
@@ -551,8 +859,11 @@ pub fn server_macro_impl(
 
         #from_impl
 
+        #protobuf_impl
+
         impl #server_fn_path::ServerFn for #wrapped_struct_name {
             const PATH: &'static str = #path;
+            const BATCHABLE: bool = #batchable;
 
             type Client = #client;
             type ServerRequest = #req;
@@ -566,11 +877,27 @@ pub fn server_macro_impl(
                 #middlewares
             }
 
+            fn client_interceptors() -> Vec<#server_fn_path::client::ClientInterceptor<#client, #error_ty>> {
+                #interceptors
+            }
+
+            #client_policy
+
+            #cache
+
+            #typed_errors
+
             #run_body
+
+            #websocket
+
+            #event_stream
         }
 
         #inventory
 
+        #openapi
+
         #func
 
         #dummy
@@ -689,6 +1016,11 @@ struct ServerFnArgs {
     custom_wrapper: Option<Path>,
     builtin_encoding: bool,
     impl_from: Option<LitBool>,
+    typed_errors: Option<LitBool>,
+    retries: Option<LitInt>,
+    timeout_ms: Option<LitInt>,
+    client_policy: Option<Expr>,
+    cache: Option<Expr>,
 }
 
 impl Parse for ServerFnArgs {
@@ -708,6 +1040,11 @@ impl Parse for ServerFnArgs {
         let mut client: Option<Type> = None;
         let mut custom_wrapper: Option<Path> = None;
         let mut impl_from: Option<LitBool> = None;
+        let mut typed_errors: Option<LitBool> = None;
+        let mut retries: Option<LitInt> = None;
+        let mut timeout_ms: Option<LitInt> = None;
+        let mut client_policy: Option<Expr> = None;
+        let mut cache: Option<Expr> = None;
 
         let mut use_key_and_value = false;
         let mut arg_pos = 0;
@@ -831,6 +1168,46 @@ impl Parse for ServerFnArgs {
                             ));
                         }
                         impl_from = Some(stream.parse()?);
+                    } else if key == "typed_errors" {
+                        if typed_errors.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `typed_errors`",
+                            ));
+                        }
+                        typed_errors = Some(stream.parse()?);
+                    } else if key == "retries" {
+                        if retries.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `retries`",
+                            ));
+                        }
+                        retries = Some(stream.parse()?);
+                    } else if key == "timeout_ms" {
+                        if timeout_ms.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `timeout_ms`",
+                            ));
+                        }
+                        timeout_ms = Some(stream.parse()?);
+                    } else if key == "client_policy" {
+                        if client_policy.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `client_policy`",
+                            ));
+                        }
+                        client_policy = Some(stream.parse()?);
+                    } else if key == "cache" {
+                        if cache.is_some() {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                "keyword argument repeated: `cache`",
+                            ));
+                        }
+                        cache = Some(stream.parse()?);
                     } else {
                         return Err(lookahead.error());
                     }
@@ -928,6 +1305,11 @@ impl Parse for ServerFnArgs {
             client,
             custom_wrapper,
             impl_from,
+            typed_errors,
+            retries,
+            timeout_ms,
+            client_policy,
+            cache,
         })
     }
 }
@@ -1041,3 +1423,203 @@ impl ServerFnBody {
         }
     }
 }
+
+/// The implementation of the `Schema` derive macro, which describes a type as
+/// a JSON schema for the OpenAPI document.
+///
+/// The schema follows the way `serde` serializes the type by default, and
+/// respects its `rename`, `rename_all`, `default` and `skip` attributes.
+/// Every type parameter is required to implement `Schema` itself. Nothing is
+/// generated unless server_fn's `openapi` feature is enabled.
+pub fn schema_derive_impl(
+    input: TokenStream2,
+    server_fn_path: Option<Path>,
+) -> Result<TokenStream2> {
+    let mut input: DeriveInput = syn::parse2(input)?;
+    let server_fn_path = server_fn_path
+        .map(|path| quote!(#path))
+        .unwrap_or_else(|| quote! { server_fn });
+    let openapi = quote! { #server_fn_path::openapi };
+    let rename_all = SerdeAttrs::parse(&input.attrs)?.rename_all;
+
+    let schema = match &input.data {
+        Data::Struct(data) => {
+            fields_schema(&openapi, &data.fields, rename_all)?
+        }
+        Data::Enum(data) => {
+            let variants = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let attrs = SerdeAttrs::parse(&variant.attrs)?;
+                    if attrs.skip {
+                        return Ok(None);
+                    }
+                    let name = attrs.rename.unwrap_or_else(|| {
+                        rename(&variant.ident.to_string(), rename_all)
+                    });
+                    let rename_all = attrs.rename_all;
+                    let schema = match &variant.fields {
+                        Fields::Unit => quote! { None },
+                        fields => {
+                            let schema =
+                                fields_schema(&openapi, fields, rename_all)?;
+                            quote! { Some(#schema) }
+                        }
+                    };
+                    Ok(Some(quote! { (#name, #schema) }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let variants = variants.into_iter().flatten();
+            quote! { #openapi::enum_schema(vec![#(#variants),*]) }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span(),
+                "`Schema` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let type_params = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+    let where_clause = input.generics.make_where_clause();
+    for param in type_params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: #openapi::Schema));
+    }
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+    // does nothing unless server_fn's `openapi` feature is enabled
+    Ok(quote! {
+        #server_fn_path::__impl_schema! {
+            impl #impl_generics #openapi::Schema for #ident #ty_generics #where_clause {
+                fn schema() -> #openapi::Value {
+                    use #openapi::{SchemaOf, ViaAny as _, ViaSchema as _};
+                    #schema
+                }
+            }
+        }
+    })
+}
+
+/// Builds the schema of the fields of a struct, or of an enum variant.
+fn fields_schema(
+    openapi: &TokenStream2,
+    fields: &Fields,
+    rename_all: Option<Case>,
+) -> Result<TokenStream2> {
+    let mut schemas = Vec::new();
+    for field in fields {
+        let attrs = SerdeAttrs::parse(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        let schema = quote! { (&SchemaOf::<#ty>::new()).schema() };
+        schemas.push(match &field.ident {
+            Some(ident) => {
+                let name = attrs.rename.unwrap_or_else(|| {
+                    rename(&ident.unraw().to_string(), rename_all)
+                });
+                let default = attrs.default;
+                quote! { (#name, #schema, #default) }
+            }
+            None => schema,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => {
+            quote! { #openapi::object_schema(vec![#(#schemas),*]) }
+        }
+        // a newtype is serialized as the value it wraps
+        Fields::Unnamed(_) if schemas.len() == 1 => quote! { #(#schemas)* },
+        Fields::Unnamed(_) => {
+            quote! { #openapi::tuple_schema(vec![#(#schemas),*]) }
+        }
+        Fields::Unit => quote! { <() as #openapi::Schema>::schema() },
+    })
+}
+
+/// Applies a `serde(rename_all)` rule to the name of a field or variant.
+fn rename(name: &str, rename_all: Option<Case>) -> String {
+    match rename_all {
+        Some(Case::Lower) => name.to_lowercase(),
+        Some(Case::Upper) => name.to_uppercase(),
+        Some(case) => Converter::new().to_case(case).convert(name),
+        None => name.to_string(),
+    }
+}
+
+/// The `serde` attributes that change the shape of the serialized data.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<Case>,
+    default: bool,
+    skip: bool,
+}
+
+impl SerdeAttrs {
+    fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    // `rename(serialize = "..")` is not interpreted
+                    if let Ok(value) = meta.value() {
+                        parsed.rename = Some(value.parse::<LitStr>()?.value());
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                } else if meta.path.is_ident("rename_all") {
+                    if let Ok(value) = meta.value() {
+                        let rule = value.parse::<LitStr>()?;
+                        parsed.rename_all = Some(match rule.value().as_str() {
+                            "lowercase" => Case::Lower,
+                            "UPPERCASE" => Case::Upper,
+                            "PascalCase" => Case::Pascal,
+                            "camelCase" => Case::Camel,
+                            "snake_case" => Case::Snake,
+                            "SCREAMING_SNAKE_CASE" => Case::UpperSnake,
+                            "kebab-case" => Case::Kebab,
+                            "SCREAMING-KEBAB-CASE" => Case::UpperKebab,
+                            _ => {
+                                return Err(Error::new(
+                                    rule.span(),
+                                    "unknown `rename_all` rule",
+                                ))
+                            }
+                        });
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                } else if meta.path.is_ident("default") {
+                    parsed.default = true;
+                    skip_meta(&meta)?;
+                } else if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Skips the value of a `serde` attribute that is not interpreted.
+fn skip_meta(meta: &meta::ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(token::Paren) {
+        meta.parse_nested_meta(|meta| skip_meta(&meta))?;
+    }
+    Ok(())
+}